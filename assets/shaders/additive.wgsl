@@ -0,0 +1,18 @@
+// Textured sprite that adds its color onto what is behind it instead of covering it.
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+
+struct AdditiveMaterial {
+    color: vec4<f32>,
+    // offset in xy and size in zw, in uv space, to draw part of a sheet
+    uv_rect: vec4<f32>,
+};
+
+@group(1) @binding(0) var<uniform> material: AdditiveMaterial;
+@group(1) @binding(1) var base_texture: texture_2d<f32>;
+@group(1) @binding(2) var base_sampler: sampler;
+
+@fragment
+fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
+    let uv = material.uv_rect.xy + mesh.uv * material.uv_rect.zw;
+    return textureSample(base_texture, base_sampler, uv) * material.color;
+}
@@ -0,0 +1,1552 @@
+use std::{collections::VecDeque, f32::consts::PI, time::Duration};
+
+use bevy::prelude::*;
+use bevy_xpbd_2d::prelude::*;
+use rand::Rng;
+
+use crate::{
+    atlas::{set_sprite_color, SpriteAtlas},
+    boss_rush::in_boss_rush,
+    collisions::{apply_damage, Layer},
+    explosion::SpawnExplosionEvent,
+    lifetime::{FadeOut, Lifetime},
+    movement::{IgnoreWrapper, PlayArea},
+    mutators::{Mutator, Mutators},
+    powerup::{maybe_drop_power_up, PowerUpSettings},
+    run::{Difficulty, GameEntity, GameRng, GameRngSet, GameState},
+    score::{Combo, ComboTimer},
+    ship::Ship,
+    weapons::Laser,
+};
+
+/// Spawns, steers, splits and breaks up the asteroid field, wave by wave.
+pub struct AsteroidPlugin;
+
+impl Plugin for AsteroidPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SpawnAsteroidEvent>()
+            .init_resource::<AsteroidSpawnQueue>()
+            .init_resource::<MaxAsteroids>()
+            .init_resource::<FieldBehavior>()
+            .add_systems(Update, steer_asteroids)
+            .init_resource::<WeakSpotSettings>()
+            .init_resource::<AsteroidSpinSettings>()
+            .init_resource::<AsteroidColliderSettings>()
+            .add_systems(
+                Update,
+                asteroid_spawner
+                    .in_set(GameRngSet::Spawn)
+                    .run_if(not(in_state(GameState::Paused))),
+            )
+            .init_resource::<WaveManager>()
+            .add_event::<WaveStartedEvent>()
+            .add_systems(
+                Update,
+                wave_spawner
+                    .in_set(GameRngSet::Waves)
+                    .before(asteroid_spawner)
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(not(in_boss_rush)),
+            )
+            .init_resource::<SpawnRampSettings>()
+            .add_systems(Update, update_spawn_ramps.after(asteroid_spawner))
+            .add_event::<AsteroidHitEvent>()
+            .add_systems(Update, shade_damaged_asteroids.after(apply_damage))
+            .init_resource::<DustSettings>()
+            .add_systems(
+                Update,
+                (begin_fracture, animate_fracture)
+                    .chain()
+                    .after(apply_damage)
+                    .before(handle_destroyed_asteroids),
+            )
+            .add_event::<AsteroidShatterEvent>()
+            .add_systems(
+                Update,
+                handle_destroyed_asteroids.in_set(GameRngSet::Breakup),
+            )
+            .add_systems(PostUpdate, despawn_broken_asteroids)
+            .add_event::<AsteroidDestroyedEvent>();
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AsteroidClass {
+    Big,
+    Medium,
+    Small,
+    Tiny,
+}
+
+impl AsteroidClass {
+    pub fn max_health(&self) -> i32 {
+        match self {
+            AsteroidClass::Big => 5,
+            AsteroidClass::Medium => 4,
+            AsteroidClass::Small => 3,
+            AsteroidClass::Tiny => 2,
+        }
+    }
+
+    /// The radius of the collider in the asteroid's local space, before its scale.
+    pub fn collider_radius(&self) -> f32 {
+        match self {
+            AsteroidClass::Big => 50.0,
+            AsteroidClass::Medium => 22.0,
+            AsteroidClass::Small => 15.0,
+            AsteroidClass::Tiny => 6.0,
+        }
+    }
+
+    /// How big the explosion is when this asteroid breaks up.
+    pub fn explosion_scale(&self) -> f32 {
+        match self {
+            AsteroidClass::Big => 2.0,
+            AsteroidClass::Medium => 1.0,
+            AsteroidClass::Small => 0.6,
+            AsteroidClass::Tiny => 0.4,
+        }
+    }
+
+    /// The meteor sprites an asteroid of this class can be drawn with.
+    pub fn sprites(&self) -> &'static [&'static str] {
+        match self {
+            AsteroidClass::Big => &[
+                "meteors/meteorGrey_big1.png",
+                "meteors/meteorGrey_big2.png",
+                "meteors/meteorGrey_big3.png",
+                "meteors/meteorGrey_big4.png",
+            ],
+            AsteroidClass::Medium => {
+                &["meteors/meteorGrey_med1.png", "meteors/meteorGrey_med2.png"]
+            }
+            AsteroidClass::Small => &[
+                "meteors/meteorGrey_small1.png",
+                "meteors/meteorGrey_small2.png",
+            ],
+            AsteroidClass::Tiny => &[
+                "meteors/meteorGrey_tiny1.png",
+                "meteors/meteorGrey_tiny2.png",
+            ],
+        }
+    }
+
+    /// Smaller asteroids are harder to hit, so they are worth more.
+    pub fn points(&self) -> u32 {
+        match self {
+            AsteroidClass::Big => 20,
+            AsteroidClass::Medium => 50,
+            AsteroidClass::Small => 100,
+            AsteroidClass::Tiny => 200,
+        }
+    }
+}
+
+/// An event sent for a firing a laser
+#[derive(Event, Clone)]
+pub struct SpawnAsteroidEvent {
+    origin: Transform,
+    class: AsteroidClass,
+    velocity: LinearVelocity,
+    angular: AngularVelocity,
+    //true for asteroids entering from outside the play area
+    edge: bool,
+}
+
+impl SpawnAsteroidEvent {
+    /// A big asteroid at `origin` heading straight for `target`.
+    pub fn heading_toward(origin: Vec2, target: Vec2, speed: f32) -> Self {
+        let direction = (target - origin).normalize_or_zero();
+        SpawnAsteroidEvent {
+            origin: Transform::from_translation(origin.extend(0.0)),
+            class: AsteroidClass::Big,
+            velocity: LinearVelocity(direction * speed),
+            angular: AngularVelocity::default(),
+            edge: true,
+        }
+    }
+
+    /// A big asteroid placed just off screen at a random spot, drifting toward the center.
+    pub fn from_edge(play_area: &PlayArea, speed: f32, rng: &mut impl Rng) -> Self {
+        Self::from_edge_toward(play_area, Vec2::ZERO, speed, rng)
+    }
+
+    /// Like [`SpawnAsteroidEvent::from_edge`], but heading straight for `target`.
+    pub fn from_edge_toward(
+        play_area: &PlayArea,
+        target: Vec2,
+        speed: f32,
+        rng: &mut impl Rng,
+    ) -> Self {
+        let origin = play_area.random_edge_point(rng, EDGE_SPAWN_MARGIN, None);
+        Self::heading_toward(origin, target, speed)
+    }
+}
+
+/// How far outside the play area edge spawns are placed, so big asteroids start fully off screen.
+pub const EDGE_SPAWN_MARGIN: f32 = 60.0;
+
+#[derive(Component)]
+pub struct AsteroidHealth(pub i32);
+
+/// Sent whenever something takes health off an asteroid.
+#[derive(Event)]
+pub struct AsteroidHitEvent {
+    pub entity: Entity,
+    pub remaining: i32,
+}
+
+fn shade_damaged_asteroids(
+    mut events: EventReader<AsteroidHitEvent>,
+    mut asteroids: Query<
+        (
+            &AsteroidClass,
+            Option<&mut Sprite>,
+            Option<&mut TextureAtlasSprite>,
+        ),
+        Without<Fracturing>,
+    >,
+) {
+    for event in events.read() {
+        //destroyed ones are darkened by the fracture instead
+        if event.remaining <= 0 {
+            continue;
+        }
+        if let Ok((class, sprite, atlas_sprite)) = asteroids.get_mut(event.entity) {
+            let shade = 0.6 + 0.4 * event.remaining as f32 / class.max_health() as f32;
+            set_sprite_color(sprite, atlas_sprite, Color::rgb(shade, shade, shade));
+        }
+    }
+}
+
+/// How fast asteroids that weren't given a spin of their own turn, in radians per second
+/// either way.
+#[derive(Resource)]
+pub struct AsteroidSpinSettings {
+    pub min_spin: f32,
+    pub max_spin: f32,
+}
+
+impl Default for AsteroidSpinSettings {
+    fn default() -> Self {
+        Self {
+            min_spin: 0.2,
+            max_spin: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AsteroidColliderShape {
+    //cheap, but grazing hits on the jagged sprites can look off
+    #[default]
+    Ball,
+    Polygon,
+}
+
+/// The collider shape used for each asteroid class.
+#[derive(Resource, Default)]
+pub struct AsteroidColliderSettings {
+    pub big: AsteroidColliderShape,
+    pub medium: AsteroidColliderShape,
+    pub small: AsteroidColliderShape,
+    pub tiny: AsteroidColliderShape,
+}
+
+impl AsteroidColliderSettings {
+    pub fn shape(&self, class: AsteroidClass) -> AsteroidColliderShape {
+        match class {
+            AsteroidClass::Big => self.big,
+            AsteroidClass::Medium => self.medium,
+            AsteroidClass::Small => self.small,
+            AsteroidClass::Tiny => self.tiny,
+        }
+    }
+}
+
+//rough outline of the meteor sprites, as fractions of the ball radius at evenly spaced angles
+const METEOR_OUTLINE: [f32; 8] = [1.0, 0.9, 1.05, 0.85, 1.0, 0.95, 1.08, 0.88];
+
+/// A convex polygon about as big as `Collider::ball(radius)`, but with the lumps of a meteor.
+pub fn polygon_collider(radius: f32) -> Collider {
+    let step = 2.0 * PI / METEOR_OUTLINE.len() as f32;
+    let points = METEOR_OUTLINE
+        .iter()
+        .enumerate()
+        .map(|(n, factor)| {
+            let angle = n as f32 * step;
+            Vec2::new(angle.cos(), angle.sin()) * radius * factor
+        })
+        .collect();
+    Collider::convex_hull(points).unwrap_or_else(|| Collider::ball(radius))
+}
+
+/// Which of its class's [`AsteroidClass::sprites`] an asteroid is drawn with.
+#[derive(Component, Default, Clone, Copy)]
+pub struct AsteroidVariant(pub usize);
+
+//asteroids bounce off each other and get hit by everything else except ufos, which fly over them
+const ASTEROID_COLLIDES_WITH: [Layer; 4] = [
+    Layer::Asteroid,
+    Layer::Player,
+    Layer::PlayerBullet,
+    Layer::EnemyBullet,
+];
+
+#[derive(Bundle)]
+pub struct AsteroidBundle {
+    sprite_bundle: SpriteBundle,
+    rigid_body: RigidBody,
+    collider: Collider,
+    linear_velocity: LinearVelocity,
+    layer: CollisionLayers,
+    health: AsteroidHealth,
+    class: AsteroidClass,
+    variant: AsteroidVariant,
+    angular_velocity: AngularVelocity,
+    wrap: IgnoreWrapper,
+    external_force: ExternalForce,
+    tag: GameEntity,
+}
+
+impl Default for AsteroidBundle {
+    fn default() -> Self {
+        Self {
+            sprite_bundle: Default::default(),
+            rigid_body: RigidBody::Dynamic,
+            collider: Collider::ball(50.0),
+            linear_velocity: Default::default(),
+            layer: CollisionLayers::new([Layer::Asteroid], ASTEROID_COLLIDES_WITH),
+            health: AsteroidHealth(5),
+            class: AsteroidClass::Big,
+            variant: AsteroidVariant::default(),
+            angular_velocity: AngularVelocity::default(),
+            wrap: IgnoreWrapper::False,
+            external_force: ExternalForce::default(),
+            tag: GameEntity,
+        }
+    }
+}
+
+impl AsteroidBundle {
+    pub fn spawn(
+        event: &SpawnAsteroidEvent,
+        asset_server: &Res<AssetServer>,
+        spin_settings: &AsteroidSpinSettings,
+        collider_settings: &AsteroidColliderSettings,
+        atlas: Option<&SpriteAtlas>,
+        rng: &mut impl Rng,
+        commands: &mut Commands,
+    ) -> Entity {
+        //the sprite is only for looks, the collider below depends on the class alone
+        let sprites = event.class.sprites();
+        let variant = AsteroidVariant(rng.gen_range(0..sprites.len()));
+        //pieces from a split already come with their own spin
+        let angular_velocity = if event.angular.0 == 0.0 {
+            let spin = rng.gen_range(spin_settings.min_spin..=spin_settings.max_spin);
+            AngularVelocity(if rng.gen_bool(0.5) { spin } else { -spin })
+        } else {
+            event.angular
+        };
+        let rotation = Quat::from_rotation_z(rng.gen_range(0.0..(2.0 * PI)));
+        let scale = match event.class {
+            AsteroidClass::Big => 2.0,
+            AsteroidClass::Medium => 1.5,
+            AsteroidClass::Small => 1.0,
+            AsteroidClass::Tiny => 1.0,
+        };
+        let collider_size = event.class.collider_radius();
+        let health = event.class.max_health();
+        let wrap = match event.class {
+            AsteroidClass::Big => IgnoreWrapper::True,
+            _ => IgnoreWrapper::False,
+        };
+
+        let mut asteroid = commands.spawn(AsteroidBundle {
+            sprite_bundle: SpriteBundle {
+                texture: asset_server.load(sprites[variant.0]),
+                transform: event
+                    .origin
+                    .with_rotation(rotation)
+                    .with_scale(Vec3::splat(scale)),
+                ..Default::default()
+            },
+            collider: match collider_settings.shape(event.class) {
+                AsteroidColliderShape::Ball => Collider::ball(collider_size),
+                AsteroidColliderShape::Polygon => polygon_collider(collider_size),
+            },
+            linear_velocity: event.velocity,
+            health: AsteroidHealth(health),
+            class: event.class,
+            variant,
+            angular_velocity,
+            wrap: wrap,
+            ..default()
+        });
+        if let Some(atlas) = atlas {
+            atlas.swap_in(&mut asteroid, sprites[variant.0]);
+        }
+        asteroid.id()
+    }
+}
+
+/// How many asteroids may be around at once. Asteroids breaking up past it
+/// leave fewer pieces behind.
+#[derive(Resource)]
+pub struct MaxAsteroids(pub usize);
+
+impl Default for MaxAsteroids {
+    fn default() -> Self {
+        Self(80)
+    }
+}
+
+/// Asteroid spawns waiting for a frame with room in the spawn budget.
+#[derive(Resource)]
+pub struct AsteroidSpawnQueue {
+    //how many asteroids can be spawned in a single frame
+    pub budget: usize,
+    pub pending: VecDeque<SpawnAsteroidEvent>,
+}
+
+impl Default for AsteroidSpawnQueue {
+    fn default() -> Self {
+        Self {
+            budget: 10,
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+/// Sends in a new, bigger wave of asteroids whenever the field has been cleared.
+#[derive(Resource)]
+pub struct WaveManager {
+    pub wave_number: u32,
+    pub base_count: u32,
+    pub base_speed: f32,
+    //how close to the ship a new wave may spawn
+    pub safe_radius: f32,
+    //a breather between clearing the field and the next wave
+    delay: Timer,
+}
+
+impl Default for WaveManager {
+    fn default() -> Self {
+        Self {
+            wave_number: 0,
+            base_count: 2,
+            base_speed: 20.0,
+            safe_radius: 250.0,
+            delay: Timer::from_seconds(2.0, TimerMode::Once),
+        }
+    }
+}
+
+impl WaveManager {
+    /// How many asteroids the current wave brings.
+    pub fn wave_size(&self, difficulty: Difficulty) -> u32 {
+        let count = self.base_count + self.wave_number;
+        (count as f32 * difficulty.wave_size()).round() as u32
+    }
+}
+
+/// Sent when a new wave of asteroids is on its way in.
+#[derive(Event)]
+pub struct WaveStartedEvent {
+    pub wave: u32,
+    pub asteroid_count: u32,
+}
+
+fn wave_spawner(
+    time: Res<Time>,
+    mut waves: ResMut<WaveManager>,
+    asteroids: Query<(), With<AsteroidClass>>,
+    queue: Res<AsteroidSpawnQueue>,
+    play_area: Res<PlayArea>,
+    ships: Query<&Transform, With<Ship>>,
+    mut asteroid_event_writer: EventWriter<SpawnAsteroidEvent>,
+    mut wave_writer: EventWriter<WaveStartedEvent>,
+    difficulty: Res<Difficulty>,
+    mut game_rng: ResMut<GameRng>,
+) {
+    if !asteroids.is_empty() || !queue.pending.is_empty() {
+        waves.delay.reset();
+        return;
+    }
+    if !waves.delay.tick(time.delta()).finished() {
+        return;
+    }
+    waves.delay.reset();
+    waves.wave_number += 1;
+    let count = waves.wave_size(*difficulty);
+    let speed =
+        waves.base_speed * (1.0 + 0.1 * waves.wave_number as f32) * difficulty.asteroid_speed();
+    let rng = &mut game_rng.0;
+    //don't drop a new asteroid right next to a ship hugging the edge
+    let avoid = ships
+        .get_single()
+        .ok()
+        .map(|ship| (ship.translation.xy(), waves.safe_radius));
+    for _ in 0..count {
+        let origin = play_area.random_edge_point(rng, EDGE_SPAWN_MARGIN, avoid);
+        //aim somewhere around the center so the wave doesn't converge on one point
+        let target = Vec2::new(rng.gen_range(-150.0..150.0), rng.gen_range(-150.0..150.0));
+        asteroid_event_writer.send(SpawnAsteroidEvent::heading_toward(origin, target, speed));
+    }
+    info!("wave {}: {} asteroids", waves.wave_number, count);
+    wave_writer.send(WaveStartedEvent {
+        wave: waves.wave_number,
+        asteroid_count: count,
+    });
+}
+
+/// Gives the asteroid classes a bit of personality: big ones slowly seek the player
+/// and tiny ones dodge incoming lasers. Kept gentle so momentum still dominates.
+#[derive(Resource)]
+pub struct FieldBehavior {
+    pub enabled: bool,
+    pub seek_acceleration: f32,
+    pub evade_acceleration: f32,
+    //how close a laser has to be before tiny asteroids react to it
+    pub evade_radius: f32,
+}
+
+impl Default for FieldBehavior {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            seek_acceleration: 3.0,
+            evade_acceleration: 40.0,
+            evade_radius: 150.0,
+        }
+    }
+}
+
+fn steer_asteroids(
+    behavior: Res<FieldBehavior>,
+    mut asteroids: Query<(&AsteroidClass, &Transform, &Mass, &mut ExternalForce)>,
+    ships: Query<&Transform, With<Ship>>,
+    lasers: Query<(&Transform, &LinearVelocity), With<Laser>>,
+) {
+    if !behavior.enabled {
+        return;
+    }
+    let ship = ships.get_single().ok().map(|ship| ship.translation.xy());
+    for (class, transform, mass, mut force) in asteroids.iter_mut() {
+        let position = transform.translation.xy();
+        let acceleration = match class {
+            AsteroidClass::Big => ship.map_or(Vec2::ZERO, |ship| {
+                (ship - position).normalize_or_zero() * behavior.seek_acceleration
+            }),
+            AsteroidClass::Tiny => {
+                let mut dodge = Vec2::ZERO;
+                for (laser, velocity) in lasers.iter() {
+                    let to_asteroid = position - laser.translation.xy();
+                    //only lasers that are close and still heading our way
+                    if to_asteroid.length() > behavior.evade_radius
+                        || to_asteroid.dot(velocity.0) <= 0.0
+                    {
+                        continue;
+                    }
+                    //sidestep perpendicular to the laser's path
+                    let side = velocity.0.perp().normalize_or_zero();
+                    dodge += side * side.dot(to_asteroid).signum();
+                }
+                dodge.normalize_or_zero() * behavior.evade_acceleration
+            }
+            _ => Vec2::ZERO,
+        };
+        force.set_force(acceleration * mass.0);
+    }
+}
+
+fn asteroid_spawner(
+    mut reader: EventReader<SpawnAsteroidEvent>,
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+    ramp_settings: Res<SpawnRampSettings>,
+    mut queue: ResMut<AsteroidSpawnQueue>,
+    weak_spot_settings: Res<WeakSpotSettings>,
+    mutators: Res<Mutators>,
+    spin_settings: Res<AsteroidSpinSettings>,
+    collider_settings: Res<AsteroidColliderSettings>,
+    atlas: Option<Res<SpriteAtlas>>,
+    mut game_rng: ResMut<GameRng>,
+) {
+    let rng = &mut game_rng.0;
+    //queue everything so splits that come in bursts are spread over a few frames
+    queue.pending.extend(reader.read().cloned());
+    let count = queue.budget.min(queue.pending.len());
+    for mut event in queue.pending.drain(..count) {
+        //pieces inherit their parent's velocity, so only fresh asteroids get sped up
+        if event.edge && mutators.is_active(Mutator::FastAsteroids) {
+            event.velocity.0 *= 2.0;
+        }
+        // info!("thwomp");
+        //spawn asteroid
+        let entity = AsteroidBundle::spawn(
+            &event,
+            &asset_server,
+            &spin_settings,
+            &collider_settings,
+            atlas.as_deref(),
+            rng,
+            &mut commands,
+        );
+        if let (true, Some(duration)) = (event.edge, ramp_settings.duration) {
+            commands.entity(entity).insert((
+                SpawnRamp {
+                    timer: Timer::new(duration, TimerMode::Once),
+                    velocity: event.velocity.0,
+                },
+                LinearVelocity(event.velocity.0 * ramp_settings.start_factor),
+            ));
+        }
+        //only fresh big asteroids get armored, their pieces are normal
+        if event.edge && rng.gen_bool(weak_spot_settings.chance) {
+            add_weak_spot(
+                &mut commands,
+                entity,
+                event.class,
+                &asset_server,
+                &weak_spot_settings,
+                rng,
+            );
+        }
+    }
+}
+
+/// Tunables for armored asteroids that only take damage on their weak spot.
+#[derive(Resource)]
+pub struct WeakSpotSettings {
+    //chance for an asteroid coming in from the edge to be armored
+    pub chance: f64,
+    pub radius: f32,
+    //extra score for destroying one
+    pub bonus: u32,
+}
+
+impl Default for WeakSpotSettings {
+    fn default() -> Self {
+        Self {
+            chance: 0.2,
+            radius: 8.0,
+            bonus: 100,
+        }
+    }
+}
+
+/// An asteroid that only takes laser damage through its [`WeakSpot`].
+#[derive(Component)]
+pub struct Armored {
+    pub bonus: u32,
+}
+
+#[derive(Component)]
+pub struct WeakSpot;
+
+/// Where a weak spot sits on an asteroid of `class`, in its local space. It has to stick out
+/// past the body's collider or the body would take every hit first.
+fn weak_spot_offset(class: AsteroidClass, settings: &WeakSpotSettings, angle: f32) -> Vec2 {
+    //clear of the lumps of a polygon collider too
+    let distance = class.collider_radius() * 1.1 + settings.radius;
+    Vec2::new(angle.cos(), angle.sin()) * distance
+}
+
+fn add_weak_spot(
+    commands: &mut Commands,
+    asteroid: Entity,
+    class: AsteroidClass,
+    asset_server: &Res<AssetServer>,
+    settings: &WeakSpotSettings,
+    rng: &mut impl Rng,
+) {
+    let offset = weak_spot_offset(class, settings, rng.gen_range(0.0..(2.0 * PI)));
+    let weak_spot = commands
+        .spawn((
+            SpriteBundle {
+                texture: asset_server.load("crosshair/crossair_redOutline.png"),
+                sprite: Sprite {
+                    custom_size: Some(Vec2::splat(settings.radius * 2.0)),
+                    ..default()
+                },
+                transform: Transform::from_translation(offset.extend(0.1)),
+                ..default()
+            },
+            Collider::ball(settings.radius),
+            CollisionLayers::new([Layer::Asteroid], ASTEROID_COLLIDES_WITH),
+            WeakSpot,
+        ))
+        .id();
+    commands
+        .entity(asteroid)
+        .insert(Armored {
+            bonus: settings.bonus,
+        })
+        .add_child(weak_spot);
+}
+
+/// Slows down asteroids entering from the edges for a moment so the player can react.
+/// `duration` of `None` disables the ramp.
+#[derive(Resource)]
+pub struct SpawnRampSettings {
+    pub duration: Option<Duration>,
+    //fraction of the full speed the asteroid starts at
+    pub start_factor: f32,
+}
+
+impl Default for SpawnRampSettings {
+    fn default() -> Self {
+        Self {
+            duration: None,
+            start_factor: 0.25,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct SpawnRamp {
+    timer: Timer,
+    //the velocity the asteroid ends up at once the ramp is done
+    pub velocity: Vec2,
+}
+
+fn update_spawn_ramps(
+    time: Res<Time>,
+    ramp_settings: Res<SpawnRampSettings>,
+    mut ramps: Query<(Entity, &mut SpawnRamp, &mut LinearVelocity)>,
+    mut commands: Commands,
+) {
+    for (entity, mut ramp, mut velocity) in ramps.iter_mut() {
+        ramp.timer.tick(time.delta());
+        let start = ramp_settings.start_factor;
+        let factor = start + (1.0 - start) * ramp.timer.percent();
+        velocity.0 = ramp.velocity * factor;
+        if ramp.timer.finished() {
+            commands.entity(entity).remove::<SpawnRamp>();
+        }
+    }
+}
+
+/// Purely cosmetic dust left behind by destroyed asteroids, off by default.
+#[derive(Resource)]
+pub struct DustSettings {
+    pub enabled: bool,
+    pub per_asteroid: u32,
+    //upper bound on dust alive at once
+    pub max: usize,
+}
+
+impl Default for DustSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            per_asteroid: 6,
+            max: 300,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Dust;
+
+fn spawn_dust(commands: &mut Commands, rng: &mut impl Rng, position: Vec2, velocity: Vec2) {
+    let drift = Vec2::new(rng.gen_range(-10.0..10.0), rng.gen_range(-10.0..10.0));
+    let offset = Vec2::new(rng.gen_range(-15.0..15.0), rng.gen_range(-15.0..15.0));
+    let grey = rng.gen_range(0.5..0.8);
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(grey, grey, grey, 0.3),
+                custom_size: Some(Vec2::splat(rng.gen_range(2.0..5.0))),
+                ..default()
+            },
+            //behind everything else
+            transform: Transform::from_translation((position + offset).extend(-1.0)),
+            ..default()
+        },
+        RigidBody::Kinematic,
+        LinearVelocity(velocity * 0.1 + drift),
+        Lifetime(Timer::from_seconds(
+            rng.gen_range(8.0..12.0),
+            TimerMode::Once,
+        )),
+        FadeOut(0.3),
+        Dust,
+        GameEntity,
+    ));
+}
+
+/// A destroyed asteroid cracking apart just before it splits.
+#[derive(Component)]
+pub struct Fracturing {
+    timer: Timer,
+    base_scale: Vec3,
+}
+
+/// An asteroid that has already been broken up and is only waiting for its despawn to go through.
+/// It stays around until the end of the frame, so anything looking for asteroids to split or
+/// chase in the meantime has to skip these.
+#[derive(Component)]
+pub struct Despawning;
+
+/// Sent once when a destroyed asteroid has finished fracturing and breaks apart.
+#[derive(Event)]
+pub struct AsteroidShatterEvent {
+    pub entity: Entity,
+}
+
+fn begin_fracture(
+    mut events: EventReader<AsteroidHitEvent>,
+    asteroids: Query<&Transform, (With<AsteroidClass>, Without<Fracturing>)>,
+    mut commands: Commands,
+) {
+    //several hits can finish off the same asteroid in one frame
+    let mut fractured = Vec::new();
+    for event in events.read() {
+        if event.remaining > 0 || fractured.contains(&event.entity) {
+            continue;
+        }
+        if let Ok(transform) = asteroids.get(event.entity) {
+            //short enough that the split still feels instant to play against
+            commands.entity(event.entity).insert(Fracturing {
+                timer: Timer::from_seconds(0.12, TimerMode::Once),
+                base_scale: transform.scale,
+            });
+            fractured.push(event.entity);
+        }
+    }
+}
+
+fn animate_fracture(
+    time: Res<Time>,
+    mut asteroids: Query<(
+        Entity,
+        &mut Fracturing,
+        Option<&mut Sprite>,
+        Option<&mut TextureAtlasSprite>,
+        &mut Transform,
+    )>,
+    mut shatter_writer: EventWriter<AsteroidShatterEvent>,
+) {
+    let mut rng = rand::thread_rng();
+    for (entity, mut fracturing, sprite, atlas_sprite, mut transform) in asteroids.iter_mut() {
+        if fracturing.timer.tick(time.delta()).just_finished() {
+            shatter_writer.send(AsteroidShatterEvent { entity });
+        }
+        let progress = fracturing.timer.percent();
+        //darken as the cracks spread, and swell and shudder as the pieces pull apart
+        let shade = 1.0 - 0.4 * progress;
+        set_sprite_color(sprite, atlas_sprite, Color::rgb(shade, shade, shade));
+        let jitter = rng.gen_range(-0.03..0.03) * progress;
+        transform.scale = fracturing.base_scale * (1.0 + 0.15 * progress + jitter);
+    }
+}
+
+pub fn handle_destroyed_asteroids(
+    mut shatter_events: EventReader<AsteroidShatterEvent>,
+    asteroids: Query<
+        (
+            Entity,
+            &AsteroidClass,
+            &Transform,
+            &LinearVelocity,
+            Option<&Armored>,
+        ),
+        Without<Despawning>,
+    >,
+    mut commands: Commands,
+    mut asteroid_event_writer: EventWriter<SpawnAsteroidEvent>,
+    mut destroyed_writer: EventWriter<AsteroidDestroyedEvent>,
+    mut explosion_writer: EventWriter<SpawnExplosionEvent>,
+    power_up_settings: Res<PowerUpSettings>,
+    dust_settings: Res<DustSettings>,
+    dust: Query<(), With<Dust>>,
+    mutators: Res<Mutators>,
+    max_asteroids: Res<MaxAsteroids>,
+    queue: Res<AsteroidSpawnQueue>,
+    mut combo: ResMut<Combo>,
+    mut combo_timer: ResMut<ComboTimer>,
+    mut game_rng: ResMut<GameRng>,
+) {
+    let rng = &mut game_rng.0;
+    //everything out there plus everything still waiting to come in
+    let mut live = asteroids.iter().count() + queue.pending.len();
+    let speed = 45.0;
+    let rot_speed = 5.0;
+    let mut dust_count = dust.iter().count();
+    //the marker only shows up once commands are applied, so also remember this run's
+    let mut destroyed = Vec::new();
+    for event in shatter_events.read() {
+        let Ok(asteroid) = asteroids.get(event.entity) else {
+            continue;
+        };
+        if destroyed.contains(&asteroid.0) {
+            continue;
+        }
+        destroyed.push(asteroid.0);
+        //never split or score the same asteroid twice, it is despawned at the end of the frame
+        commands.entity(asteroid.0).insert(Despawning);
+        if dust_settings.enabled {
+            for _ in 0..dust_settings.per_asteroid {
+                if dust_count >= dust_settings.max {
+                    break;
+                }
+                spawn_dust(
+                    &mut commands,
+                    &mut rand::thread_rng(),
+                    asteroid.2.translation.xy(),
+                    asteroid.3 .0,
+                );
+                dust_count += 1;
+            }
+        }
+        //boom
+        let mut points = asteroid.1.points();
+        if let Some(armored) = asteroid.4 {
+            points += armored.bonus;
+        }
+        let combo_multiplier = combo.register_kill(&mut combo_timer);
+        destroyed_writer.send(AsteroidDestroyedEvent {
+            class: *asteroid.1,
+            position: asteroid.2.translation.xy(),
+            points: points * mutators.score_multiplier() * combo_multiplier,
+        });
+        explosion_writer.send(SpawnExplosionEvent {
+            position: asteroid.2.translation.xy(),
+            scale: asteroid.1.explosion_scale(),
+        });
+        maybe_drop_power_up(
+            &mut commands,
+            rng,
+            &power_up_settings,
+            asteroid.2.translation.xy(),
+        );
+        //spawn the children!
+        let mut children = Vec::new();
+        match asteroid.1 {
+            AsteroidClass::Big => {
+                //we have a lot of children to spawn lol
+                //center
+                children.push(SpawnAsteroidEvent {
+                    origin: Transform {
+                        translation: asteroid.2.translation,
+                        ..default()
+                    },
+                    class: AsteroidClass::Medium,
+                    velocity: LinearVelocity::default(),
+                    angular: AngularVelocity::default(),
+                    edge: false,
+                });
+                let count = 6.0;
+                let step = 2.0 * PI / count;
+                //angle offset
+                let angle_offset = rng.gen_range(0.0..360.0);
+
+                info!("offset : {}", angle_offset);
+                for n in 1..=6 {
+                    //velocity
+                    let x = rng.gen_range(-speed..speed);
+                    let y = rng.gen_range(-speed..speed);
+                    let velocity = Vec2 {
+                        x: asteroid.3 .0.x + x,
+                        y: asteroid.3 .0.y + y,
+                    };
+                    let rot = rng.gen_range(-rot_speed..rot_speed);
+                    let translation = asteroid.2.translation
+                        + Quat::from_rotation_z(angle_offset + n as f32 * step)
+                            .mul_vec3(Vec3::Y * 68.0);
+                    children.push(SpawnAsteroidEvent {
+                        origin: Transform {
+                            translation: translation,
+                            ..default()
+                        },
+                        class: AsteroidClass::Medium,
+                        velocity: LinearVelocity(velocity),
+                        angular: AngularVelocity(rot),
+                        edge: false,
+                    });
+                }
+            }
+            AsteroidClass::Medium => {
+                //we have a lot of children to spawn lol
+                let count = 3.0;
+                let step = 2.0 * PI / count;
+                //angle offset
+                let angle_offset = rng.gen_range(0.0..360.0);
+                //velocity
+
+                for n in 1..=3 {
+                    let x = rng.gen_range(-speed..speed);
+                    let y = rng.gen_range(-speed..speed);
+                    let velocity = Vec2 {
+                        x: asteroid.3 .0.x + x,
+                        y: asteroid.3 .0.y + y,
+                    };
+                    let rot = rng.gen_range(-rot_speed..rot_speed);
+                    let translation = asteroid.2.translation
+                        + Quat::from_rotation_z(angle_offset + n as f32 * step)
+                            .mul_vec3(Vec3::Y * 20.0);
+                    children.push(SpawnAsteroidEvent {
+                        origin: Transform {
+                            translation: translation,
+                            ..default()
+                        },
+                        class: AsteroidClass::Small,
+                        velocity: LinearVelocity(velocity),
+                        angular: AngularVelocity(rot),
+                        edge: false,
+                    });
+                }
+            }
+            AsteroidClass::Small => {
+                //we have a lot of children to spawn lol
+                let count = 4.0;
+                let step = 2.0 * PI / count;
+                //angle offset
+                let angle_offset = rng.gen_range(0.0..360.0);
+
+                for n in 1..=4 {
+                    //velocity
+                    let x = rng.gen_range(-speed..speed);
+                    let y = rng.gen_range(-speed..speed);
+                    let velocity = Vec2 {
+                        x: asteroid.3 .0.x + x,
+                        y: asteroid.3 .0.y + y,
+                    };
+                    let rot = rng.gen_range(-rot_speed..rot_speed);
+                    let translation = asteroid.2.translation
+                        + Quat::from_rotation_z(angle_offset + n as f32 * step)
+                            .mul_vec3(Vec3::Y * 10.0);
+                    children.push(SpawnAsteroidEvent {
+                        origin: Transform {
+                            translation: translation,
+                            ..default()
+                        },
+                        class: AsteroidClass::Tiny,
+                        velocity: LinearVelocity(velocity),
+                        angular: AngularVelocity(rot),
+                        edge: false,
+                    });
+                }
+            }
+            AsteroidClass::Tiny => {}
+        };
+        //over the cap only the first piece comes out, so the field can still be cleared
+        live = live.saturating_sub(1);
+        children.truncate(max_asteroids.0.saturating_sub(live).max(1));
+        live += children.len();
+        for child in children {
+            asteroid_event_writer.send(child);
+        }
+    }
+}
+
+fn despawn_broken_asteroids(mut commands: Commands, asteroids: Query<Entity, With<Despawning>>) {
+    for entity in asteroids.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Sent when an asteroid breaks apart, with the points it was worth.
+#[derive(Event)]
+pub struct AsteroidDestroyedEvent {
+    pub class: AsteroidClass,
+    pub position: Vec2,
+    pub points: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::score::{award_points, tick_combo, KillCount, Score, ScoreEvent};
+
+    /// Breaking up asteroids and scoring them.
+    fn destroy_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<PowerUpSettings>()
+            .init_resource::<DustSettings>()
+            .init_resource::<Mutators>()
+            .init_resource::<MaxAsteroids>()
+            .init_resource::<AsteroidSpawnQueue>()
+            .init_resource::<Combo>()
+            .init_resource::<ComboTimer>()
+            .init_resource::<GameRng>()
+            .init_resource::<Score>()
+            .init_resource::<KillCount>()
+            .add_event::<AsteroidShatterEvent>()
+            .add_event::<SpawnAsteroidEvent>()
+            .add_event::<AsteroidDestroyedEvent>()
+            .add_event::<SpawnExplosionEvent>()
+            .add_event::<crate::ufo::UfoDestroyedEvent>()
+            .add_event::<ScoreEvent>()
+            .add_event::<crate::weapons::WeaponUpgrade>()
+            .add_systems(Update, (handle_destroyed_asteroids, award_points).chain())
+            .add_systems(PostUpdate, despawn_broken_asteroids);
+        app
+    }
+
+    fn shatter(app: &mut App, class: AsteroidClass) -> Entity {
+        let asteroid = app
+            .world
+            .spawn((class, Transform::default(), LinearVelocity::default()))
+            .id();
+        app.world
+            .send_event(AsteroidShatterEvent { entity: asteroid });
+        asteroid
+    }
+
+    #[test]
+    fn each_class_scores_its_own_points() {
+        let mut app = destroy_app();
+        //no combo, so every kill scores its plain points
+        app.world.resource_mut::<Combo>().max_multiplier = 1;
+        for class in [
+            AsteroidClass::Big,
+            AsteroidClass::Medium,
+            AsteroidClass::Small,
+            AsteroidClass::Tiny,
+        ] {
+            shatter(&mut app, class);
+        }
+        app.update();
+
+        assert_eq!(app.world.resource::<Score>().0, 20 + 50 + 100 + 200);
+    }
+
+    /// Where the pieces of a big asteroid broken up with `seed` end up, and how fast they go.
+    fn split_with_seed(seed: u64) -> Vec<(Vec2, Vec2)> {
+        let mut app = destroy_app();
+        app.insert_resource(GameRng::seeded(seed));
+        shatter(&mut app, AsteroidClass::Big);
+        app.update();
+        app.world
+            .resource_mut::<Events<SpawnAsteroidEvent>>()
+            .drain()
+            .map(|event| (event.origin.translation.xy(), event.velocity.0))
+            .collect()
+    }
+
+    #[test]
+    fn the_same_seed_splits_the_same_way() {
+        let first = split_with_seed(42);
+        assert!(!first.is_empty());
+        assert_eq!(first, split_with_seed(42));
+        assert_ne!(first, split_with_seed(43));
+    }
+
+    /// Every asteroid left after a frame of spawning and a frame of breaking them all up, with
+    /// breakup and the spawner run in their [`GameRngSet`] order.
+    fn spawn_and_split_with_seed(seed: u64) -> Vec<(Vec2, Vec2, f32)> {
+        let mut app = spawner_app();
+        app.insert_resource(GameRng::seeded(seed))
+            .init_resource::<PowerUpSettings>()
+            .init_resource::<DustSettings>()
+            .init_resource::<MaxAsteroids>()
+            .init_resource::<Combo>()
+            .init_resource::<ComboTimer>()
+            .add_event::<AsteroidShatterEvent>()
+            .add_event::<AsteroidDestroyedEvent>()
+            .add_event::<SpawnExplosionEvent>()
+            .configure_sets(Update, (GameRngSet::Breakup, GameRngSet::Spawn).chain())
+            .add_systems(
+                Update,
+                handle_destroyed_asteroids.in_set(GameRngSet::Breakup),
+            )
+            .add_systems(PostUpdate, despawn_broken_asteroids);
+        spawn_big_asteroids(&mut app, 3);
+
+        let mut asteroids = app.world.query_filtered::<Entity, With<AsteroidClass>>();
+        let big: Vec<Entity> = asteroids.iter(&app.world).collect();
+        for entity in big {
+            app.world.send_event(AsteroidShatterEvent { entity });
+        }
+        app.update();
+
+        let mut asteroids = app
+            .world
+            .query_filtered::<(&Transform, &LinearVelocity, &AngularVelocity), With<AsteroidClass>>(
+            );
+        asteroids
+            .iter(&app.world)
+            .map(|(transform, velocity, spin)| (transform.translation.xy(), velocity.0, spin.0))
+            .collect()
+    }
+
+    #[test]
+    fn the_same_seed_spawns_and_splits_the_same_way() {
+        let first = spawn_and_split_with_seed(7);
+        //three big ones, each broken into its pieces
+        assert!(first.len() > 3);
+        assert_eq!(first, spawn_and_split_with_seed(7));
+        assert_ne!(first, spawn_and_split_with_seed(8));
+    }
+
+    #[test]
+    fn splitting_stops_at_the_asteroid_cap() {
+        let mut app = destroy_app();
+        app.insert_resource(MaxAsteroids(4));
+        for _ in 0..2 {
+            app.world.spawn((
+                AsteroidClass::Small,
+                Transform::default(),
+                LinearVelocity::default(),
+            ));
+        }
+        shatter(&mut app, AsteroidClass::Big);
+        app.update();
+
+        let mut asteroids = app.world.query_filtered::<(), With<AsteroidClass>>();
+        let remaining = asteroids.iter(&app.world).count();
+        let pieces = app
+            .world
+            .resource_mut::<Events<SpawnAsteroidEvent>>()
+            .drain()
+            .count();
+        //a big one would normally leave seven pieces
+        assert_eq!(remaining, 2);
+        assert_eq!(pieces, 2);
+        assert!(remaining + pieces <= 4);
+    }
+
+    #[test]
+    fn a_full_field_still_leaves_one_piece() {
+        let mut app = destroy_app();
+        app.insert_resource(MaxAsteroids(1));
+        app.world.spawn((
+            AsteroidClass::Small,
+            Transform::default(),
+            LinearVelocity::default(),
+        ));
+        shatter(&mut app, AsteroidClass::Big);
+        app.update();
+
+        let pieces = app.world.resource::<Events<SpawnAsteroidEvent>>().len();
+        assert_eq!(pieces, 1);
+    }
+
+    #[test]
+    fn two_killing_blows_in_one_frame_split_an_asteroid_once() {
+        let mut app = destroy_app();
+        app.init_resource::<Time>()
+            .add_event::<AsteroidHitEvent>()
+            .add_systems(
+                Update,
+                (begin_fracture, animate_fracture)
+                    .chain()
+                    .before(handle_destroyed_asteroids),
+            );
+        let asteroid = app
+            .world
+            .spawn((
+                AsteroidClass::Big,
+                Sprite::default(),
+                Transform::default(),
+                LinearVelocity::default(),
+            ))
+            .id();
+        //say a laser and a missile both finish it off
+        for _ in 0..2 {
+            app.world.send_event(AsteroidHitEvent {
+                entity: asteroid,
+                remaining: 0,
+            });
+        }
+        let mut pieces = 0;
+        let mut destroyed = 0;
+        for _ in 0..4 {
+            app.world
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_millis(100));
+            app.update();
+            pieces += app
+                .world
+                .resource_mut::<Events<SpawnAsteroidEvent>>()
+                .drain()
+                .count();
+            destroyed += app
+                .world
+                .resource_mut::<Events<AsteroidDestroyedEvent>>()
+                .drain()
+                .count();
+        }
+        assert_eq!(pieces, 7);
+        assert_eq!(destroyed, 1);
+    }
+
+    #[test]
+    fn breaking_up_twice_before_the_despawn_splits_once() {
+        use bevy::ecs::system::RunSystemOnce;
+
+        let mut app = destroy_app();
+        let asteroid = shatter(&mut app, AsteroidClass::Big);
+        //each run reads the shatter event afresh, only the marker holds the second one back
+        app.world.run_system_once(handle_destroyed_asteroids);
+        app.world.run_system_once(handle_destroyed_asteroids);
+        assert!(app.world.get::<Despawning>(asteroid).is_some());
+
+        //the regular run this frame sees it too, and the despawn goes through at the end
+        app.update();
+        assert!(app.world.get_entity(asteroid).is_none());
+        let pieces = app
+            .world
+            .resource_mut::<Events<SpawnAsteroidEvent>>()
+            .drain()
+            .count();
+        let destroyed = app
+            .world
+            .resource_mut::<Events<AsteroidDestroyedEvent>>()
+            .drain()
+            .count();
+        assert_eq!(pieces, 7);
+        assert_eq!(destroyed, 1);
+    }
+
+    #[test]
+    fn a_quick_second_kill_scores_double() {
+        let mut app = destroy_app();
+        app.init_resource::<Time>()
+            .add_systems(Update, tick_combo.before(handle_destroyed_asteroids));
+        let score = |app: &App| app.world.resource::<Score>().0;
+        let kill_after = |app: &mut App, seconds: f32| {
+            app.world
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_secs_f32(seconds));
+            shatter(app, AsteroidClass::Tiny);
+            app.update();
+        };
+
+        kill_after(&mut app, 0.0);
+        assert_eq!(score(&app), 200);
+        kill_after(&mut app, 0.5);
+        assert_eq!(score(&app), 200 + 2 * 200);
+        //once the window lapses the next kill starts over
+        kill_after(&mut app, 2.0);
+        assert_eq!(score(&app), 200 + 2 * 200 + 200);
+    }
+
+    #[test]
+    fn big_asteroids_blow_up_bigger() {
+        let mut app = destroy_app();
+        shatter(&mut app, AsteroidClass::Big);
+        app.update();
+        shatter(&mut app, AsteroidClass::Tiny);
+        app.update();
+
+        let scales: Vec<f32> = app
+            .world
+            .resource_mut::<Events<SpawnExplosionEvent>>()
+            .drain()
+            .map(|event| event.scale)
+            .collect();
+        assert_eq!(scales.len(), 2);
+        assert_eq!(scales[0], AsteroidClass::Big.explosion_scale());
+        assert!(scales[0] > scales[1]);
+    }
+
+    #[test]
+    fn a_cleared_field_brings_a_bigger_wave() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<WaveManager>()
+            .init_resource::<AsteroidSpawnQueue>()
+            .init_resource::<PlayArea>()
+            .init_resource::<Difficulty>()
+            .insert_resource(GameRng::seeded(1))
+            .add_event::<SpawnAsteroidEvent>()
+            .add_event::<WaveStartedEvent>()
+            .add_systems(Update, wave_spawner);
+        let straggler = app.world.spawn(AsteroidClass::Tiny).id();
+        let wave_after = |app: &mut App, delay: f32| {
+            app.world
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_secs_f32(delay));
+            app.update();
+            let spawned = app
+                .world
+                .resource_mut::<Events<SpawnAsteroidEvent>>()
+                .drain()
+                .count();
+            let waves: Vec<(u32, u32)> = app
+                .world
+                .resource_mut::<Events<WaveStartedEvent>>()
+                .drain()
+                .map(|event| (event.wave, event.asteroid_count))
+                .collect();
+            (spawned, waves)
+        };
+        //the breather only starts once the field is clear
+        assert_eq!(wave_after(&mut app, 5.0), (0, vec![]));
+
+        app.world.despawn(straggler);
+        assert_eq!(wave_after(&mut app, 2.0), (3, vec![(1, 3)]));
+        //nothing was spawned from those events, so the field is still clear
+        assert_eq!(wave_after(&mut app, 2.0), (4, vec![(2, 4)]));
+    }
+
+    /// Turning spawn events into asteroids, all in a single frame.
+    fn spawner_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((TaskPoolPlugin::default(), AssetPlugin::default()))
+            .init_asset::<Image>()
+            .init_resource::<SpawnRampSettings>()
+            .insert_resource(AsteroidSpawnQueue {
+                budget: 1000,
+                ..default()
+            })
+            //no armor, so every spawned entity is an asteroid
+            .insert_resource(WeakSpotSettings {
+                chance: 0.0,
+                ..default()
+            })
+            .init_resource::<Mutators>()
+            .init_resource::<AsteroidSpinSettings>()
+            .init_resource::<AsteroidColliderSettings>()
+            .insert_resource(GameRng::seeded(1))
+            .add_event::<SpawnAsteroidEvent>()
+            .add_systems(Update, asteroid_spawner.in_set(GameRngSet::Spawn));
+        app
+    }
+
+    fn spawn_big_asteroids(app: &mut App, count: usize) {
+        for n in 0..count {
+            let origin = Vec2::new(n as f32 * 10.0, 0.0);
+            app.world
+                .send_event(SpawnAsteroidEvent::heading_toward(origin, Vec2::ZERO, 20.0));
+        }
+        app.update();
+    }
+
+    #[test]
+    fn big_asteroids_come_in_several_looks() {
+        let mut app = spawner_app();
+        spawn_big_asteroids(&mut app, 50);
+
+        let mut asteroids = app.world.query::<(&Handle<Image>, &Collider)>();
+        let mut textures = std::collections::HashSet::new();
+        let mut count = 0;
+        for (texture, collider) in asteroids.iter(&app.world) {
+            textures.insert(texture.id());
+            //the look changes, the hitbox doesn't
+            let ball = collider.shape().as_ball().unwrap();
+            assert_eq!(ball.radius, AsteroidClass::Big.collider_radius());
+            count += 1;
+        }
+        assert_eq!(count, 50);
+        assert!(textures.len() > 1);
+    }
+
+    #[test]
+    fn asteroids_switch_to_the_atlas_once_it_is_packed() {
+        let mut app = spawner_app();
+        let sprites = AsteroidClass::Big.sprites();
+        //packed in the same order they are listed in
+        app.insert_resource(SpriteAtlas {
+            atlas: Handle::default(),
+            indices: sprites
+                .iter()
+                .enumerate()
+                .map(|(index, path)| (path.to_string(), index))
+                .collect(),
+        });
+        spawn_big_asteroids(&mut app, 20);
+
+        let mut asteroids = app
+            .world
+            .query_filtered::<(&TextureAtlasSprite, &AsteroidVariant), Without<Handle<Image>>>();
+        let mut count = 0;
+        for (sprite, variant) in asteroids.iter(&app.world) {
+            assert_eq!(sprite.index, variant.0);
+            count += 1;
+        }
+        assert_eq!(count, 20);
+    }
+
+    #[test]
+    fn fresh_asteroids_spin_at_random() {
+        let mut app = spawner_app();
+        spawn_big_asteroids(&mut app, 100);
+
+        let settings = AsteroidSpinSettings::default();
+        let mut asteroids = app.world.query::<&AngularVelocity>();
+        let spins: Vec<f32> = asteroids.iter(&app.world).map(|spin| spin.0).collect();
+        assert_eq!(spins.len(), 100);
+        for spin in &spins {
+            assert!((settings.min_spin..=settings.max_spin).contains(&spin.abs()));
+        }
+        //both ways round, and not all at one speed
+        assert!(spins.iter().any(|spin| *spin > 0.0));
+        assert!(spins.iter().any(|spin| *spin < 0.0));
+        let fastest = spins.iter().fold(0.0f32, |max, spin| max.max(spin.abs()));
+        let slowest = spins.iter().fold(f32::MAX, |min, spin| min.min(spin.abs()));
+        assert!(fastest - slowest > 0.4);
+    }
+
+    #[test]
+    fn harder_difficulties_bring_bigger_faster_waves() {
+        //the third wave at each difficulty: how many asteroids and how fast they come
+        let third_wave = |difficulty: Difficulty| {
+            let mut app = App::new();
+            app.init_resource::<Time>()
+                .insert_resource(WaveManager {
+                    wave_number: 2,
+                    ..default()
+                })
+                .init_resource::<AsteroidSpawnQueue>()
+                .init_resource::<PlayArea>()
+                .insert_resource(difficulty)
+                .insert_resource(GameRng::seeded(1))
+                .add_event::<SpawnAsteroidEvent>()
+                .add_event::<WaveStartedEvent>()
+                .add_systems(Update, wave_spawner);
+            app.world
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_secs(2));
+            app.update();
+            let speeds: Vec<f32> = app
+                .world
+                .resource_mut::<Events<SpawnAsteroidEvent>>()
+                .drain()
+                .map(|event| event.velocity.length())
+                .collect();
+            (speeds.len(), speeds[0])
+        };
+        let (easy, easy_speed) = third_wave(Difficulty::Easy);
+        let (normal, normal_speed) = third_wave(Difficulty::Normal);
+        let (hard, hard_speed) = third_wave(Difficulty::Hard);
+
+        assert_eq!((easy, normal, hard), (5, 5, 8));
+        assert!(easy_speed < normal_speed && normal_speed < hard_speed);
+    }
+
+    #[test]
+    fn a_big_polygon_collider_follows_the_meteor_outline() {
+        let radius = AsteroidClass::Big.collider_radius();
+        let collider = polygon_collider(radius);
+        let polygon = collider.shape().as_convex_polygon().unwrap();
+        assert_eq!(polygon.points().len(), METEOR_OUTLINE.len());
+        //at density one the mass is the area, which should be about that of the ball
+        let area = collider.shape().mass_properties(1.0).mass();
+        assert!(area > 0.0);
+        assert!(area < PI * radius * radius * 1.1);
+    }
+
+    #[test]
+    fn weak_spots_stick_out_past_the_body() {
+        let settings = WeakSpotSettings::default();
+        for class in [
+            AsteroidClass::Big,
+            AsteroidClass::Medium,
+            AsteroidClass::Small,
+            AsteroidClass::Tiny,
+        ] {
+            for n in 0..8 {
+                let angle = n as f32 * PI / 4.0;
+                let offset = weak_spot_offset(class, &settings, angle);
+                //the polygon collider's biggest lump is 8% out
+                let body = class.collider_radius() * 1.08;
+                assert!(offset.length() - settings.radius >= body);
+            }
+        }
+    }
+}
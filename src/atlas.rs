@@ -0,0 +1,205 @@
+use bevy::{asset::LoadState, ecs::system::EntityCommands, prelude::*, utils::HashMap};
+
+use crate::asteroids::AsteroidClass;
+
+/// Packs the ship, laser, meteor and crosshair images into one [`TextureAtlas`] once they have
+/// loaded, so a screen full of asteroids and bolts binds a single texture. Spawners keep using
+/// the separate images until the atlas is ready, or for good if packing it fails.
+pub struct SpriteAtlasPlugin;
+
+impl Plugin for SpriteAtlasPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_atlas_sources).add_systems(
+            Update,
+            build_sprite_atlas.run_if(resource_exists::<AtlasSources>()),
+        );
+    }
+}
+
+pub const SHIP_SPRITE: &str = "playerShip1_orange.png";
+pub const LASER_SPRITE: &str = "lasers/laserBlue01.png";
+pub const CROSSHAIR_SPRITE: &str = "crosshair/crossair_white.png";
+pub const TARGET_SPRITE: &str = "crosshair/crossair_redOutline.png";
+
+/// The asset path of every image that goes into the atlas, which is also its name in it.
+fn sprite_paths() -> Vec<&'static str> {
+    let mut paths = vec![SHIP_SPRITE, LASER_SPRITE, CROSSHAIR_SPRITE, TARGET_SPRITE];
+    for class in [
+        AsteroidClass::Big,
+        AsteroidClass::Medium,
+        AsteroidClass::Small,
+        AsteroidClass::Tiny,
+    ] {
+        paths.extend_from_slice(class.sprites());
+    }
+    paths
+}
+
+/// The images still waiting to be packed, gone once the atlas is built.
+#[derive(Resource)]
+struct AtlasSources(Vec<(&'static str, Handle<Image>)>);
+
+/// The packed sprites and where each image ended up in them, by asset path.
+#[derive(Resource)]
+pub struct SpriteAtlas {
+    pub atlas: Handle<TextureAtlas>,
+    pub indices: HashMap<String, usize>,
+}
+
+impl SpriteAtlas {
+    /// The atlas index of the image loaded from `path`, if it was packed.
+    pub fn index(&self, path: &str) -> Option<usize> {
+        self.indices.get(path).copied()
+    }
+
+    /// Swaps a freshly spawned sprite for its packed copy, leaving it alone if `path` wasn't
+    /// packed. The copy starts out untinted, the transform and everything else stay.
+    pub fn swap_in(&self, entity: &mut EntityCommands, path: &str) {
+        let Some(index) = self.index(path) else {
+            return;
+        };
+        entity
+            .remove::<(Sprite, Handle<Image>)>()
+            .insert((self.atlas.clone(), TextureAtlasSprite::new(index)));
+    }
+}
+
+/// Tints whichever kind of sprite an entity has, a separate image or a packed one.
+pub fn set_sprite_color(
+    sprite: Option<Mut<Sprite>>,
+    atlas_sprite: Option<Mut<TextureAtlasSprite>>,
+    color: Color,
+) {
+    if let Some(mut sprite) = sprite {
+        sprite.color = color;
+    }
+    if let Some(mut sprite) = atlas_sprite {
+        sprite.color = color;
+    }
+}
+
+fn load_atlas_sources(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let sources = sprite_paths()
+        .into_iter()
+        .map(|path| (path, asset_server.load(path)))
+        .collect();
+    commands.insert_resource(AtlasSources(sources));
+}
+
+fn build_sprite_atlas(
+    mut commands: Commands,
+    sources: Res<AtlasSources>,
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+    mut atlases: ResMut<Assets<TextureAtlas>>,
+) {
+    //one missing image and there is no atlas, the separate textures still work
+    let failed = sources
+        .0
+        .iter()
+        .any(|(_, handle)| asset_server.get_load_state(handle) == Some(LoadState::Failed));
+    if failed {
+        warn!("a sprite failed to load, not packing the sprite atlas");
+        commands.remove_resource::<AtlasSources>();
+        return;
+    }
+    if sources.0.iter().any(|(_, handle)| !images.contains(handle)) {
+        return;
+    }
+    let mut builder = TextureAtlasBuilder::default();
+    for (_, handle) in &sources.0 {
+        builder.add_texture(handle.id(), images.get(handle).unwrap());
+    }
+    commands.remove_resource::<AtlasSources>();
+    let atlas = match builder.finish(&mut images) {
+        Ok(atlas) => atlas,
+        Err(error) => {
+            warn!("couldn't pack the sprite atlas: {:?}", error);
+            return;
+        }
+    };
+    let indices = sources
+        .0
+        .iter()
+        .filter_map(|(path, handle)| {
+            let index = atlas.get_texture_index(handle)?;
+            Some((path.to_string(), index))
+        })
+        .collect();
+    info!("packed {} sprites into an atlas", sources.0.len());
+    commands.insert_resource(SpriteAtlas {
+        atlas: atlases.add(atlas),
+        indices,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+    use super::*;
+
+    fn image(size: u32) -> Image {
+        Image::new_fill(
+            Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[255, 255, 255, 255],
+            TextureFormat::Rgba8UnormSrgb,
+        )
+    }
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((TaskPoolPlugin::default(), AssetPlugin::default()))
+            .init_asset::<Image>()
+            .init_asset::<TextureAtlas>()
+            .add_systems(
+                Update,
+                build_sprite_atlas.run_if(resource_exists::<AtlasSources>()),
+            );
+        app
+    }
+
+    #[test]
+    fn every_loaded_sprite_gets_its_own_index() {
+        let mut app = app();
+        let mut images = app.world.resource_mut::<Assets<Image>>();
+        let sources = vec![
+            (SHIP_SPRITE, images.add(image(16))),
+            (LASER_SPRITE, images.add(image(8))),
+            (TARGET_SPRITE, images.add(image(4))),
+        ];
+        app.insert_resource(AtlasSources(sources));
+        app.update();
+
+        let atlas = app.world.resource::<SpriteAtlas>();
+        let mut indices: Vec<usize> = [SHIP_SPRITE, LASER_SPRITE, TARGET_SPRITE]
+            .iter()
+            .map(|path| atlas.index(path).unwrap())
+            .collect();
+        indices.sort();
+        assert_eq!(indices, vec![0, 1, 2]);
+        assert_eq!(atlas.index(CROSSHAIR_SPRITE), None);
+        assert!(!app.world.contains_resource::<AtlasSources>());
+    }
+
+    #[test]
+    fn the_atlas_waits_for_every_sprite() {
+        let mut app = app();
+        let ship = app.world.resource_mut::<Assets<Image>>().add(image(16));
+        //reserved but never filled in, like an image still loading
+        let laser = app.world.resource::<Assets<Image>>().reserve_handle();
+        app.insert_resource(AtlasSources(vec![
+            (SHIP_SPRITE, ship),
+            (LASER_SPRITE, laser),
+        ]));
+        app.update();
+
+        assert!(!app.world.contains_resource::<SpriteAtlas>());
+        assert!(app.world.contains_resource::<AtlasSources>());
+    }
+}
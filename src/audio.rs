@@ -0,0 +1,226 @@
+use bevy::{audio::Volume, prelude::*};
+use rand::Rng;
+
+use crate::{
+    asteroids::{AsteroidClass, AsteroidDestroyedEvent, AsteroidHitEvent},
+    collisions::apply_damage,
+    ufo::{GrazeEvent, UfoDestroyedEvent},
+    weapons::SpawnLaserEvent,
+};
+
+/// Plays the game's sound effects in response to gameplay events.
+pub struct SfxPlugin;
+
+impl Plugin for SfxPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SfxVolume>()
+            .add_systems(Startup, load_sfx)
+            .add_systems(Update, play_laser_sfx)
+            .add_systems(Update, play_impact_sfx.after(apply_damage))
+            .add_systems(Update, play_explosion_sfx)
+            .add_systems(Update, play_graze_sfx);
+    }
+}
+
+/// Volume every sound effect is played at, from 0 (muted) to 1.
+#[derive(Resource)]
+pub struct SfxVolume(pub f32);
+
+impl Default for SfxVolume {
+    fn default() -> Self {
+        Self(0.5)
+    }
+}
+
+#[derive(Resource)]
+struct SfxHandles {
+    laser: Handle<AudioSource>,
+    impact: Handle<AudioSource>,
+    explosion: Handle<AudioSource>,
+}
+
+fn load_sfx(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(SfxHandles {
+        laser: asset_server.load("audio/laser.wav"),
+        impact: asset_server.load("audio/impact.wav"),
+        explosion: asset_server.load("audio/explosion.wav"),
+    });
+}
+
+//every sound gets its own entity so rapid fire overlaps instead of cutting itself off
+fn play(commands: &mut Commands, source: &Handle<AudioSource>, volume: &SfxVolume) {
+    play_at_pitch(commands, source, volume, 1.0);
+}
+
+fn play_at_pitch(
+    commands: &mut Commands,
+    source: &Handle<AudioSource>,
+    volume: &SfxVolume,
+    pitch: f32,
+) {
+    commands.spawn(AudioBundle {
+        source: source.clone(),
+        settings: PlaybackSettings::DESPAWN
+            .with_volume(Volume::new_relative(volume.0))
+            .with_speed(pitch),
+    });
+}
+
+fn play_laser_sfx(
+    mut events: EventReader<SpawnLaserEvent>,
+    mut commands: Commands,
+    handles: Res<SfxHandles>,
+    volume: Res<SfxVolume>,
+) {
+    //a whole volley goes out in one frame, one sound is enough for it
+    if events.read().count() > 0 {
+        play(&mut commands, &handles.laser, &volume);
+    }
+}
+
+fn play_impact_sfx(
+    mut events: EventReader<AsteroidHitEvent>,
+    mut commands: Commands,
+    handles: Res<SfxHandles>,
+    volume: Res<SfxVolume>,
+) {
+    //rapid fire into a crowd lands a lot of hits in one frame, one sound covers them
+    if events.read().count() > 0 {
+        play(&mut commands, &handles.impact, &volume);
+    }
+}
+
+fn play_graze_sfx(
+    mut events: EventReader<GrazeEvent>,
+    mut commands: Commands,
+    handles: Res<SfxHandles>,
+    volume: Res<SfxVolume>,
+) {
+    //a quiet, high tick off the impact sound
+    if events.read().count() > 0 {
+        let quiet = SfxVolume(volume.0 * 0.4);
+        play_at_pitch(&mut commands, &handles.impact, &quiet, 1.8);
+    }
+}
+
+/// How fast the explosion sound plays for an asteroid of `class`, bigger ones rumble lower.
+fn explosion_pitch(class: AsteroidClass) -> f32 {
+    match class {
+        AsteroidClass::Big => 0.7,
+        AsteroidClass::Medium => 0.85,
+        AsteroidClass::Small => 1.0,
+        AsteroidClass::Tiny => 1.2,
+    }
+}
+
+fn play_explosion_sfx(
+    mut events: EventReader<AsteroidDestroyedEvent>,
+    mut ufo_events: EventReader<UfoDestroyedEvent>,
+    mut commands: Commands,
+    handles: Res<SfxHandles>,
+    volume: Res<SfxVolume>,
+) {
+    //a bomb can break up a whole field at once, one sound per class is plenty for that
+    let mut classes: Vec<AsteroidClass> = Vec::new();
+    for event in events.read() {
+        if !classes.contains(&event.class) {
+            classes.push(event.class);
+        }
+    }
+    //cosmetic jitter, so it stays off the seeded GameRng and can't shift gameplay draws
+    let mut rng = rand::thread_rng();
+    for class in classes {
+        let pitch = explosion_pitch(class) * rng.gen_range(0.95..1.05);
+        play_at_pitch(&mut commands, &handles.explosion, &volume, pitch);
+    }
+    if ufo_events.read().count() > 0 {
+        play(&mut commands, &handles.explosion, &volume);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_event::<AsteroidDestroyedEvent>()
+            .add_event::<UfoDestroyedEvent>()
+            .add_event::<AsteroidHitEvent>()
+            .add_event::<SpawnLaserEvent>()
+            .init_resource::<SfxVolume>()
+            .insert_resource(SfxHandles {
+                laser: Handle::default(),
+                impact: Handle::default(),
+                explosion: Handle::default(),
+            });
+        app
+    }
+
+    fn destroyed(app: &mut App, class: AsteroidClass) {
+        app.world.send_event(AsteroidDestroyedEvent {
+            class,
+            position: Vec2::ZERO,
+            points: 0,
+        });
+    }
+
+    fn sounds(app: &mut App) -> usize {
+        let mut sounds = app.world.query::<&PlaybackSettings>();
+        sounds.iter(&app.world).count()
+    }
+
+    #[test]
+    fn firing_plays_a_laser_sound() {
+        let mut app = app();
+        app.add_systems(Update, play_laser_sfx);
+        app.world
+            .send_event(SpawnLaserEvent::plain(Transform::default()));
+        app.update();
+
+        let mut sounds = app.world.query::<&Handle<AudioSource>>();
+        let laser = app.world.resource::<SfxHandles>().laser.clone();
+        assert_eq!(sounds.iter(&app.world).collect::<Vec<_>>(), vec![&laser]);
+    }
+
+    #[test]
+    fn a_frame_of_hits_plays_one_impact() {
+        let mut app = app();
+        app.add_systems(Update, play_impact_sfx);
+        app.update();
+        assert_eq!(sounds(&mut app), 0);
+
+        for remaining in 0..5 {
+            app.world.send_event(AsteroidHitEvent {
+                entity: Entity::PLACEHOLDER,
+                remaining,
+            });
+        }
+        app.update();
+        assert_eq!(sounds(&mut app), 1);
+    }
+
+    #[test]
+    fn bigger_asteroids_sound_lower() {
+        assert!(explosion_pitch(AsteroidClass::Big) < explosion_pitch(AsteroidClass::Medium));
+        assert!(explosion_pitch(AsteroidClass::Medium) < explosion_pitch(AsteroidClass::Small));
+        assert!(explosion_pitch(AsteroidClass::Small) < explosion_pitch(AsteroidClass::Tiny));
+    }
+
+    #[test]
+    fn mass_destruction_plays_one_sound_per_class() {
+        let mut app = app();
+        app.add_systems(Update, play_explosion_sfx);
+        for _ in 0..6 {
+            destroyed(&mut app, AsteroidClass::Big);
+        }
+        destroyed(&mut app, AsteroidClass::Tiny);
+        app.update();
+
+        let mut sounds = app.world.query::<&PlaybackSettings>();
+        let speeds: Vec<f32> = sounds.iter(&app.world).map(|s| s.speed).collect();
+        assert_eq!(speeds.len(), 2);
+        assert!(speeds.iter().any(|speed| (speed - 0.7).abs() < 0.05));
+        assert!(speeds.iter().any(|speed| (speed - 1.2).abs() < 0.07));
+    }
+}
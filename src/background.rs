@@ -0,0 +1,145 @@
+use bevy::prelude::*;
+
+use crate::asteroids::WaveStartedEvent;
+
+/// Slowly shifts the background color as the waves go on, so later waves feel deeper in.
+pub struct BackgroundPlugin;
+
+impl Plugin for BackgroundPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BackgroundPalette>()
+            .add_systems(Update, (start_background_fade, fade_background).chain());
+    }
+}
+
+/// The background color of each wave in turn, the last one holds for every wave after it. The
+/// first is what the game starts with.
+#[derive(Resource)]
+pub struct BackgroundPalette {
+    pub colors: Vec<Color>,
+    //seconds it takes to fade into the next wave's color
+    pub fade: f32,
+}
+
+impl Default for BackgroundPalette {
+    fn default() -> Self {
+        let colors = [
+            "#071c42", "#0a1a4c", "#141752", "#211350", "#2e1048", "#38103b",
+        ];
+        Self {
+            colors: colors
+                .iter()
+                .map(|hex| Color::hex(hex).expect("a valid hex color"))
+                .collect(),
+            fade: 3.0,
+        }
+    }
+}
+
+impl BackgroundPalette {
+    /// The color wave `wave` fades to, counting from 1.
+    pub fn color(&self, wave: u32) -> Color {
+        let index = wave.saturating_sub(1) as usize;
+        self.colors
+            .get(index)
+            .or(self.colors.last())
+            .copied()
+            .unwrap_or(Color::BLACK)
+    }
+}
+
+/// A fade of the clear color in progress, removed once it gets there.
+#[derive(Resource)]
+struct BackgroundFade {
+    from: Color,
+    to: Color,
+    timer: Timer,
+}
+
+fn start_background_fade(
+    mut events: EventReader<WaveStartedEvent>,
+    palette: Res<BackgroundPalette>,
+    clear_color: Res<ClearColor>,
+    mut commands: Commands,
+) {
+    let Some(event) = events.read().last() else {
+        return;
+    };
+    //from wherever it is now, a fresh run fades back to the first color
+    commands.insert_resource(BackgroundFade {
+        from: clear_color.0,
+        to: palette.color(event.wave),
+        timer: Timer::from_seconds(palette.fade, TimerMode::Once),
+    });
+}
+
+fn fade_background(
+    time: Res<Time>,
+    fade: Option<ResMut<BackgroundFade>>,
+    mut clear_color: ResMut<ClearColor>,
+    mut commands: Commands,
+) {
+    let Some(mut fade) = fade else {
+        return;
+    };
+    if fade.timer.tick(time.delta()).finished() {
+        clear_color.0 = fade.to;
+        commands.remove_resource::<BackgroundFade>();
+        return;
+    }
+    let from = Vec4::from(fade.from.as_rgba_f32());
+    let to = Vec4::from(fade.to.as_rgba_f32());
+    let [r, g, b, a] = from.lerp(to, fade.timer.percent()).to_array();
+    clear_color.0 = Color::rgba(r, g, b, a);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn first_wave_keeps_the_original_color() {
+        let palette = BackgroundPalette::default();
+        assert_eq!(palette.color(1), Color::hex("#071c42").unwrap());
+    }
+
+    #[test]
+    fn waves_past_the_end_hold_the_last_color() {
+        let palette = BackgroundPalette::default();
+        assert_eq!(palette.color(100), *palette.colors.last().unwrap());
+    }
+
+    #[test]
+    fn a_new_wave_fades_the_background_over() {
+        let mut app = App::new();
+        let palette = BackgroundPalette::default();
+        let start = palette.color(1);
+        let target = palette.color(3);
+        app.init_resource::<Time>()
+            .insert_resource(palette)
+            .insert_resource(ClearColor(start))
+            .add_event::<WaveStartedEvent>()
+            .add_systems(Update, (start_background_fade, fade_background).chain());
+        app.world.send_event(WaveStartedEvent {
+            wave: 3,
+            asteroid_count: 4,
+        });
+        app.update();
+
+        app.world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(1.5));
+        app.update();
+        let halfway = app.world.resource::<ClearColor>().0;
+        assert_ne!(halfway, start);
+        assert_ne!(halfway, target);
+
+        app.world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs(2));
+        app.update();
+        assert_eq!(app.world.resource::<ClearColor>().0, target);
+    }
+}
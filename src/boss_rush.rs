@@ -0,0 +1,286 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{
+    asteroids::EDGE_SPAWN_MARGIN,
+    highscore::{read_save, write_save},
+    movement::PlayArea,
+    run::GameState,
+    ship::PlayerRespawnEvent,
+    ufo::{spawn_ufo, UfoSettings},
+};
+
+/// A challenge mode picked from the menu: no waves and no stray UFOs, just a run of ever
+/// tougher UFO bosses one after another against the clock. The best time for a whole rush is
+/// kept as a high score of its own.
+pub struct BossRushPlugin;
+
+impl Plugin for BossRushPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BossRush>()
+            .add_systems(Startup, load_best_boss_rush)
+            //pausing leaves and re-enters Playing, so the rush starts over when the intro ends
+            .add_systems(OnExit(GameState::Intro), start_boss_rush)
+            .add_systems(
+                Update,
+                (count_boss_rush_deaths, run_boss_rush)
+                    .chain()
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(in_boss_rush),
+            );
+    }
+}
+
+const BOSS_RUSH_SAVE: &str = "bossrush";
+
+/// How many bosses make up a rush.
+pub const BOSS_COUNT: u32 = 5;
+
+//the breather before each boss comes in
+const BOSS_DELAY: Duration = Duration::from_secs(3);
+
+/// Where a boss rush is up to. Only `active` and `best` carry over from one run to the next,
+/// the rest starts over with each run.
+#[derive(Resource)]
+pub struct BossRush {
+    pub active: bool,
+    pub phase: BossRushPhase,
+    pub beaten: u32,
+    pub elapsed: Duration,
+    pub deaths: u32,
+    //the fastest finished rush, None until there is one
+    pub best: Option<Duration>,
+}
+
+impl Default for BossRush {
+    fn default() -> Self {
+        Self {
+            active: false,
+            phase: BossRushPhase::incoming(),
+            beaten: 0,
+            elapsed: Duration::ZERO,
+            deaths: 0,
+            best: None,
+        }
+    }
+}
+
+impl BossRush {
+    /// The boss being fought or coming up next, counting from 1.
+    pub fn current_boss(&self) -> u32 {
+        (self.beaten + 1).min(BOSS_COUNT)
+    }
+}
+
+/// The boss rush steps through these for each boss, and ends up `Cleared` after the last.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BossRushPhase {
+    Incoming(Timer),
+    Fighting,
+    Cleared,
+}
+
+impl BossRushPhase {
+    fn incoming() -> Self {
+        BossRushPhase::Incoming(Timer::new(BOSS_DELAY, TimerMode::Once))
+    }
+}
+
+/// A UFO sent in by the boss rush.
+#[derive(Component)]
+pub struct Boss;
+
+/// Run condition, true while the run is a boss rush.
+pub fn in_boss_rush(rush: Res<BossRush>) -> bool {
+    rush.active
+}
+
+fn load_best_boss_rush(mut rush: ResMut<BossRush>) {
+    //nothing saved yet means no rush has been finished
+    let Some(saved) = read_save(BOSS_RUSH_SAVE) else {
+        return;
+    };
+    match saved.trim().parse::<f32>() {
+        Ok(seconds) if seconds > 0.0 => rush.best = Some(Duration::from_secs_f32(seconds)),
+        _ => warn!("ignoring unreadable boss rush time {:?}", saved),
+    }
+}
+
+fn start_boss_rush(mut rush: ResMut<BossRush>) {
+    *rush = BossRush {
+        active: rush.active,
+        best: rush.best,
+        ..default()
+    };
+}
+
+fn count_boss_rush_deaths(mut events: EventReader<PlayerRespawnEvent>, mut rush: ResMut<BossRush>) {
+    rush.deaths += events.read().count() as u32;
+}
+
+/// Where boss `number` comes in, going round the edges of the play area.
+fn boss_origin(play_area: &PlayArea, number: u32) -> Vec2 {
+    let reach = play_area.half_extents + Vec2::splat(EDGE_SPAWN_MARGIN);
+    match number % 4 {
+        1 => Vec2::new(0.0, reach.y),
+        2 => Vec2::new(reach.x, 0.0),
+        3 => Vec2::new(0.0, -reach.y),
+        _ => Vec2::new(-reach.x, 0.0),
+    }
+}
+
+fn run_boss_rush(
+    time: Res<Time>,
+    mut rush: ResMut<BossRush>,
+    bosses: Query<(), With<Boss>>,
+    settings: Res<UfoSettings>,
+    play_area: Res<PlayArea>,
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if rush.phase == BossRushPhase::Cleared {
+        return;
+    }
+    rush.elapsed += time.delta();
+    match &mut rush.phase {
+        BossRushPhase::Incoming(timer) => {
+            if !timer.tick(time.delta()).finished() {
+                return;
+            }
+            let number = rush.current_boss();
+            info!("boss {} incoming", number);
+            //each one tougher and bigger than the last
+            let health = settings.health * (number as i32 + 1);
+            let scale = 0.4 + 0.1 * number as f32;
+            let origin = boss_origin(&play_area, number);
+            spawn_ufo(&mut commands, &asset_server, origin, health, scale).insert(Boss);
+            rush.phase = BossRushPhase::Fighting;
+        }
+        BossRushPhase::Fighting => {
+            if !bosses.is_empty() {
+                return;
+            }
+            rush.beaten += 1;
+            if rush.beaten < BOSS_COUNT {
+                rush.phase = BossRushPhase::incoming();
+                return;
+            }
+            rush.phase = BossRushPhase::Cleared;
+            finish_boss_rush(&mut *rush);
+            next_state.set(GameState::GameOver);
+        }
+        BossRushPhase::Cleared => {}
+    }
+}
+
+fn finish_boss_rush(rush: &mut BossRush) {
+    info!(
+        "boss rush cleared in {:.1}s with {} deaths",
+        rush.elapsed.as_secs_f32(),
+        rush.deaths
+    );
+    if rush.best.is_some_and(|best| best <= rush.elapsed) {
+        return;
+    }
+    rush.best = Some(rush.elapsed);
+    let seconds = rush.elapsed.as_secs_f32().to_string();
+    if let Err(error) = write_save(BOSS_RUSH_SAVE, &seconds) {
+        warn!("could not save boss rush time: {}", error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::ufo::Ufo;
+
+    fn rush_app() -> App {
+        let mut app = App::new();
+        //bosses load their sprite
+        app.add_plugins((TaskPoolPlugin::default(), AssetPlugin::default()))
+            .init_asset::<Image>()
+            .init_resource::<Time>()
+            .init_resource::<UfoSettings>()
+            .init_resource::<PlayArea>()
+            .add_state::<GameState>()
+            .insert_resource(BossRush {
+                active: true,
+                ..default()
+            })
+            .add_event::<PlayerRespawnEvent>()
+            .add_systems(Update, (count_boss_rush_deaths, run_boss_rush).chain());
+        app
+    }
+
+    fn advance(app: &mut App, seconds: u64) {
+        app.world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs(seconds));
+        app.update();
+    }
+
+    /// Waits out the breather, then shoots down the boss that comes in.
+    fn beat_next_boss(app: &mut App) -> i32 {
+        advance(app, 3);
+        let mut bosses = app.world.query_filtered::<(Entity, &Ufo), With<Boss>>();
+        let (boss, ufo) = bosses.single(&app.world);
+        let health = ufo.health;
+        app.world.despawn(boss);
+        advance(app, 1);
+        health
+    }
+
+    #[test]
+    fn bosses_come_one_after_another_until_the_rush_is_cleared() {
+        let mut app = rush_app();
+        let mut healths = Vec::new();
+        for number in 1..=BOSS_COUNT {
+            assert_eq!(app.world.resource::<BossRush>().current_boss(), number);
+            healths.push(beat_next_boss(&mut app));
+        }
+        //every boss takes more beating than the one before
+        assert!(healths.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let rush = app.world.resource::<BossRush>();
+        assert_eq!(rush.phase, BossRushPhase::Cleared);
+        assert_eq!(rush.beaten, BOSS_COUNT);
+        assert_eq!(rush.elapsed, Duration::from_secs(4 * BOSS_COUNT as u64));
+        assert_eq!(rush.best, Some(rush.elapsed));
+        assert_eq!(read_save(BOSS_RUSH_SAVE).as_deref(), Some("20"));
+        app.update();
+        assert_eq!(
+            *app.world.resource::<State<GameState>>(),
+            GameState::GameOver
+        );
+    }
+
+    #[test]
+    fn a_slower_rush_keeps_the_best_time() {
+        write_save(BOSS_RUSH_SAVE, "12.5").unwrap();
+        let mut app = rush_app();
+        app.world.run_system_once(load_best_boss_rush);
+        for _ in 0..BOSS_COUNT {
+            beat_next_boss(&mut app);
+        }
+
+        let rush = app.world.resource::<BossRush>();
+        assert_eq!(rush.best, Some(Duration::from_secs_f32(12.5)));
+        assert_eq!(read_save(BOSS_RUSH_SAVE).as_deref(), Some("12.5"));
+    }
+
+    #[test]
+    fn deaths_during_the_rush_are_counted() {
+        let mut app = rush_app();
+        for _ in 0..2 {
+            app.world.send_event(PlayerRespawnEvent {
+                position: Vec2::ZERO,
+            });
+        }
+        app.update();
+        assert_eq!(app.world.resource::<BossRush>().deaths, 2);
+    }
+}
@@ -0,0 +1,130 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    explosion::SpawnExplosionEvent,
+    run::{GameState, MainCamera},
+    ship::PlayerDamagedEvent,
+};
+
+/// Shakes the camera on explosions and when the ship takes a hit.
+pub struct CameraShakePlugin;
+
+impl Plugin for CameraShakePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraShakeSettings>()
+            .add_systems(Update, add_trauma)
+            .add_systems(
+                Update,
+                shake_camera
+                    .after(add_trauma)
+                    .run_if(not(in_state(GameState::Paused))),
+            );
+    }
+}
+
+/// How hard things shake, trauma goes from 0 (still) to 1 (`max_offset` at most).
+#[derive(Resource)]
+pub struct CameraShakeSettings {
+    pub max_offset: f32,
+    //trauma lost per second
+    pub decay: f32,
+    //trauma added per unit of explosion scale
+    pub explosion_trauma: f32,
+    //trauma added per point of damage taken
+    pub damage_trauma: f32,
+}
+
+impl Default for CameraShakeSettings {
+    fn default() -> Self {
+        Self {
+            max_offset: 12.0,
+            decay: 1.5,
+            explosion_trauma: 0.15,
+            damage_trauma: 0.4,
+        }
+    }
+}
+
+#[derive(Component, Default)]
+pub struct CameraShake {
+    pub trauma: f32,
+    //the offset applied last frame, taken back off before the next one so the camera never drifts
+    offset: Vec2,
+}
+
+fn add_trauma(
+    settings: Res<CameraShakeSettings>,
+    mut explosions: EventReader<SpawnExplosionEvent>,
+    mut damage: EventReader<PlayerDamagedEvent>,
+    mut cameras: Query<&mut CameraShake, With<MainCamera>>,
+) {
+    let mut trauma = 0.0;
+    for event in explosions.read() {
+        trauma += event.scale * settings.explosion_trauma;
+    }
+    for event in damage.read() {
+        trauma += event.amount as f32 * settings.damage_trauma;
+    }
+    if trauma <= 0.0 {
+        return;
+    }
+    for mut shake in cameras.iter_mut() {
+        shake.trauma = (shake.trauma + trauma).min(1.0);
+    }
+}
+
+fn shake_camera(
+    time: Res<Time>,
+    settings: Res<CameraShakeSettings>,
+    mut cameras: Query<(&mut Transform, &mut CameraShake), With<MainCamera>>,
+) {
+    let mut rng = rand::thread_rng();
+    for (mut transform, mut shake) in cameras.iter_mut() {
+        let base = transform.translation.xy() - shake.offset;
+        shake.trauma = (shake.trauma - settings.decay * time.delta_seconds()).max(0.0);
+        //squaring keeps small knocks subtle while big ones still feel big
+        let strength = shake.trauma * shake.trauma * settings.max_offset;
+        shake.offset = Vec2::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)) * strength;
+        transform.translation = (base + shake.offset).extend(transform.translation.z);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn a_shake_settles_back_where_it_started() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<CameraShakeSettings>()
+            .add_systems(Update, shake_camera);
+        let camera = app
+            .world
+            .spawn((
+                MainCamera,
+                Transform::default(),
+                CameraShake {
+                    trauma: 1.0,
+                    ..default()
+                },
+            ))
+            .id();
+        let step = |app: &mut App, millis| {
+            app.world
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_millis(millis));
+            app.update();
+            app.world.get::<Transform>(camera).unwrap().translation.xy()
+        };
+
+        assert!(step(&mut app, 16).length() > 0.0);
+        //a full second of decay is more than any trauma lasts
+        step(&mut app, 1000);
+        assert!(step(&mut app, 16).length() < 0.001);
+        assert_eq!(app.world.get::<CameraShake>(camera).unwrap().trauma, 0.0);
+    }
+}
@@ -0,0 +1,699 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use bevy_xpbd_2d::prelude::*;
+use rand::Rng;
+
+use crate::{
+    asteroids::{Armored, AsteroidClass, AsteroidHealth, AsteroidHitEvent, WeakSpot},
+    cooldown::{tick_cooldowns, Cooldown},
+    lifetime::{FadeOut, Lifetime},
+    run::{GameEntity, GameState},
+    shield::{Shield, ShieldBrokenEvent},
+    ship::{CollisionDamage, Invulnerable, PlayerDamagedEvent, Ship, ShipHealth},
+    ufo::Ufo,
+    weapons::{Damage, Laser, PiercedTargets, Piercing, Retired},
+};
+
+/// Resolves contacts between the ship, lasers and asteroids, and throws sparks where lasers hit.
+/// Power-ups are picked up by distance in [`crate::powerup`] instead.
+pub struct CollisionPlugin;
+
+impl Plugin for CollisionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, tick_cooldowns::<CollisionDamage>)
+            .add_systems(
+                Update,
+                handle_collisions
+                    .after(tick_cooldowns::<CollisionDamage>)
+                    .run_if(not(in_state(GameState::Paused))),
+            )
+            .add_event::<DamageEvent>()
+            .add_systems(
+                Update,
+                apply_damage
+                    .after(handle_collisions)
+                    .run_if(not(in_state(GameState::Paused))),
+            )
+            .add_event::<SpawnSparksEvent>()
+            .add_systems(Update, spark_spawner.after(handle_collisions))
+            .init_resource::<SweepSettings>()
+            .add_systems(
+                Update,
+                sweep_fast_lasers.run_if(not(in_state(GameState::Paused))),
+            );
+    }
+}
+
+// Define the collision layers
+//friendly fire is off: nothing on the player's side collides with the player's own lasers
+#[derive(PhysicsLayer)]
+pub enum Layer {
+    Player,
+    PlayerBullet,
+    //ufos
+    Enemy,
+    //ufo shots, which hit the player and asteroids
+    EnemyBullet,
+    Asteroid,
+}
+
+/// Fast lasers can skip clean over a small asteroid between two physics steps. Lasers faster
+/// than `min_speed` look ahead along their path each frame and are moved onto whatever they
+/// would have passed through, so the usual contact still happens.
+#[derive(Resource)]
+pub struct SweepSettings {
+    pub enabled: bool,
+    pub min_speed: f32,
+}
+
+impl Default for SweepSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_speed: 300.0,
+        }
+    }
+}
+
+fn sweep_fast_lasers(
+    time: Res<Time>,
+    settings: Res<SweepSettings>,
+    spatial_query: SpatialQuery,
+    mut lasers: Query<(&mut Transform, &LinearVelocity, Option<&PiercedTargets>), With<Laser>>,
+) {
+    if !settings.enabled {
+        return;
+    }
+    for (mut transform, velocity, pierced) in lasers.iter_mut() {
+        let speed = velocity.0.length();
+        if speed < settings.min_speed {
+            continue;
+        }
+        //the same layers a laser collides with, minus what a piercing one already went through
+        let mut filter = SpatialQueryFilter::new().with_masks([Layer::Asteroid, Layer::Enemy]);
+        if let Some(pierced) = pierced {
+            filter = filter.without_entities(pierced.0.iter().copied());
+        }
+        let Some(hit) = spatial_query.cast_ray(
+            transform.translation.xy(),
+            velocity.0 / speed,
+            speed * time.delta_seconds(),
+            true,
+            filter,
+        ) else {
+            continue;
+        };
+        //already touching, the physics will report it without help
+        if hit.time_of_impact <= 0.0 {
+            continue;
+        }
+        let step = velocity.0 / speed * hit.time_of_impact;
+        transform.translation += step.extend(0.0);
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum EntityTypes {
+    Asteroid,
+    Laser,
+    Ship,
+    Unknown,
+}
+
+pub fn handle_collisions(
+    mut events: EventReader<Collision>,
+    ships: Query<(), With<Ship>>,
+    lasers: Query<&Damage, With<Laser>>,
+    mut piercing: Query<(&mut Piercing, &mut PiercedTargets)>,
+    asteroids: Query<&AsteroidHealth, With<AsteroidClass>>,
+    transforms: Query<&Transform>,
+    mut spark_writer: EventWriter<SpawnSparksEvent>,
+    weak_spots: Query<&Parent, With<WeakSpot>>,
+    armored: Query<(), With<Armored>>,
+    mut damage_writer: EventWriter<DamageEvent>,
+    mut commands: Commands,
+) {
+    for event in events.read() {
+        // info!(
+        //     "{:?} and {:?} are colliding",
+        //     event.0.entity1, event.0.entity2
+        // );
+
+        //lasers hitting a weak spot damage the armored asteroid it belongs to
+        let weak_spot_hit = match (
+            weak_spots.get(event.0.entity1),
+            weak_spots.get(event.0.entity2),
+        ) {
+            (Ok(parent), _) if lasers.contains(event.0.entity2) => {
+                Some((event.0.entity2, parent.get()))
+            }
+            (_, Ok(parent)) if lasers.contains(event.0.entity1) => {
+                Some((event.0.entity1, parent.get()))
+            }
+            _ => None,
+        };
+        if let Some((laser, asteroid)) = weak_spot_hit {
+            if spend_laser(laser, asteroid, &mut piercing, &mut commands) {
+                if let Some(sparks) = SpawnSparksEvent::at_impact(laser, asteroid, &transforms) {
+                    spark_writer.send(sparks);
+                }
+                damage_writer.send(DamageEvent {
+                    target: asteroid,
+                    amount: lasers.get(laser).map_or(1, |damage| damage.0),
+                    source: laser,
+                });
+            }
+            continue;
+        }
+
+        let kinds = [
+            (
+                event.0.entity1,
+                classify(event.0.entity1, &ships, &lasers, &asteroids),
+            ),
+            (
+                event.0.entity2,
+                classify(event.0.entity2, &ships, &lasers, &asteroids),
+            ),
+        ];
+        //look at the pair both ways round, so only the asteroid-first order needs handling
+        for ((a, kind_a), (b, kind_b)) in [(kinds[0], kinds[1]), (kinds[1], kinds[0])] {
+            match (kind_a, kind_b) {
+                (EntityTypes::Asteroid, EntityTypes::Laser) => {
+                    //use up the laser and decrement health of asteroid
+                    if !spend_laser(b, a, &mut piercing, &mut commands) {
+                        continue;
+                    }
+                    if let Some(sparks) = SpawnSparksEvent::at_impact(b, a, &transforms) {
+                        spark_writer.send(sparks);
+                    }
+                    //armored asteroids shrug off hits anywhere but their weak spot
+                    if !armored.contains(a) {
+                        damage_writer.send(DamageEvent {
+                            target: a,
+                            amount: lasers.get(b).map_or(1, |damage| damage.0),
+                            source: b,
+                        });
+                    }
+                }
+                (EntityTypes::Asteroid, EntityTypes::Ship) => {
+                    damage_writer.send(DamageEvent {
+                        target: b,
+                        amount: 1,
+                        source: a,
+                    });
+                    //the asteroid doesn't survive ramming the ship
+                    if let Ok(health) = asteroids.get(a) {
+                        if health.0 > 0 {
+                            damage_writer.send(DamageEvent {
+                                target: a,
+                                amount: health.0,
+                                source: b,
+                            });
+                        }
+                    }
+                }
+                //asteroids just bounce off each other
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Works out which kind of entity took part in a collision.
+fn classify(
+    entity: Entity,
+    ships: &Query<(), With<Ship>>,
+    lasers: &Query<&Damage, With<Laser>>,
+    asteroids: &Query<&AsteroidHealth, With<AsteroidClass>>,
+) -> EntityTypes {
+    if asteroids.contains(entity) {
+        EntityTypes::Asteroid
+    } else if lasers.contains(entity) {
+        EntityTypes::Laser
+    } else if ships.contains(entity) {
+        EntityTypes::Ship
+    } else {
+        EntityTypes::Unknown
+    }
+}
+
+/// Sent when something should take health off a ship, an asteroid or a ufo, applied by
+/// [`apply_damage`].
+#[derive(Event)]
+pub struct DamageEvent {
+    pub target: Entity,
+    pub amount: i32,
+    //whatever dealt the damage
+    pub source: Entity,
+}
+
+pub fn apply_damage(
+    mut events: EventReader<DamageEvent>,
+    mut asteroids: Query<&mut AsteroidHealth>,
+    mut ships: Query<(
+        &mut ShipHealth,
+        &mut Cooldown<CollisionDamage>,
+        Option<&Invulnerable>,
+        Option<&mut Shield>,
+    )>,
+    mut ufos: Query<&mut Ufo>,
+    mut hit_writer: EventWriter<AsteroidHitEvent>,
+    mut damage_writer: EventWriter<PlayerDamagedEvent>,
+    mut shield_writer: EventWriter<ShieldBrokenEvent>,
+) {
+    for event in events.read() {
+        if let Ok(mut ufo) = ufos.get_mut(event.target) {
+            ufo.health -= event.amount;
+        } else if let Ok(mut health) = asteroids.get_mut(event.target) {
+            health.0 -= event.amount;
+            hit_writer.send(AsteroidHitEvent {
+                entity: event.target,
+                remaining: health.0,
+            });
+        } else if let Ok(mut ship) = ships.get_mut(event.target) {
+            //one sustained overlap only counts once per damage window
+            if ship.2.is_some() || !ship.1.trigger() {
+                continue;
+            }
+            if ship.3.as_deref_mut().is_some_and(Shield::absorb) {
+                shield_writer.send(ShieldBrokenEvent { ship: event.target });
+            } else {
+                ship.0 .0 -= event.amount;
+                damage_writer.send(PlayerDamagedEvent {
+                    amount: event.amount,
+                    remaining: ship.0 .0,
+                    source: event.source,
+                });
+            }
+        }
+    }
+}
+
+/// Retires a laser that hit `target`, unless it can still pierce through. Returns false
+/// when a piercing laser is still passing through a target it already hit.
+pub fn spend_laser(
+    laser: Entity,
+    target: Entity,
+    piercing: &mut Query<(&mut Piercing, &mut PiercedTargets)>,
+    commands: &mut Commands,
+) -> bool {
+    match piercing.get_mut(laser) {
+        Ok((mut piercing, mut pierced)) => {
+            //contact lasts a few frames, only the first one counts
+            if pierced.0.contains(&target) {
+                return false;
+            }
+            pierced.0.push(target);
+            if piercing.0 == 0 {
+                commands.entity(laser).insert(Retired);
+            } else {
+                piercing.0 -= 1;
+            }
+            true
+        }
+        Err(_) => {
+            commands.entity(laser).insert(Retired);
+            true
+        }
+    }
+}
+
+/// An event sent when a laser hits something and should throw sparks.
+#[derive(Event)]
+pub struct SpawnSparksEvent {
+    pub position: Vec2,
+    //the direction the sparks fly out in
+    pub normal: Vec2,
+}
+
+impl SpawnSparksEvent {
+    /// Sparks at the laser's position, flying away from the center of what it hit.
+    fn at_impact(laser: Entity, target: Entity, transforms: &Query<&Transform>) -> Option<Self> {
+        let laser = transforms.get(laser).ok()?.translation.xy();
+        let target = transforms.get(target).ok()?.translation.xy();
+        Some(SpawnSparksEvent {
+            position: laser,
+            normal: (laser - target).normalize_or_zero(),
+        })
+    }
+}
+
+#[derive(Component)]
+pub struct Spark;
+
+fn spark_spawner(mut reader: EventReader<SpawnSparksEvent>, mut commands: Commands) {
+    let mut rng = rand::thread_rng();
+    //keep this low, rapid fire makes a lot of hits
+    let count = 4;
+    let spread = PI / 4.0;
+    for event in reader.read() {
+        let base = event.normal.y.atan2(event.normal.x);
+        for _ in 0..count {
+            let angle = base + rng.gen_range(-spread..spread);
+            let speed = rng.gen_range(100.0..200.0);
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::rgb(1.0, 0.85, 0.4),
+                        custom_size: Some(Vec2::splat(3.0)),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(event.position.extend(1.0)),
+                    ..default()
+                },
+                RigidBody::Kinematic,
+                LinearVelocity(Vec2::new(angle.cos(), angle.sin()) * speed),
+                Lifetime(Timer::from_seconds(0.25, TimerMode::Once)),
+                FadeOut(1.0),
+                Spark,
+                GameEntity,
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use bevy::ecs::event::ManualEventReader;
+
+    use super::*;
+
+    /// A collision between `a` and `b`, reported in that order.
+    pub(crate) fn collision(a: Entity, b: Entity) -> Collision {
+        Collision(Contacts {
+            entity1: a,
+            entity2: b,
+            manifolds: Vec::new(),
+            during_current_frame: true,
+            during_current_substep: true,
+            total_normal_impulse: 0.0,
+            total_tangent_impulse: 0.0,
+        })
+    }
+
+    fn collision_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_event::<Collision>()
+            .add_event::<DamageEvent>()
+            .add_event::<SpawnSparksEvent>()
+            .add_event::<AsteroidHitEvent>()
+            .add_event::<PlayerDamagedEvent>()
+            .add_event::<ShieldBrokenEvent>()
+            .add_systems(
+                Update,
+                (
+                    tick_cooldowns::<CollisionDamage>,
+                    handle_collisions,
+                    apply_damage,
+                )
+                    .chain(),
+            );
+        app
+    }
+
+    fn laser(app: &mut App) -> Entity {
+        app.world
+            .spawn((Laser, Damage(1), Transform::default()))
+            .id()
+    }
+
+    fn health(app: &App, asteroid: Entity) -> i32 {
+        app.world.get::<AsteroidHealth>(asteroid).unwrap().0
+    }
+
+    fn asteroid(app: &mut App, health: i32) -> Entity {
+        app.world
+            .spawn((
+                AsteroidClass::Big,
+                AsteroidHealth(health),
+                Transform::default(),
+            ))
+            .id()
+    }
+
+    fn ship(app: &mut App) -> Entity {
+        app.world
+            .spawn((
+                Ship,
+                ShipHealth(5),
+                Cooldown::<CollisionDamage>::new(crate::ship::DAMAGE_COOLDOWN),
+                Transform::default(),
+            ))
+            .id()
+    }
+
+    /// Collides the two entities `setup` spawns in a fresh app, once reported each way round,
+    /// and checks the outcome is the same both times.
+    fn both_orders(
+        setup: impl Fn(&mut App) -> (Entity, Entity),
+        check: impl Fn(&mut App, Entity, Entity),
+    ) {
+        for flipped in [false, true] {
+            let mut app = collision_app();
+            let (a, b) = setup(&mut app);
+            let (first, second) = if flipped { (b, a) } else { (a, b) };
+            app.world.send_event(collision(first, second));
+            app.update();
+            check(&mut app, a, b);
+        }
+    }
+
+    /// Every damage event sent so far, as (target, amount, source).
+    fn damage_dealt(app: &App) -> Vec<(Entity, i32, Entity)> {
+        let events = app.world.resource::<Events<DamageEvent>>();
+        ManualEventReader::<DamageEvent>::default()
+            .read(events)
+            .map(|event| (event.target, event.amount, event.source))
+            .collect()
+    }
+
+    #[test]
+    fn a_laser_deals_the_same_damage_either_way_round() {
+        both_orders(
+            |app| {
+                let heavy = app
+                    .world
+                    .spawn((Laser, Damage(2), Transform::default()))
+                    .id();
+                (asteroid(app, 3), heavy)
+            },
+            |app, asteroid, laser| {
+                assert_eq!(damage_dealt(app), vec![(asteroid, 2, laser)]);
+            },
+        );
+    }
+
+    #[test]
+    fn a_ram_deals_the_same_damage_either_way_round() {
+        both_orders(
+            |app| (ship(app), asteroid(app, 3)),
+            |app, ship, asteroid| {
+                assert_eq!(
+                    damage_dealt(app),
+                    vec![(ship, 1, asteroid), (asteroid, 3, ship)]
+                );
+            },
+        );
+    }
+
+    #[test]
+    fn a_laser_hit_costs_the_asteroid_health_and_the_laser() {
+        both_orders(
+            |app| (asteroid(app, 3), laser(app)),
+            |app, asteroid, laser| {
+                assert_eq!(health(app, asteroid), 2);
+                assert!(app.world.get::<Retired>(laser).is_some());
+            },
+        );
+    }
+
+    #[test]
+    fn ramming_hurts_the_ship_and_breaks_the_asteroid() {
+        both_orders(
+            |app| (ship(app), asteroid(app, 3)),
+            |app, ship, asteroid| {
+                assert_eq!(app.world.get::<ShipHealth>(ship).unwrap().0, 4);
+                assert_eq!(health(app, asteroid), 0);
+            },
+        );
+    }
+
+    #[test]
+    fn asteroids_bounce_off_each_other() {
+        both_orders(
+            |app| (asteroid(app, 3), asteroid(app, 3)),
+            |app, a, b| {
+                assert_eq!(health(app, a), 3);
+                assert_eq!(health(app, b), 3);
+            },
+        );
+    }
+
+    #[test]
+    fn the_ship_flies_through_its_own_lasers() {
+        both_orders(
+            |app| (ship(app), laser(app)),
+            |app, ship, laser| {
+                assert_eq!(app.world.get::<ShipHealth>(ship).unwrap().0, 5);
+                assert!(app.world.get::<Retired>(laser).is_none());
+            },
+        );
+    }
+
+    #[test]
+    fn a_shield_takes_the_hit_instead_of_the_hull() {
+        let mut app = collision_app();
+        let ship = ship(&mut app);
+        app.world.entity_mut(ship).insert(Shield { active: true });
+        let asteroid = asteroid(&mut app, 3);
+        app.world.send_event(collision(ship, asteroid));
+        app.update();
+
+        assert_eq!(app.world.get::<ShipHealth>(ship).unwrap().0, 5);
+        assert!(!app.world.get::<Shield>(ship).unwrap().active);
+        let events = app.world.resource::<Events<ShieldBrokenEvent>>();
+        assert_eq!(
+            ManualEventReader::<ShieldBrokenEvent>::default()
+                .read(events)
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn a_heavy_laser_breaks_a_big_asteroid_in_one_hit() {
+        let mut app = collision_app();
+        let asteroid = app
+            .world
+            .spawn((AsteroidClass::Big, AsteroidHealth(5), Transform::default()))
+            .id();
+        let heavy = app
+            .world
+            .spawn((Laser, Damage(5), Transform::default()))
+            .id();
+        app.world.send_event(collision(heavy, asteroid));
+        app.update();
+
+        assert_eq!(health(&app, asteroid), 0);
+        let events = app.world.resource::<Events<AsteroidHitEvent>>();
+        let hits: Vec<i32> = ManualEventReader::<AsteroidHitEvent>::default()
+            .read(events)
+            .map(|hit| hit.remaining)
+            .collect();
+        assert_eq!(hits, vec![0]);
+    }
+
+    #[test]
+    fn armor_only_gives_way_at_the_weak_spot() {
+        let mut app = collision_app();
+        let asteroid = app
+            .world
+            .spawn((
+                AsteroidClass::Big,
+                AsteroidHealth(5),
+                Armored { bonus: 0 },
+                Transform::default(),
+            ))
+            .id();
+        let spot = app.world.spawn((WeakSpot, Transform::default())).id();
+        app.world.entity_mut(asteroid).add_child(spot);
+
+        let body_shot = laser(&mut app);
+        app.world.send_event(collision(asteroid, body_shot));
+        app.update();
+        assert_eq!(health(&app, asteroid), 5);
+
+        let spot_shot = laser(&mut app);
+        app.world.send_event(collision(spot_shot, spot));
+        app.update();
+        assert_eq!(health(&app, asteroid), 4);
+    }
+
+    #[test]
+    fn a_sustained_overlap_only_hurts_once_per_window() {
+        let mut app = collision_app();
+        let ship = app
+            .world
+            .spawn((
+                Ship,
+                ShipHealth(5),
+                Cooldown::<CollisionDamage>::new(crate::ship::DAMAGE_COOLDOWN),
+                Transform::default(),
+            ))
+            .id();
+        let asteroid = app
+            .world
+            .spawn((
+                AsteroidClass::Big,
+                AsteroidHealth(100),
+                Transform::default(),
+            ))
+            .id();
+        let frame = std::time::Duration::from_millis(16);
+        //the physics reports the overlap every frame it lasts
+        for _ in 0..10 {
+            app.world.resource_mut::<Time>().advance_by(frame);
+            app.world.send_event(collision(ship, asteroid));
+            app.update();
+        }
+        assert_eq!(app.world.get::<ShipHealth>(ship).unwrap().0, 4);
+
+        //still touching once the window is over hurts again
+        for _ in 0..25 {
+            app.world.resource_mut::<Time>().advance_by(frame);
+            app.world.send_event(collision(ship, asteroid));
+            app.update();
+        }
+        assert_eq!(app.world.get::<ShipHealth>(ship).unwrap().0, 3);
+    }
+
+    #[test]
+    fn a_fast_laser_through_a_tiny_asteroid_hits_it() {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            TransformPlugin,
+            HierarchyPlugin,
+            PhysicsPlugins::default(),
+        ))
+        .insert_resource(bevy::time::TimeUpdateStrategy::ManualDuration(
+            std::time::Duration::from_millis(16),
+        ))
+        .insert_resource(Gravity(Vec2::ZERO))
+        .init_resource::<SweepSettings>()
+        .add_event::<DamageEvent>()
+        .add_event::<SpawnSparksEvent>()
+        .add_systems(Update, (sweep_fast_lasers, handle_collisions).chain());
+        let asteroid = app
+            .world
+            .spawn((
+                AsteroidClass::Tiny,
+                AsteroidHealth(AsteroidClass::Tiny.max_health()),
+                RigidBody::Dynamic,
+                Collider::ball(AsteroidClass::Tiny.collider_radius()),
+                CollisionLayers::new([Layer::Asteroid], [Layer::PlayerBullet]),
+                TransformBundle::default(),
+            ))
+            .id();
+        //the real bundle, so the sweep goes through the layers the game uses, several hundred
+        //units a frame and starting well short of the asteroid
+        app.world
+            .spawn(crate::weapons::LaserBoltBundle::default())
+            .insert((
+                LinearVelocity(Vec2::Y * 20000.0),
+                Transform::from_xyz(0.0, -200.0, 0.0),
+            ));
+
+        let mut damage = ManualEventReader::<DamageEvent>::default();
+        let mut hits = Vec::new();
+        for _ in 0..5 {
+            app.update();
+            let events = app.world.resource::<Events<DamageEvent>>();
+            hits.extend(damage.read(events).map(|event| event.target));
+        }
+        assert_eq!(hits, vec![asteroid]);
+    }
+}
@@ -0,0 +1,152 @@
+use std::{marker::PhantomData, time::Duration};
+
+use bevy::prelude::*;
+
+/// A reusable cooldown for an ability. `T` names the ability so one entity can
+/// carry several independent cooldowns.
+#[derive(Component)]
+pub struct Cooldown<T: Send + Sync + 'static> {
+    timer: Timer,
+    //how far past its end the tick that made it ready went, zero on every other tick, carried
+    //into the next cooldown so abilities used back to back keep their rate at any frame rate
+    overshoot: Duration,
+    ability: PhantomData<T>,
+}
+
+impl<T: Send + Sync + 'static> Cooldown<T> {
+    /// Creates a cooldown that is ready to use straight away.
+    pub fn new(duration: Duration) -> Self {
+        let mut timer = Timer::new(duration, TimerMode::Once);
+        timer.tick(duration);
+        Self {
+            timer,
+            overshoot: Duration::ZERO,
+            ability: PhantomData,
+        }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.timer.finished()
+    }
+
+    /// How far into the current cooldown it is.
+    pub fn elapsed(&self) -> Duration {
+        self.timer.elapsed()
+    }
+
+    pub fn set_duration(&mut self, duration: Duration) {
+        self.timer.set_duration(duration);
+    }
+
+    /// Restarts the cooldown whether or not it was ready.
+    pub fn restart(&mut self) {
+        self.timer.reset();
+        self.overshoot = Duration::ZERO;
+    }
+
+    /// Uses the ability if it is ready, restarting the cooldown. Returns whether it was used.
+    pub fn trigger(&mut self) -> bool {
+        if self.is_ready() {
+            self.timer.reset();
+            let overshoot = std::mem::take(&mut self.overshoot).min(self.timer.duration());
+            self.timer.tick(overshoot);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Ticks every `Cooldown<T>`, added by whichever plugin owns the ability `T`.
+pub fn tick_cooldowns<T: Send + Sync + 'static>(
+    time: Res<Time>,
+    mut cooldowns: Query<&mut Cooldown<T>>,
+) {
+    let delta = time.delta();
+    for mut cooldown in cooldowns.iter_mut() {
+        let remaining = cooldown.timer.remaining();
+        let was_ready = cooldown.timer.finished();
+        cooldown.timer.tick(delta);
+        //a cooldown that was already sitting ready has nothing to carry over
+        cooldown.overshoot = if was_ready {
+            Duration::ZERO
+        } else {
+            delta.saturating_sub(remaining)
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    enum Ability {}
+
+    fn advance(app: &mut App, delta: Duration) {
+        app.world.resource_mut::<Time>().advance_by(delta);
+        app.update();
+    }
+
+    fn cooldown_app(duration: Duration) -> (App, Entity) {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_systems(Update, tick_cooldowns::<Ability>);
+        let entity = app.world.spawn(Cooldown::<Ability>::new(duration)).id();
+        (app, entity)
+    }
+
+    fn cooldown(app: &mut App, entity: Entity) -> Mut<Cooldown<Ability>> {
+        app.world.get_mut::<Cooldown<Ability>>(entity).unwrap()
+    }
+
+    #[test]
+    fn cooldown_starts_ready() {
+        assert!(Cooldown::<Ability>::new(Duration::from_secs(1)).is_ready());
+    }
+
+    #[test]
+    fn trigger_only_fires_when_ready() {
+        let mut cooldown = Cooldown::<Ability>::new(Duration::from_secs(1));
+        assert!(cooldown.trigger());
+        assert!(!cooldown.is_ready());
+        assert!(!cooldown.trigger());
+    }
+
+    #[test]
+    fn cooldown_is_ready_again_after_its_duration() {
+        let (mut app, entity) = cooldown_app(Duration::from_millis(500));
+        assert!(cooldown(&mut app, entity).trigger());
+        advance(&mut app, Duration::from_millis(300));
+        assert!(!cooldown(&mut app, entity).is_ready());
+        advance(&mut app, Duration::from_millis(300));
+        assert!(cooldown(&mut app, entity).trigger());
+    }
+
+    #[test]
+    fn restart_resets_a_ready_cooldown() {
+        let (mut app, entity) = cooldown_app(Duration::from_millis(500));
+        cooldown(&mut app, entity).restart();
+        assert!(!cooldown(&mut app, entity).is_ready());
+        advance(&mut app, Duration::from_millis(500));
+        assert!(cooldown(&mut app, entity).is_ready());
+    }
+
+    #[test]
+    fn only_the_tick_that_finishes_a_cooldown_carries_over() {
+        let (mut app, entity) = cooldown_app(Duration::from_millis(500));
+        assert!(cooldown(&mut app, entity).trigger());
+        //ready 100ms into this frame, so the next cooldown starts 100ms in
+        advance(&mut app, Duration::from_millis(600));
+        assert!(cooldown(&mut app, entity).trigger());
+        advance(&mut app, Duration::from_millis(400));
+        assert!(cooldown(&mut app, entity).is_ready());
+
+        //left sitting ready, the next use gets the full cooldown
+        advance(&mut app, Duration::from_millis(300));
+        assert!(cooldown(&mut app, entity).trigger());
+        advance(&mut app, Duration::from_millis(400));
+        assert!(!cooldown(&mut app, entity).is_ready());
+        advance(&mut app, Duration::from_millis(100));
+        assert!(cooldown(&mut app, entity).is_ready());
+    }
+}
@@ -0,0 +1,203 @@
+use bevy::prelude::*;
+use bevy_xpbd_2d::{parry::na::ComplexField, prelude::*};
+
+use crate::{asteroids::AsteroidClass, ship::Ship, weapons::Laser};
+#[cfg(feature = "dev")]
+use crate::{
+    cooldown::Cooldown,
+    input::AimTarget,
+    ship::{recenter_ship, CollisionDamage},
+    weapons::{spread_angles, AimMode, MuzzlePoints, MuzzleSettings, ProjectileCount},
+};
+
+/// Debugging helpers. Drawing colliders by type on F2 is always there, the rest is only
+/// compiled in with the `dev` feature.
+pub struct DevPlugin;
+
+impl Plugin for DevPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ColliderDebugFilter>().add_systems(
+            Update,
+            (cycle_collider_debug_filter, draw_filtered_colliders).chain(),
+        );
+        #[cfg(feature = "dev")]
+        app.add_systems(Update, adjust_time_scale)
+            .init_resource::<FireArcVisible>()
+            .add_systems(Update, draw_fire_arc)
+            //collider gizmos stay hidden until asked for
+            .add_plugins(PhysicsDebugPlugin::default())
+            .insert_resource(PhysicsDebugConfig {
+                enabled: false,
+                ..default()
+            })
+            .add_systems(Update, toggle_physics_debug)
+            .add_systems(Update, dev_recenter_ship);
+    }
+}
+
+/// Free, unlimited version of the emergency warp on F6 for unsticking the ship while testing.
+#[cfg(feature = "dev")]
+fn dev_recenter_ship(
+    key: Res<Input<KeyCode>>,
+    mut ships: Query<
+        (
+            &mut Transform,
+            &mut LinearVelocity,
+            &mut Cooldown<CollisionDamage>,
+        ),
+        With<Ship>,
+    >,
+) {
+    if key.just_pressed(KeyCode::F6) {
+        for (mut transform, mut velocity, mut damage_cooldown) in ships.iter_mut() {
+            recenter_ship(&mut transform, &mut velocity, &mut damage_cooldown);
+        }
+    }
+}
+
+/// Shows or hides the physics debug rendering with F1.
+#[cfg(feature = "dev")]
+fn toggle_physics_debug(key: Res<Input<KeyCode>>, mut config: ResMut<PhysicsDebugConfig>) {
+    if key.just_pressed(KeyCode::F1) {
+        config.enabled = !config.enabled;
+    }
+}
+
+/// Whether the directions the next volley would fire in are drawn, toggled with F5.
+#[cfg(feature = "dev")]
+#[derive(Resource, Default)]
+struct FireArcVisible(bool);
+
+#[cfg(feature = "dev")]
+fn draw_fire_arc(
+    mut gizmos: Gizmos,
+    key: Res<Input<KeyCode>>,
+    mut visible: ResMut<FireArcVisible>,
+    aim_mode: Res<AimMode>,
+    aim_target: Res<AimTarget>,
+    muzzle_settings: Res<MuzzleSettings>,
+    weapon_query: Query<(&Parent, &ProjectileCount, &MuzzlePoints)>,
+    ship_query: Query<&Transform, With<Ship>>,
+) {
+    if key.just_pressed(KeyCode::F5) {
+        visible.0 = !visible.0;
+    }
+    if !visible.0 {
+        return;
+    }
+    let length = 300.0;
+    for (parent, count, muzzles) in weapon_query.iter() {
+        let Ok(transform) = ship_query.get(parent.get()) else {
+            continue;
+        };
+        for origin in muzzles.world_positions(transform, muzzle_settings.distance) {
+            let muzzle = transform.with_translation(origin.extend(0.0));
+            let aim = aim_mode.angle(&muzzle, aim_target.0);
+            for angle in spread_angles(count.0) {
+                let direction = Vec2::new((aim + angle).cos(), (aim + angle).sin());
+                gizmos.line_2d(origin, origin + direction * length, Color::YELLOW);
+            }
+        }
+    }
+}
+
+/// Halves or doubles how fast the game runs with -/+, 0 goes back to normal speed.
+#[cfg(feature = "dev")]
+fn adjust_time_scale(
+    key: Res<Input<KeyCode>>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut physics_time: ResMut<Time<Physics>>,
+) {
+    let current = virtual_time.relative_speed();
+    let scale = if key.any_just_pressed([KeyCode::Equals, KeyCode::NumpadAdd]) {
+        current * 2.0
+    } else if key.any_just_pressed([KeyCode::Minus, KeyCode::NumpadSubtract]) {
+        current / 2.0
+    } else if key.just_pressed(KeyCode::Key0) {
+        1.0
+    } else {
+        return;
+    };
+    let scale = scale.clamp(1.0 / 16.0, 4.0);
+    //physics keeps its own clock so it has to be scaled separately
+    virtual_time.set_relative_speed(scale);
+    physics_time.set_relative_speed(scale);
+    info!("time scale: {}", scale);
+}
+
+/// Which type of collider gets drawn for debugging, cycled with F2.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColliderDebugFilter {
+    #[default]
+    Off,
+    Ship,
+    Laser,
+    Asteroid,
+}
+
+impl ColliderDebugFilter {
+    fn next(self) -> Self {
+        match self {
+            ColliderDebugFilter::Off => ColliderDebugFilter::Ship,
+            ColliderDebugFilter::Ship => ColliderDebugFilter::Laser,
+            ColliderDebugFilter::Laser => ColliderDebugFilter::Asteroid,
+            ColliderDebugFilter::Asteroid => ColliderDebugFilter::Off,
+        }
+    }
+}
+
+fn cycle_collider_debug_filter(key: Res<Input<KeyCode>>, mut filter: ResMut<ColliderDebugFilter>) {
+    if key.just_pressed(KeyCode::F2) {
+        *filter = filter.next();
+        info!("collider debug: {:?}", *filter);
+    }
+}
+
+fn draw_filtered_colliders(
+    mut gizmos: Gizmos,
+    filter: Res<ColliderDebugFilter>,
+    colliders: Query<(
+        &Transform,
+        &Collider,
+        Option<&Ship>,
+        Option<&Laser>,
+        Option<&AsteroidClass>,
+    )>,
+) {
+    let color = Color::LIME_GREEN;
+    for (transform, collider, ship, laser, asteroid) in colliders.iter() {
+        let selected = match *filter {
+            ColliderDebugFilter::Off => false,
+            ColliderDebugFilter::Ship => ship.is_some(),
+            ColliderDebugFilter::Laser => laser.is_some(),
+            ColliderDebugFilter::Asteroid => asteroid.is_some(),
+        };
+        if !selected {
+            continue;
+        }
+        let center = transform.translation.xy();
+        let shape = collider.shape_scaled();
+        if let Some(ball) = shape.as_ball() {
+            gizmos.circle_2d(center, ball.radius, color);
+        } else if let Some(capsule) = shape.as_capsule() {
+            //rotate the capsule segment into world space
+            let a = transform
+                .rotation
+                .mul_vec3(Vec3::new(capsule.segment.a.x, capsule.segment.a.y, 0.0))
+                .xy();
+            let b = transform
+                .rotation
+                .mul_vec3(Vec3::new(capsule.segment.b.x, capsule.segment.b.y, 0.0))
+                .xy();
+            let side = (b - a).perp().normalize_or_zero() * capsule.radius;
+            gizmos.circle_2d(center + a, capsule.radius, color);
+            gizmos.circle_2d(center + b, capsule.radius, color);
+            gizmos.line_2d(center + a + side, center + b + side, color);
+            gizmos.line_2d(center + a - side, center + b - side, color);
+        } else {
+            //anything else just gets its bounding circle
+            let radius = shape.compute_local_bounding_sphere().radius();
+            gizmos.circle_2d(center, radius, color);
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use bevy::{
+    diagnostic::{DiagnosticsStore, EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin},
+    prelude::*,
+};
+
+/// An FPS and entity count readout for checking performance, toggled with F3. Dev builds
+/// also show the time scale set with -/+.
+pub struct DiagnosticsOverlayPlugin;
+
+impl Plugin for DiagnosticsOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((FrameTimeDiagnosticsPlugin, EntityCountDiagnosticsPlugin))
+            .init_resource::<DiagnosticsVisible>()
+            .add_systems(Startup, spawn_diagnostics_text)
+            .add_systems(Update, toggle_diagnostics)
+            .add_systems(
+                Update,
+                (show_diagnostics, update_diagnostics_text).after(toggle_diagnostics),
+            );
+    }
+}
+
+/// Whether the diagnostics overlay is shown, hidden until asked for.
+#[derive(Resource, Default)]
+pub struct DiagnosticsVisible(pub bool);
+
+#[derive(Component)]
+pub struct DiagnosticsText;
+
+fn spawn_diagnostics_text(mut commands: Commands) {
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font_size: 18.0,
+                    color: Color::YELLOW,
+                    ..default()
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0),
+                right: Val::Px(10.0),
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        DiagnosticsText,
+    ));
+}
+
+fn toggle_diagnostics(key: Res<Input<KeyCode>>, mut visible: ResMut<DiagnosticsVisible>) {
+    if key.just_pressed(KeyCode::F3) {
+        visible.0 = !visible.0;
+    }
+}
+
+fn show_diagnostics(
+    visible: Res<DiagnosticsVisible>,
+    mut texts: Query<&mut Visibility, With<DiagnosticsText>>,
+) {
+    if !visible.is_changed() {
+        return;
+    }
+    for mut visibility in texts.iter_mut() {
+        *visibility = if visible.0 {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+fn update_diagnostics_text(
+    visible: Res<DiagnosticsVisible>,
+    diagnostics: Res<DiagnosticsStore>,
+    #[cfg(feature = "dev")] virtual_time: Res<Time<Virtual>>,
+    mut texts: Query<&mut Text, With<DiagnosticsText>>,
+) {
+    if !visible.0 {
+        return;
+    }
+    let value = |id| {
+        diagnostics
+            .get(id)
+            .and_then(|diagnostic| diagnostic.smoothed())
+            .unwrap_or(0.0)
+    };
+    let fps = value(FrameTimeDiagnosticsPlugin::FPS);
+    let entities = value(EntityCountDiagnosticsPlugin::ENTITY_COUNT);
+    let readout = format!("FPS: {:.0}\nEntities: {:.0}", fps, entities);
+    #[cfg(feature = "dev")]
+    let readout = format!(
+        "{}\nTime scale: {}x",
+        readout,
+        virtual_time.relative_speed()
+    );
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = readout.clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_resource_shows_and_hides_the_text() {
+        let mut app = App::new();
+        app.init_resource::<DiagnosticsVisible>()
+            .add_systems(Startup, spawn_diagnostics_text)
+            .add_systems(Update, show_diagnostics);
+        let visibility = |app: &mut App| {
+            let mut texts = app
+                .world
+                .query_filtered::<&Visibility, With<DiagnosticsText>>();
+            *texts.single(&app.world)
+        };
+        app.update();
+        assert_eq!(visibility(&mut app), Visibility::Hidden);
+
+        app.world.resource_mut::<DiagnosticsVisible>().0 = true;
+        app.update();
+        assert_eq!(visibility(&mut app), Visibility::Inherited);
+
+        app.world.resource_mut::<DiagnosticsVisible>().0 = false;
+        app.update();
+        assert_eq!(visibility(&mut app), Visibility::Hidden);
+    }
+}
@@ -0,0 +1,200 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_xpbd_2d::prelude::*;
+
+use crate::{
+    asteroids::{handle_destroyed_asteroids, AsteroidClass, SpawnAsteroidEvent},
+    collisions::apply_damage,
+    movement::PlayArea,
+    run::{Difficulty, GameRng, GameRngSet, GameState},
+    score::Score,
+    ship::{PlayerDamagedEvent, Ship},
+    weapons::Laser,
+};
+
+/// Extra asteroids on top of the waves: the optional [`Director`] keeping the pressure up and
+/// the [`ThreatTimer`] going after players who hang back.
+pub struct DirectorPlugin;
+
+impl Plugin for DirectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Director>()
+            .add_systems(
+                Update,
+                (
+                    measure_pressure,
+                    director_spawner.in_set(GameRngSet::Director),
+                )
+                    .chain()
+                    .after(apply_damage)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .init_resource::<ThreatTimer>()
+            .add_systems(
+                Update,
+                threat_timer
+                    .in_set(GameRngSet::Threat)
+                    .after(handle_destroyed_asteroids)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Optional dynamic difficulty: keeps an eye on how much trouble the player is in and
+/// spawns extra asteroids faster or slower to keep that pressure inside `target`.
+#[derive(Resource)]
+pub struct Director {
+    pub enabled: bool,
+    //the band of pressure we try to keep the player in
+    pub target: (f32, f32),
+    //how quickly spawn intensity follows the pressure, per second
+    pub adaptation_rate: f32,
+    //how quickly pressure cools off, per second
+    pub decay: f32,
+    pub near_miss_radius: f32,
+    pressure: f32,
+    intensity: f32,
+    spawn_timer: Timer,
+}
+
+impl Default for Director {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target: (1.0, 3.0),
+            adaptation_rate: 0.1,
+            decay: 0.2,
+            near_miss_radius: 80.0,
+            pressure: 0.0,
+            intensity: 1.0,
+            spawn_timer: Timer::from_seconds(15.0, TimerMode::Once),
+        }
+    }
+}
+
+/// Marks an asteroid that already counted as a near miss, so it is only counted once.
+#[derive(Component)]
+pub struct NearMissed;
+
+fn measure_pressure(
+    time: Res<Time>,
+    mut director: ResMut<Director>,
+    mut collisions: EventReader<Collision>,
+    mut damage_events: EventReader<PlayerDamagedEvent>,
+    lasers: Query<(), With<Laser>>,
+    asteroids: Query<(Entity, &Transform), (With<AsteroidClass>, Without<NearMissed>)>,
+    ships: Query<&Transform, With<Ship>>,
+    mut commands: Commands,
+) {
+    if !director.enabled {
+        collisions.clear();
+        damage_events.clear();
+        return;
+    }
+    let decay = director.decay * time.delta_seconds();
+    director.pressure = (director.pressure - decay).max(0.0);
+    //landing shots means the player is comfortable
+    for event in collisions.read() {
+        if lasers.contains(event.0.entity1) != lasers.contains(event.0.entity2) {
+            director.pressure = (director.pressure - 0.05).max(0.0);
+        }
+    }
+    //taking damage is the strongest signal
+    for event in damage_events.read() {
+        director.pressure += event.amount as f32;
+    }
+    for ship_transform in ships.iter() {
+        for (entity, transform) in asteroids.iter() {
+            let distance = transform
+                .translation
+                .xy()
+                .distance(ship_transform.translation.xy());
+            if distance < director.near_miss_radius {
+                director.pressure += 0.25;
+                commands.entity(entity).insert(NearMissed);
+            }
+        }
+    }
+}
+
+fn director_spawner(
+    time: Res<Time>,
+    mut director: ResMut<Director>,
+    mut asteroid_event_writer: EventWriter<SpawnAsteroidEvent>,
+    play_area: Res<PlayArea>,
+    difficulty: Res<Difficulty>,
+    mut rng: ResMut<GameRng>,
+) {
+    if !director.enabled {
+        return;
+    }
+    //push harder while the player is cruising, back off while they are struggling
+    let (low, high) = director.target;
+    let step = director.adaptation_rate * time.delta_seconds();
+    if director.pressure < low {
+        director.intensity += step;
+    } else if director.pressure > high {
+        director.intensity -= step;
+    }
+    director.intensity = director.intensity.clamp(0.5, 3.0);
+    let intensity = director.intensity;
+    director.spawn_timer.tick(time.delta().mul_f32(intensity));
+    if director.spawn_timer.finished() {
+        director.spawn_timer.reset();
+        asteroid_event_writer.send(SpawnAsteroidEvent::from_edge(
+            &play_area,
+            15.0 * intensity * difficulty.asteroid_speed(),
+            &mut rng.0,
+        ));
+    }
+}
+
+/// Punishes camping: if nothing has been destroyed for `idle_threshold`, a big
+/// asteroid is sent straight at the ship.
+#[derive(Resource)]
+pub struct ThreatTimer {
+    pub idle_threshold: Duration,
+    timer: Timer,
+}
+
+impl Default for ThreatTimer {
+    fn default() -> Self {
+        let idle_threshold = Duration::from_secs(20);
+        Self {
+            idle_threshold,
+            timer: Timer::new(idle_threshold, TimerMode::Repeating),
+        }
+    }
+}
+
+fn threat_timer(
+    time: Res<Time>,
+    score: Res<Score>,
+    mut threat: ResMut<ThreatTimer>,
+    ships: Query<&Transform, With<Ship>>,
+    mut asteroid_event_writer: EventWriter<SpawnAsteroidEvent>,
+    play_area: Res<PlayArea>,
+    difficulty: Res<Difficulty>,
+    mut rng: ResMut<GameRng>,
+) {
+    //the score only moves when something gets destroyed
+    if score.is_changed() {
+        let idle_threshold = threat.idle_threshold;
+        threat.timer.set_duration(idle_threshold);
+        threat.timer.reset();
+        return;
+    }
+    threat.timer.tick(time.delta());
+    if threat.timer.just_finished() {
+        if let Ok(ship) = ships.get_single() {
+            info!("threat: sending an asteroid at the ship");
+            asteroid_event_writer.send(SpawnAsteroidEvent::from_edge_toward(
+                &play_area,
+                ship.translation.xy(),
+                30.0 * difficulty.asteroid_speed(),
+                &mut rng.0,
+            ));
+        }
+    }
+}
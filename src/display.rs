@@ -0,0 +1,223 @@
+use bevy::{prelude::*, transform::TransformSystem, window::PrimaryWindow};
+use bevy_framepace::{FramepaceSettings, Limiter};
+use serde::{Deserialize, Serialize};
+
+/// Pixel snapping, anti-aliasing and the frame rate cap. The [`PixelSnap`] setting has to be
+/// in place before this is added, the image sampler is picked from it at launch.
+pub struct DisplayPlugin;
+
+impl Plugin for DisplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, toggle_pixel_snap)
+            .add_systems(Update, cycle_msaa)
+            .add_systems(
+                PostUpdate,
+                snap_to_pixels.after(TransformSystem::TransformPropagate),
+            )
+            //add framepacing to help with input lag
+            .add_plugins(bevy_framepace::FramepacePlugin)
+            .init_resource::<FrameRateCap>()
+            .add_systems(Update, apply_frame_rate_cap);
+    }
+}
+
+/// Rounds rendered positions to whole screen pixels, toggled with F11. Only the rendered
+/// transforms are rounded, aiming and physics use the real positions. Textures are sampled
+/// with nearest-neighbor only if it was on at launch, the sampler can't change afterwards.
+#[derive(Resource)]
+pub struct PixelSnap(pub bool);
+
+//the camera shows this many world units from top to bottom, whatever the window's size
+pub const VIEW_HEIGHT: f32 = 720.0;
+
+fn toggle_pixel_snap(key: Res<Input<KeyCode>>, mut pixel_snap: ResMut<PixelSnap>) {
+    if key.just_pressed(KeyCode::F11) {
+        pixel_snap.0 = !pixel_snap.0;
+        info!("pixel snap: {}", pixel_snap.0);
+    }
+}
+
+/// `translation` moved onto the nearest whole screen pixel, with `pixels_per_unit` screen
+/// pixels to a world unit. Depth is left alone, it only orders sprites.
+fn snap_translation(translation: Vec3, pixels_per_unit: f32) -> Vec3 {
+    if pixels_per_unit <= 0.0 {
+        return translation;
+    }
+    let snapped = (translation.truncate() * pixels_per_unit).round() / pixels_per_unit;
+    snapped.extend(translation.z)
+}
+
+fn snap_to_pixels(
+    pixel_snap: Res<PixelSnap>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut query: Query<
+        &mut GlobalTransform,
+        Or<(With<Sprite>, With<TextureAtlasSprite>, With<Camera>)>,
+    >,
+) {
+    if !pixel_snap.0 {
+        return;
+    }
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    //a world unit only covers a whole pixel when the window is exactly VIEW_HEIGHT tall
+    let pixels_per_unit = window.physical_height() as f32 / VIEW_HEIGHT;
+    for mut global in query.iter_mut() {
+        let mut transform = global.compute_transform();
+        transform.translation = snap_translation(transform.translation, pixels_per_unit);
+        //don't let anything downstream (like physics sync) see this as a real move
+        *global.bypass_change_detection() = GlobalTransform::from(transform);
+    }
+}
+
+/// Steps through the anti-aliasing levels with F7, for trading quality against speed.
+fn cycle_msaa(key: Res<Input<KeyCode>>, mut msaa: ResMut<Msaa>) {
+    if !key.just_pressed(KeyCode::F7) {
+        return;
+    }
+    *msaa = next_msaa(*msaa);
+    info!("msaa: {:?}", *msaa);
+}
+
+/// The anti-aliasing level after `msaa`, wrapping back round to off.
+pub fn next_msaa(msaa: Msaa) -> Msaa {
+    //webgpu only supports off and 4x
+    #[cfg(target_arch = "wasm32")]
+    let next = match msaa {
+        Msaa::Off => Msaa::Sample4,
+        _ => Msaa::Off,
+    };
+    #[cfg(not(target_arch = "wasm32"))]
+    let next = match msaa {
+        Msaa::Off => Msaa::Sample2,
+        Msaa::Sample2 => Msaa::Sample4,
+        _ => Msaa::Off,
+    };
+    next
+}
+
+/// The anti-aliasing level taking `samples` per pixel, `None` for a count there is no level for.
+pub fn msaa_from_samples(samples: u32) -> Option<Msaa> {
+    match samples {
+        1 => Some(Msaa::Off),
+        2 => Some(Msaa::Sample2),
+        4 => Some(Msaa::Sample4),
+        8 => Some(Msaa::Sample8),
+        _ => None,
+    }
+}
+
+/// The frame rate the frame limiter holds the game to, lower caps save battery while
+/// higher ones cut input lag.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FrameRateCap {
+    //follow the display's refresh rate
+    #[default]
+    Auto,
+    Fps60,
+    Fps120,
+    Fps144,
+    Uncapped,
+}
+
+impl FrameRateCap {
+    pub fn next(self) -> Self {
+        match self {
+            FrameRateCap::Auto => FrameRateCap::Fps60,
+            FrameRateCap::Fps60 => FrameRateCap::Fps120,
+            FrameRateCap::Fps120 => FrameRateCap::Fps144,
+            FrameRateCap::Fps144 => FrameRateCap::Uncapped,
+            FrameRateCap::Uncapped => FrameRateCap::Auto,
+        }
+    }
+
+    pub fn limiter(&self) -> Limiter {
+        match self {
+            FrameRateCap::Auto => Limiter::Auto,
+            FrameRateCap::Fps60 => Limiter::from_framerate(60.0),
+            FrameRateCap::Fps120 => Limiter::from_framerate(120.0),
+            FrameRateCap::Fps144 => Limiter::from_framerate(144.0),
+            FrameRateCap::Uncapped => Limiter::Off,
+        }
+    }
+}
+
+fn apply_frame_rate_cap(cap: Res<FrameRateCap>, mut framepace: ResMut<FramepaceSettings>) {
+    if cap.is_changed() {
+        framepace.limiter = cap.limiter();
+        info!("frame rate cap: {:?}", *cap);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn pixel_snap_rounds_to_screen_pixels_not_world_units() {
+        let mut app = App::new();
+        app.insert_resource(PixelSnap(true))
+            .add_systems(Update, snap_to_pixels);
+        //twice as tall as the view, so every world unit covers two screen pixels
+        let mut window = Window::default();
+        window.resolution.set_physical_resolution(2560, 1440);
+        app.world.spawn((window, PrimaryWindow));
+        let sprite = app
+            .world
+            .spawn((
+                Sprite::default(),
+                GlobalTransform::from_translation(Vec3::new(10.3, -20.2, 0.5)),
+            ))
+            .id();
+        app.update();
+
+        let snapped = app
+            .world
+            .get::<GlobalTransform>(sprite)
+            .unwrap()
+            .translation();
+        assert!(
+            snapped.distance(Vec3::new(10.5, -20.0, 0.5)) < 0.001,
+            "{:?}",
+            snapped
+        );
+
+        app.world.resource_mut::<PixelSnap>().0 = false;
+        app.world
+            .entity_mut(sprite)
+            .insert(GlobalTransform::from_translation(Vec3::new(
+                10.3, -20.2, 0.5,
+            )));
+        app.update();
+        let unsnapped = app
+            .world
+            .get::<GlobalTransform>(sprite)
+            .unwrap()
+            .translation();
+        assert_eq!(unsnapped, Vec3::new(10.3, -20.2, 0.5));
+    }
+
+    #[test]
+    fn picking_a_frame_rate_cap_sets_the_limiter() {
+        let mut app = App::new();
+        app.init_resource::<FramepaceSettings>()
+            .insert_resource(FrameRateCap::Fps144)
+            .add_systems(Update, apply_frame_rate_cap);
+        app.update();
+        let limiter = app.world.resource::<FramepaceSettings>().limiter.clone();
+        let frame = Duration::from_secs_f64(1.0 / 144.0);
+        assert!(
+            matches!(limiter, Limiter::Manual(limit) if limit == frame),
+            "{:?}",
+            limiter
+        );
+
+        *app.world.resource_mut::<FrameRateCap>() = FrameRateCap::Uncapped;
+        app.update();
+        let limiter = app.world.resource::<FramepaceSettings>().limiter.clone();
+        assert!(matches!(limiter, Limiter::Off), "{:?}", limiter);
+    }
+}
@@ -0,0 +1,422 @@
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+use bevy_xpbd_2d::prelude::*;
+
+use crate::{
+    asteroids::{Armored, AsteroidClass},
+    collisions::{apply_damage, DamageEvent},
+    run::GameEntity,
+    ship::Ship,
+    weapons::{AdditiveMaterial, VisualStyle},
+};
+
+/// Animated explosions for anything that gets blown up, driven by [`SpawnExplosionEvent`],
+/// and the area damage of [`Explosion`]s.
+pub struct ExplosionPlugin;
+
+impl Plugin for ExplosionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SpawnExplosionEvent>()
+            .init_resource::<ExplosionSettings>()
+            .add_systems(Startup, load_explosion_atlas)
+            .add_systems(Update, spawn_explosions)
+            .add_systems(Update, animate_explosions.after(spawn_explosions))
+            .add_systems(Update, apply_explosion_damage.before(apply_damage));
+    }
+}
+
+/// How an explosion's force falls off away from its center, and the blasts bombs leave behind.
+#[derive(Resource)]
+pub struct ExplosionSettings {
+    //damage and push scale with (1 - distance / radius) to this power, 1 falls off linearly and
+    //higher keeps the worst of it close to the center
+    pub falloff: f32,
+    pub bomb_radius: f32,
+    //at the center, anything caught further out takes less
+    pub bomb_damage: i32,
+    pub bomb_impulse: f32,
+}
+
+impl Default for ExplosionSettings {
+    fn default() -> Self {
+        Self {
+            falloff: 1.5,
+            bomb_radius: 150.0,
+            bomb_damage: 2,
+            bomb_impulse: 250.0,
+        }
+    }
+}
+
+impl ExplosionSettings {
+    /// How much of an explosion's force reaches something `distance` from its center, from 1
+    /// right at the center down to 0 at `radius` and beyond.
+    pub fn strength(&self, distance: f32, radius: f32) -> f32 {
+        (1.0 - distance / radius).clamp(0.0, 1.0).powf(self.falloff)
+    }
+}
+
+/// Damages everything within `radius` of the entity once, the frame after it is spawned, less
+/// the further out it is, and pushes asteroids away. The ship only gets caught in it with
+/// `hurts_ship` set. Give it a short [`crate::lifetime::Lifetime`] to clean it up afterwards.
+#[derive(Component)]
+pub struct Explosion {
+    pub radius: f32,
+    //at the center
+    pub damage: i32,
+    pub impulse: f32,
+    pub hurts_ship: bool,
+}
+
+//anything inside the radius takes at least 1
+fn scaled_damage(damage: i32, strength: f32) -> i32 {
+    (damage as f32 * strength).ceil() as i32
+}
+
+fn apply_explosion_damage(
+    settings: Res<ExplosionSettings>,
+    explosions: Query<(Entity, &Explosion, &Transform), Added<Explosion>>,
+    mut asteroids: Query<
+        (
+            Entity,
+            &Transform,
+            &Collider,
+            &mut LinearVelocity,
+            Option<&Armored>,
+        ),
+        With<AsteroidClass>,
+    >,
+    ships: Query<(Entity, &Transform), With<Ship>>,
+    mut damage_writer: EventWriter<DamageEvent>,
+) {
+    for (entity, explosion, transform) in explosions.iter() {
+        let center = transform.translation.xy();
+        for (target, asteroid, collider, mut velocity, armored) in asteroids.iter_mut() {
+            let offset = asteroid.translation.xy() - center;
+            //big asteroids are caught from their edge, not their center
+            let edge = collider
+                .shape_scaled()
+                .compute_local_bounding_sphere()
+                .radius();
+            let strength = settings.strength((offset.length() - edge).max(0.0), explosion.radius);
+            if strength <= 0.0 {
+                continue;
+            }
+            velocity.0 += offset.normalize_or_zero() * explosion.impulse * strength;
+            //armor shrugs off the blast, weak spots are not asteroids themselves
+            if armored.is_none() {
+                damage_writer.send(DamageEvent {
+                    target,
+                    amount: scaled_damage(explosion.damage, strength),
+                    source: entity,
+                });
+            }
+        }
+        if !explosion.hurts_ship {
+            continue;
+        }
+        //shields and invulnerability are left to apply_damage
+        for (ship, ship_transform) in ships.iter() {
+            let distance = ship_transform.translation.xy().distance(center);
+            let strength = settings.strength(distance, explosion.radius);
+            if strength > 0.0 {
+                damage_writer.send(DamageEvent {
+                    target: ship,
+                    amount: scaled_damage(explosion.damage, strength),
+                    source: entity,
+                });
+            }
+        }
+    }
+}
+
+//the sheet is a single row of square frames
+const FRAME_SIZE: f32 = 64.0;
+const FRAME_COUNT: usize = 8;
+const FRAME_TIME: f32 = 0.05;
+
+/// Sent to play an explosion, `scale` 1 is about the size of a medium asteroid.
+#[derive(Event)]
+pub struct SpawnExplosionEvent {
+    pub position: Vec2,
+    pub scale: f32,
+}
+
+#[derive(Resource)]
+struct ExplosionAtlas(Handle<TextureAtlas>);
+
+/// The same sheet drawn additively, one material per frame since the mesh can't pick its frame
+/// like a [`TextureAtlasSprite`] does.
+#[derive(Resource)]
+struct AdditiveExplosionFrames {
+    mesh: Handle<Mesh>,
+    frames: Vec<Handle<AdditiveMaterial>>,
+}
+
+/// Steps through the sheet once, the explosion is gone after the last frame.
+#[derive(Component)]
+pub struct ExplosionAnimation {
+    timer: Timer,
+    frame: usize,
+}
+
+#[derive(Bundle)]
+pub struct ExplosionBundle {
+    sprite_sheet: SpriteSheetBundle,
+    animation: ExplosionAnimation,
+    tag: GameEntity,
+}
+
+impl ExplosionAnimation {
+    fn new() -> Self {
+        Self {
+            timer: Timer::from_seconds(FRAME_TIME, TimerMode::Repeating),
+            frame: 0,
+        }
+    }
+}
+
+//drawn over the asteroids and the ship
+fn explosion_transform(position: Vec2, scale: f32) -> Transform {
+    Transform::from_translation(position.extend(1.0)).with_scale(Vec3::splat(scale * 1.5))
+}
+
+impl ExplosionBundle {
+    fn new(atlas: Handle<TextureAtlas>, position: Vec2, scale: f32) -> Self {
+        Self {
+            sprite_sheet: SpriteSheetBundle {
+                texture_atlas: atlas,
+                sprite: TextureAtlasSprite::new(0),
+                transform: explosion_transform(position, scale),
+                ..default()
+            },
+            animation: ExplosionAnimation::new(),
+            tag: GameEntity,
+        }
+    }
+}
+
+fn load_explosion_atlas(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut atlases: ResMut<Assets<TextureAtlas>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<AdditiveMaterial>>,
+) {
+    let texture: Handle<Image> = asset_server.load("explosion.png");
+    //each frame is a 1/FRAME_COUNT wide slice of the row
+    let width = 1.0 / FRAME_COUNT as f32;
+    commands.insert_resource(AdditiveExplosionFrames {
+        mesh: meshes.add(shape::Quad::new(Vec2::splat(FRAME_SIZE)).into()),
+        frames: (0..FRAME_COUNT)
+            .map(|i| {
+                materials.add(AdditiveMaterial {
+                    uv_rect: Vec4::new(i as f32 * width, 0.0, width, 1.0),
+                    ..AdditiveMaterial::new(Color::WHITE, texture.clone())
+                })
+            })
+            .collect(),
+    });
+    let atlas =
+        TextureAtlas::from_grid(texture, Vec2::splat(FRAME_SIZE), FRAME_COUNT, 1, None, None);
+    commands.insert_resource(ExplosionAtlas(atlases.add(atlas)));
+}
+
+fn spawn_explosions(
+    mut events: EventReader<SpawnExplosionEvent>,
+    atlas: Res<ExplosionAtlas>,
+    additive: Res<AdditiveExplosionFrames>,
+    style: Res<VisualStyle>,
+    mut commands: Commands,
+) {
+    for event in events.read() {
+        if style.additive {
+            commands.spawn((
+                MaterialMesh2dBundle {
+                    mesh: additive.mesh.clone().into(),
+                    material: additive.frames[0].clone(),
+                    transform: explosion_transform(event.position, event.scale),
+                    ..default()
+                },
+                ExplosionAnimation::new(),
+                GameEntity,
+            ));
+        } else {
+            commands.spawn(ExplosionBundle::new(
+                atlas.0.clone(),
+                event.position,
+                event.scale,
+            ));
+        }
+    }
+}
+
+fn animate_explosions(
+    time: Res<Time>,
+    additive: Res<AdditiveExplosionFrames>,
+    mut explosions: Query<(
+        Entity,
+        &mut ExplosionAnimation,
+        Option<&mut TextureAtlasSprite>,
+        Option<&mut Handle<AdditiveMaterial>>,
+    )>,
+    mut commands: Commands,
+) {
+    for (entity, mut animation, sprite, material) in explosions.iter_mut() {
+        animation.timer.tick(time.delta());
+        for _ in 0..animation.timer.times_finished_this_tick() {
+            if animation.frame + 1 >= FRAME_COUNT {
+                commands.entity(entity).despawn_recursive();
+                break;
+            }
+            animation.frame += 1;
+        }
+        //explosions keep the look they were spawned with, toggling only changes new ones
+        if let Some(mut sprite) = sprite {
+            sprite.index = animation.frame;
+        }
+        if let Some(mut material) = material {
+            let frame = &additive.frames[animation.frame];
+            if *material != *frame {
+                *material = frame.clone();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::event::ManualEventReader;
+
+    use super::*;
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.init_resource::<ExplosionSettings>()
+            .add_event::<DamageEvent>()
+            .add_systems(Update, apply_explosion_damage);
+        app
+    }
+
+    fn explode(app: &mut App, position: Vec2, hurts_ship: bool) {
+        app.world.spawn((
+            TransformBundle::from_transform(Transform::from_translation(position.extend(0.0))),
+            Explosion {
+                radius: 100.0,
+                damage: 4,
+                impulse: 200.0,
+                hurts_ship,
+            },
+        ));
+    }
+
+    fn ship_at(app: &mut App, position: Vec2) -> Entity {
+        app.world
+            .spawn((Ship, Transform::from_translation(position.extend(0.0))))
+            .id()
+    }
+
+    fn damage_to(app: &App, target: Entity) -> Vec<i32> {
+        let events = app.world.resource::<Events<DamageEvent>>();
+        ManualEventReader::<DamageEvent>::default()
+            .read(events)
+            .filter(|event| event.target == target)
+            .map(|event| event.amount)
+            .collect()
+    }
+
+    #[test]
+    fn strength_falls_off_to_nothing_at_the_radius() {
+        let settings = ExplosionSettings::default();
+        assert_eq!(settings.strength(0.0, 100.0), 1.0);
+        assert!(settings.strength(50.0, 100.0) < settings.strength(25.0, 100.0));
+        assert_eq!(settings.strength(100.0, 100.0), 0.0);
+        assert_eq!(settings.strength(150.0, 100.0), 0.0);
+    }
+
+    #[test]
+    fn a_ship_caught_close_takes_more_than_one_at_the_edge() {
+        let mut app = app();
+        let close = ship_at(&mut app, Vec2::new(10.0, 0.0));
+        let far = ship_at(&mut app, Vec2::new(90.0, 0.0));
+        let outside = ship_at(&mut app, Vec2::new(150.0, 0.0));
+        explode(&mut app, Vec2::ZERO, true);
+        app.update();
+
+        let close = damage_to(&app, close);
+        let far = damage_to(&app, far);
+        assert_eq!(close.len(), 1);
+        assert_eq!(far.len(), 1);
+        assert!(close[0] > far[0]);
+        assert!(damage_to(&app, outside).is_empty());
+    }
+
+    #[test]
+    fn only_explosions_that_hurt_the_ship_do() {
+        let mut app = app();
+        let ship = ship_at(&mut app, Vec2::new(10.0, 0.0));
+        explode(&mut app, Vec2::ZERO, false);
+        app.update();
+        assert!(damage_to(&app, ship).is_empty());
+    }
+
+    #[test]
+    fn asteroids_are_pushed_away_and_armor_takes_no_damage() {
+        let mut app = app();
+        let plain = app
+            .world
+            .spawn((
+                AsteroidClass::Small,
+                Transform::from_xyz(50.0, 0.0, 0.0),
+                Collider::ball(15.0),
+                LinearVelocity::default(),
+            ))
+            .id();
+        let armored = app
+            .world
+            .spawn((
+                AsteroidClass::Small,
+                Armored { bonus: 0 },
+                Transform::from_xyz(-50.0, 0.0, 0.0),
+                Collider::ball(15.0),
+                LinearVelocity::default(),
+            ))
+            .id();
+        explode(&mut app, Vec2::ZERO, false);
+        app.update();
+
+        assert!(app.world.get::<LinearVelocity>(plain).unwrap().0.x > 0.0);
+        assert!(app.world.get::<LinearVelocity>(armored).unwrap().0.x < 0.0);
+        assert_eq!(damage_to(&app, plain).len(), 1);
+        assert!(damage_to(&app, armored).is_empty());
+    }
+
+    #[test]
+    fn every_asteroid_in_a_cluster_takes_damage() {
+        let mut app = app();
+        let cluster: Vec<Entity> = [
+            Vec2::new(30.0, 0.0),
+            Vec2::new(-20.0, 40.0),
+            Vec2::new(0.0, -60.0),
+        ]
+        .into_iter()
+        .map(|position| {
+            app.world
+                .spawn((
+                    AsteroidClass::Medium,
+                    Transform::from_translation(position.extend(0.0)),
+                    Collider::ball(20.0),
+                    LinearVelocity::default(),
+                ))
+                .id()
+        })
+        .collect();
+        explode(&mut app, Vec2::ZERO, false);
+        app.update();
+
+        for asteroid in cluster {
+            let damage = damage_to(&app, asteroid);
+            assert_eq!(damage.len(), 1);
+            assert!(damage[0] > 0);
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use bevy::prelude::*;
+
+use crate::ship::Ship;
+
+/// Replays the longest survived run so far as a faint ghost ship.
+pub struct GhostPlugin;
+
+impl Plugin for GhostPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GhostRecorder>()
+            .add_systems(Update, record_ghost);
+    }
+}
+
+#[derive(Clone, Copy)]
+struct GhostSample {
+    time: f32,
+    position: Vec2,
+    rotation: Quat,
+}
+
+/// Records the ship's path each run and keeps the longest survived one around to
+/// replay as a ghost. Toggle the ghost with G.
+#[derive(Resource)]
+pub struct GhostRecorder {
+    pub enabled: bool,
+    elapsed: f32,
+    sample_timer: Timer,
+    current: Vec<GhostSample>,
+    best: Vec<GhostSample>,
+    best_time: f32,
+}
+
+impl Default for GhostRecorder {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            elapsed: 0.0,
+            sample_timer: Timer::from_seconds(0.1, TimerMode::Repeating),
+            current: Vec::new(),
+            best: Vec::new(),
+            best_time: 0.0,
+        }
+    }
+}
+
+impl GhostRecorder {
+    pub fn start_run(&mut self) {
+        self.elapsed = 0.0;
+        self.sample_timer.reset();
+        self.current.clear();
+    }
+
+    pub fn finish_run(&mut self) {
+        if self.elapsed > self.best_time {
+            self.best_time = self.elapsed;
+            self.best = std::mem::take(&mut self.current);
+        }
+    }
+
+    /// Where the best run's ship was at `time`, blending between recorded samples.
+    fn ghost_at(&self, time: f32) -> Option<GhostSample> {
+        let next = self.best.partition_point(|sample| sample.time < time);
+        let after = self.best.get(next)?;
+        let Some(before) = next.checked_sub(1).and_then(|i| self.best.get(i)) else {
+            return Some(*after);
+        };
+        let t = (time - before.time) / (after.time - before.time).max(f32::EPSILON);
+        Some(GhostSample {
+            time,
+            position: before.position.lerp(after.position, t),
+            rotation: before.rotation.slerp(after.rotation, t),
+        })
+    }
+}
+
+#[derive(Component)]
+pub struct GhostShip;
+
+fn record_ghost(
+    time: Res<Time>,
+    key: Res<Input<KeyCode>>,
+    mut ghost: ResMut<GhostRecorder>,
+    ship_query: Query<&Transform, With<Ship>>,
+    mut ghost_query: Query<(&mut Transform, &mut Visibility), (With<GhostShip>, Without<Ship>)>,
+) {
+    if key.just_pressed(KeyCode::G) {
+        ghost.enabled = !ghost.enabled;
+    }
+    ghost.elapsed += time.delta_seconds();
+    ghost.sample_timer.tick(time.delta());
+    if ghost.sample_timer.just_finished() {
+        if let Ok(ship) = ship_query.get_single() {
+            let sample = GhostSample {
+                time: ghost.elapsed,
+                position: ship.translation.xy(),
+                rotation: ship.rotation,
+            };
+            ghost.current.push(sample);
+        }
+    }
+    let sample = ghost.ghost_at(ghost.elapsed);
+    for (mut transform, mut visibility) in ghost_query.iter_mut() {
+        match (ghost.enabled, sample) {
+            (true, Some(sample)) => {
+                transform.translation = sample.position.extend(-0.5);
+                transform.rotation = sample.rotation;
+                *visibility = Visibility::Visible;
+            }
+            _ => *visibility = Visibility::Hidden,
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use bevy::prelude::*;
+
+use crate::{run::GameState, score::Score};
+
+/// Keeps the best score between sessions, in a file natively and in local storage on the web.
+pub struct HighScorePlugin;
+
+impl Plugin for HighScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_high_score)
+            .add_systems(OnEnter(GameState::GameOver), update_high_score);
+    }
+}
+
+const HIGH_SCORE_SAVE: &str = "highscore";
+
+/// The best final score so far.
+#[derive(Resource, Default)]
+pub struct HighScore(pub u32);
+
+fn load_high_score(mut commands: Commands) {
+    let high_score = match read_save(HIGH_SCORE_SAVE) {
+        //nothing saved yet, so this is the first run
+        None => 0,
+        Some(saved) => saved.trim().parse().unwrap_or_else(|_| {
+            warn!("ignoring unreadable high score {:?}", saved);
+            0
+        }),
+    };
+    commands.insert_resource(HighScore(high_score));
+}
+
+pub fn update_high_score(score: Res<Score>, mut high_score: ResMut<HighScore>) {
+    if score.0 <= high_score.0 {
+        return;
+    }
+    info!("new high score: {}", score.0);
+    high_score.0 = score.0;
+    if let Err(error) = write_save(HIGH_SCORE_SAVE, &high_score.0.to_string()) {
+        warn!("could not save high score: {}", error);
+    }
+}
+
+/// Reads back what [`write_save`] stored under `name`, or `None` if nothing was saved yet.
+#[cfg(all(not(target_arch = "wasm32"), not(test)))]
+pub fn read_save(name: &str) -> Option<String> {
+    std::fs::read_to_string(format!("{}.txt", name)).ok()
+}
+
+/// Stores `value` under `name`, next to the executable natively and in local storage on the web.
+#[cfg(all(not(target_arch = "wasm32"), not(test)))]
+pub fn write_save(name: &str, value: &str) -> std::io::Result<()> {
+    std::fs::write(format!("{}.txt", name), value)
+}
+
+//tests keep their saves in memory, each test runs on its own thread so they can't see each other's
+#[cfg(test)]
+thread_local! {
+    static TEST_SAVES: std::cell::RefCell<std::collections::HashMap<String, String>> =
+        Default::default();
+}
+
+#[cfg(test)]
+pub fn read_save(name: &str) -> Option<String> {
+    TEST_SAVES.with(|saves| saves.borrow().get(name).cloned())
+}
+
+#[cfg(test)]
+pub fn write_save(name: &str, value: &str) -> std::io::Result<()> {
+    TEST_SAVES.with(|saves| {
+        saves
+            .borrow_mut()
+            .insert(name.to_string(), value.to_string())
+    });
+    Ok(())
+}
+
+#[cfg(all(target_arch = "wasm32", not(test)))]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+#[cfg(all(target_arch = "wasm32", not(test)))]
+pub fn read_save(name: &str) -> Option<String> {
+    local_storage()?
+        .get_item(&format!("survive.{}", name))
+        .ok()?
+}
+
+#[cfg(all(target_arch = "wasm32", not(test)))]
+pub fn write_save(name: &str, value: &str) -> std::io::Result<()> {
+    let error = |message| std::io::Error::new(std::io::ErrorKind::Other, message);
+    local_storage()
+        .ok_or_else(|| error("no local storage"))?
+        .set_item(&format!("survive.{}", name), value)
+        .map_err(|_| error("local storage refused the write"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn high_score_app() -> App {
+        let mut app = App::new();
+        app.add_systems(Startup, load_high_score)
+            .add_systems(Update, update_high_score);
+        app
+    }
+
+    #[test]
+    fn beating_the_high_score_saves_it() {
+        write_save(HIGH_SCORE_SAVE, "300").unwrap();
+        let mut app = high_score_app();
+        app.insert_resource(Score(450));
+        app.update();
+
+        assert_eq!(app.world.resource::<HighScore>().0, 450);
+        assert_eq!(read_save(HIGH_SCORE_SAVE).as_deref(), Some("450"));
+    }
+
+    #[test]
+    fn a_corrupt_save_starts_from_zero() {
+        write_save(HIGH_SCORE_SAVE, "not a number").unwrap();
+        let mut app = high_score_app();
+        app.insert_resource(Score(0));
+        app.update();
+
+        assert_eq!(app.world.resource::<HighScore>().0, 0);
+        //nothing was beaten, so the bad save is left alone
+        assert_eq!(read_save(HIGH_SCORE_SAVE).as_deref(), Some("not a number"));
+    }
+}
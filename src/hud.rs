@@ -0,0 +1,303 @@
+use bevy::prelude::*;
+
+use crate::{
+    asteroids::WaveStartedEvent,
+    boss_rush::{BossRush, BossRushPhase, BOSS_COUNT},
+    highscore::{update_high_score, HighScore},
+    mutators::Mutators,
+    run::GameState,
+    score::{Combo, Score},
+    ship::{Lives, ShipHealth},
+    weapons::{Bombs, ChargeLevel, Weapon, WeaponKind},
+};
+
+/// On-screen readout of the run: score, lives, bombs, ship health, weapon, combo and any
+/// active mutators, and the final tally once the game is over.
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Playing), spawn_hud)
+            .add_systems(OnExit(GameState::Playing), despawn_hud)
+            .add_systems(Update, update_hud.run_if(in_state(GameState::Playing)))
+            .add_systems(Update, announce_wave.run_if(in_state(GameState::Playing)))
+            .add_systems(Update, fade_wave_banner)
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                spawn_game_over_screen.after(update_high_score),
+            )
+            .add_systems(OnExit(GameState::GameOver), despawn_game_over_screen);
+    }
+}
+
+/// The top of the HUD, despawning it takes everything in the HUD with it.
+#[derive(Component)]
+pub struct HudRoot;
+
+#[derive(Component)]
+struct HudText;
+
+fn spawn_hud(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(10.0),
+                    left: Val::Px(10.0),
+                    ..default()
+                },
+                ..default()
+            },
+            HudRoot,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 24.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+                HudText,
+            ));
+        });
+}
+
+fn despawn_hud(mut commands: Commands, roots: Query<Entity, With<HudRoot>>) {
+    for entity in roots.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn update_hud(
+    score: Res<Score>,
+    lives: Res<Lives>,
+    bombs: Res<Bombs>,
+    combo: Res<Combo>,
+    mutators: Res<Mutators>,
+    boss_rush: Res<BossRush>,
+    ships: Query<&ShipHealth>,
+    weapons: Query<(&Weapon, &WeaponKind, &ChargeLevel)>,
+    mut texts: Query<&mut Text, With<HudText>>,
+) {
+    let health = ships.iter().map(|health| health.0).sum::<i32>().max(0);
+    let mut value = format!(
+        "Score: {}   Lives: {}   Bombs: {}   Hull: {}",
+        score.0, lives.0, bombs.0, health
+    );
+    let primary = weapons.iter().find(|(slot, ..)| **slot == Weapon::Primary);
+    if let Some((_, weapon, charge)) = primary {
+        value.push_str(&format!("   Weapon: {:?}", weapon));
+        if *weapon == WeaponKind::Charge {
+            value.push_str(&format!(" {:.0}%", charge.0 * 100.0));
+        }
+    }
+    //a single kill isn't a combo yet
+    if combo.multiplier > 1 {
+        value.push_str(&format!("   Combo: x{}", combo.multiplier));
+    }
+    if !mutators.active().is_empty() {
+        value.push_str(&format!(
+            "\nMutators: {:?} (x{})",
+            mutators.active(),
+            mutators.score_multiplier()
+        ));
+    }
+    if boss_rush.active {
+        value.push_str(&format!(
+            "\nBoss: {}/{}   Time: {:.1}s   Deaths: {}",
+            boss_rush.current_boss(),
+            BOSS_COUNT,
+            boss_rush.elapsed.as_secs_f32(),
+            boss_rush.deaths
+        ));
+        if let Some(best) = boss_rush.best {
+            value.push_str(&format!("   Best: {:.1}s", best.as_secs_f32()));
+        }
+    }
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = value.clone();
+    }
+}
+
+/// Briefly shows which wave just started in the middle of the screen.
+#[derive(Component)]
+struct WaveBanner(Timer);
+
+fn announce_wave(mut events: EventReader<WaveStartedEvent>, mut commands: Commands) {
+    for event in events.read() {
+        commands.spawn((
+            TextBundle::from_section(
+                format!("Wave {} - {} asteroids", event.wave, event.asteroid_count),
+                TextStyle {
+                    font_size: 40.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                top: Val::Percent(30.0),
+                justify_self: JustifySelf::Center,
+                ..default()
+            }),
+            WaveBanner(Timer::from_seconds(2.0, TimerMode::Once)),
+            //goes away with the rest of the hud
+            HudRoot,
+        ));
+    }
+}
+
+fn fade_wave_banner(
+    time: Res<Time>,
+    mut banners: Query<(Entity, &mut WaveBanner, &mut Text)>,
+    mut commands: Commands,
+) {
+    for (entity, mut banner, mut text) in banners.iter_mut() {
+        banner.0.tick(time.delta());
+        if banner.0.finished() {
+            commands.entity(entity).despawn_recursive();
+        } else {
+            let alpha = banner.0.percent_left();
+            for section in text.sections.iter_mut() {
+                section.style.color.set_a(alpha);
+            }
+        }
+    }
+}
+
+/// The top of the game over screen.
+#[derive(Component)]
+struct GameOverRoot;
+
+fn spawn_game_over_screen(
+    score: Res<Score>,
+    high_score: Res<HighScore>,
+    boss_rush: Res<BossRush>,
+    mut commands: Commands,
+) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(10.0),
+                    ..default()
+                },
+                ..default()
+            },
+            GameOverRoot,
+        ))
+        .with_children(|parent| {
+            let line = |value: String, font_size| {
+                TextBundle::from_section(
+                    value,
+                    TextStyle {
+                        font_size,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                )
+            };
+            if !boss_rush.active {
+                parent.spawn(line("Game Over".into(), 60.0));
+                parent.spawn(line(format!("Score: {}", score.0), 32.0));
+                parent.spawn(line(format!("High score: {}", high_score.0), 32.0));
+            } else if boss_rush.phase == BossRushPhase::Cleared {
+                parent.spawn(line("Boss Rush Cleared".into(), 60.0));
+                let time = boss_rush.elapsed.as_secs_f32();
+                parent.spawn(line(format!("Time: {:.1}s", time), 32.0));
+            } else {
+                parent.spawn(line("Game Over".into(), 60.0));
+                let beaten = format!("Bosses beaten: {}/{}", boss_rush.beaten, BOSS_COUNT);
+                parent.spawn(line(beaten, 32.0));
+            }
+            if let (true, Some(best)) = (boss_rush.active, boss_rush.best) {
+                let best = format!("Best boss rush: {:.1}s", best.as_secs_f32());
+                parent.spawn(line(best, 32.0));
+            }
+            parent.spawn(line("Press Enter to play again".into(), 24.0));
+        });
+}
+
+fn despawn_game_over_screen(mut commands: Commands, roots: Query<Entity, With<GameOverRoot>>) {
+    for entity in roots.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mutators::Mutator;
+
+    #[test]
+    fn the_hud_shows_the_score() {
+        let mut app = App::new();
+        app.insert_resource(Score(1234))
+            .init_resource::<Lives>()
+            .init_resource::<Bombs>()
+            .init_resource::<Combo>()
+            .init_resource::<Mutators>()
+            .init_resource::<BossRush>()
+            .add_systems(Startup, spawn_hud)
+            .add_systems(Update, update_hud);
+        app.update();
+
+        let mut texts = app.world.query_filtered::<&Text, With<HudText>>();
+        let text = texts.single(&app.world);
+        assert!(text.sections[0].value.contains("1234"));
+    }
+
+    #[test]
+    fn the_hud_shows_the_boss_rush_progress() {
+        let mut app = App::new();
+        app.init_resource::<Score>()
+            .init_resource::<Lives>()
+            .init_resource::<Bombs>()
+            .init_resource::<Combo>()
+            .init_resource::<Mutators>()
+            .insert_resource(BossRush {
+                active: true,
+                beaten: 2,
+                ..default()
+            })
+            .add_systems(Startup, spawn_hud)
+            .add_systems(Update, update_hud);
+        app.update();
+
+        let mut texts = app.world.query_filtered::<&Text, With<HudText>>();
+        let text = texts.single(&app.world);
+        assert!(text.sections[0].value.contains("Boss: 3/5"));
+    }
+
+    #[test]
+    fn the_hud_lists_the_active_mutators() {
+        let mut mutators = Mutators::default();
+        mutators.toggle(Mutator::LasersPierce);
+        mutators.start_run();
+        let mut app = App::new();
+        app.init_resource::<Score>()
+            .init_resource::<Lives>()
+            .init_resource::<Bombs>()
+            .init_resource::<Combo>()
+            .insert_resource(mutators)
+            .init_resource::<BossRush>()
+            .add_systems(Startup, spawn_hud)
+            .add_systems(Update, update_hud);
+        app.update();
+
+        let mut texts = app.world.query_filtered::<&Text, With<HudText>>();
+        let text = texts.single(&app.world);
+        assert!(text.sections[0]
+            .value
+            .contains("Mutators: [LasersPierce] (x2)"));
+    }
+}
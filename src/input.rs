@@ -0,0 +1,428 @@
+use std::f32::consts::PI;
+
+use bevy::{
+    prelude::*,
+    window::{CursorEntered, CursorGrabMode, CursorLeft, CursorMoved, PrimaryWindow},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    run::{GameState, MainCamera},
+    ship::Ship,
+};
+
+/// Turns keyboard, mouse and gamepad input into [`InputAction`] events and the crosshair position.
+pub struct InputPlugin;
+
+impl Plugin for InputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MousePosition>()
+            .add_systems(PreUpdate, update_mouse_position_system)
+            //the crosshair stands in for the OS cursor while playing
+            .add_systems(OnEnter(GameState::Playing), hide_cursor)
+            .add_systems(OnExit(GameState::Playing), show_cursor)
+            .add_systems(Update, grab_mouse.run_if(in_state(GameState::Playing)))
+            .add_systems(Update, track_cursor_presence)
+            .init_resource::<AimTarget>()
+            .init_resource::<AimScheme>()
+            .add_systems(Update, aim_with_mouse)
+            .add_systems(
+                Update,
+                (sync_crosshair, look_at_aim_target)
+                    .after(aim_with_mouse)
+                    .after(gamepad_input),
+            )
+            .add_systems(Update, proto_input.run_if(in_state(GameState::Playing)))
+            .init_resource::<GamepadSettings>()
+            .add_systems(
+                Update,
+                gamepad_input
+                    .after(aim_with_mouse)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_event::<InputAction>();
+    }
+}
+
+/// We will store the world position of the mouse cursor here.
+#[derive(Resource, Default)]
+pub struct MousePosition(pub Vec2);
+
+#[derive(Component)]
+pub struct Mouse;
+
+pub fn update_mouse_position_system(
+    mut mouse_position_resource: ResMut<MousePosition>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+) {
+    // get the camera info and transform
+    // assuming there is exactly one main camera entity, so Query::single() is OK
+    let result = camera_query.get_single();
+    match result {
+        Ok(_) => {}
+        Err(_) => return,
+    }
+    let (camera, camera_transform) = camera_query.single();
+
+    // There is only one primary window, so we can similarly get it from the query:
+    let window = window_query.single();
+
+    // check if the cursor is inside the window and get its position
+    // then, ask bevy to convert into world coordinates, and truncate to discard Z
+    if let Some(world_position) = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor))
+        .map(|ray| ray.origin.truncate())
+    {
+        //print what we found
+        // info!("World coords: {}/{}", world_position.x, world_position.y);
+        //update the resource
+        mouse_position_resource.0 = world_position;
+    }
+}
+
+/// The world position the ship aims at and the crosshair sits on, set by whichever of the
+/// mouse or the right stick moved last.
+#[derive(Resource, Default)]
+pub struct AimTarget(pub Vec2);
+
+/// Which input is allowed to move the [`AimTarget`].
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AimScheme {
+    //whichever of the mouse or the right stick moved last
+    #[default]
+    Auto,
+    Mouse,
+    Gamepad,
+}
+
+impl AimScheme {
+    pub fn next(self) -> Self {
+        match self {
+            AimScheme::Auto => AimScheme::Mouse,
+            AimScheme::Mouse => AimScheme::Gamepad,
+            AimScheme::Gamepad => AimScheme::Auto,
+        }
+    }
+}
+
+fn aim_with_mouse(
+    mut cursor_moved: EventReader<CursorMoved>,
+    mouse_position: Res<MousePosition>,
+    scheme: Res<AimScheme>,
+    mut aim_target: ResMut<AimTarget>,
+) {
+    //a mouse sitting still leaves the aim to the stick
+    if cursor_moved.read().count() > 0 && *scheme != AimScheme::Gamepad {
+        aim_target.0 = mouse_position.0;
+    }
+}
+
+fn sync_crosshair(aim_target: Res<AimTarget>, mut mouse_query: Query<&mut Transform, With<Mouse>>) {
+    for mut mouse in mouse_query.iter_mut() {
+        mouse.translation = aim_target.0.extend(0.0);
+    }
+}
+
+/// Hides the OS cursor and, where the platform allows it, keeps it inside the window.
+fn set_cursor_grab(window: &mut Window, grabbed: bool) {
+    window.cursor.visible = !grabbed;
+    //browsers only allow locking the pointer, so on wasm the cursor is just hidden
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    {
+        window.cursor.grab_mode = if grabbed {
+            CursorGrabMode::Confined
+        } else {
+            CursorGrabMode::None
+        };
+    }
+}
+
+fn hide_cursor(mut windows: Query<&mut Window, With<PrimaryWindow>>) {
+    for mut window in windows.iter_mut() {
+        set_cursor_grab(&mut window, true);
+    }
+}
+
+fn show_cursor(mut windows: Query<&mut Window, With<PrimaryWindow>>) {
+    for mut window in windows.iter_mut() {
+        set_cursor_grab(&mut window, false);
+    }
+}
+
+// This system grabs the mouse again when the left mouse button is pressed,
+// in case the window lost it (alt-tab and the like)
+fn grab_mouse(
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+    mouse: Res<Input<MouseButton>>,
+) {
+    if mouse.just_pressed(MouseButton::Left) {
+        for mut window in windows.iter_mut() {
+            set_cursor_grab(&mut window, true);
+        }
+    }
+}
+
+/// Hides the crosshair while the cursor is outside the window instead of leaving it
+/// frozen at the last position.
+fn track_cursor_presence(
+    mut left: EventReader<CursorLeft>,
+    mut entered: EventReader<CursorEntered>,
+    mut mouse_query: Query<&mut Visibility, With<Mouse>>,
+) {
+    let visibility = match (left.read().count() > 0, entered.read().count() > 0) {
+        //entered wins if both happened this frame, the cursor is most likely back
+        (_, true) => Visibility::Inherited,
+        (true, false) => Visibility::Hidden,
+        (false, false) => return,
+    };
+    for mut mouse in mouse_query.iter_mut() {
+        *mouse = visibility;
+    }
+}
+
+#[derive(Component)]
+pub struct LookAtMouse;
+
+fn look_at_aim_target(
+    aim_target: Res<AimTarget>,
+    mut observers_query: Query<&mut Transform, With<LookAtMouse>>,
+) {
+    for mut entity_transform in observers_query.iter_mut() {
+        let diff = aim_target.0 - entity_transform.translation.xy();
+        //the sprites point up, not along x
+        let angle = diff.y.atan2(diff.x) - PI / 2.0;
+        // info!("diff: {}, angle: {}", diff, angle);
+        entity_transform.rotation = Quat::from_rotation_z(angle);
+    }
+}
+
+/// An event sent for a movement input action.
+#[derive(Event)]
+pub enum InputAction {
+    Move(Vec2),
+    Fire,
+    Dash,
+    Bomb,
+}
+
+pub fn proto_input(
+    mut input_event_writer: EventWriter<InputAction>,
+    keyboard_input: Res<Input<KeyCode>>,
+) {
+    let left = keyboard_input.any_pressed([KeyCode::A, KeyCode::Left]);
+    let right = keyboard_input.any_pressed([KeyCode::D, KeyCode::Right]);
+    let up = keyboard_input.any_pressed([KeyCode::W, KeyCode::Up]);
+    let down = keyboard_input.any_pressed([KeyCode::S, KeyCode::Down]);
+    let horizontal = match (left, right) {
+        (true, true) => 0.0,
+        (true, false) => -1.0,
+        (false, true) => 1.0,
+        (false, false) => 0.0,
+    };
+
+    let vertical = match (up, down) {
+        (true, true) => 0.0,
+        (true, false) => 1.0,
+        (false, true) => -1.0,
+        (false, false) => 0.0,
+    };
+    // info!("h: {}, v:{}", horizontal, vertical);
+    if horizontal.abs() > 0.0 || vertical.abs() > 0.0 {
+        //keep diagonals from thrusting harder than a single direction
+        let direction = Vec2 {
+            x: horizontal,
+            y: vertical,
+        };
+        input_event_writer.send(InputAction::Move(direction.clamp_length_max(1.0)))
+    }
+    let fire = keyboard_input.any_pressed([KeyCode::Space]);
+    if fire {
+        input_event_writer.send(InputAction::Fire);
+    }
+    if keyboard_input.any_just_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        input_event_writer.send(InputAction::Dash);
+    }
+    if keyboard_input.just_pressed(KeyCode::B) {
+        input_event_writer.send(InputAction::Bomb);
+    }
+}
+
+/// Stick deflection below `deadzone` is treated as drift and ignored.
+#[derive(Resource)]
+pub struct GamepadSettings {
+    pub deadzone: f32,
+    //how far in front of the ship the right stick puts the crosshair
+    pub aim_distance: f32,
+}
+
+impl Default for GamepadSettings {
+    fn default() -> Self {
+        Self {
+            deadzone: 0.15,
+            aim_distance: 200.0,
+        }
+    }
+}
+
+pub fn gamepad_input(
+    mut input_event_writer: EventWriter<InputAction>,
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    buttons: Res<Input<GamepadButton>>,
+    settings: Res<GamepadSettings>,
+    scheme: Res<AimScheme>,
+    mut aim_target: ResMut<AimTarget>,
+    mut mouse_query: Query<&mut Visibility, With<Mouse>>,
+    ship_query: Query<&Transform, With<Ship>>,
+) {
+    for gamepad in gamepads.iter() {
+        let stick = |x, y| {
+            Vec2::new(
+                axes.get(GamepadAxis::new(gamepad, x)).unwrap_or(0.0),
+                axes.get(GamepadAxis::new(gamepad, y)).unwrap_or(0.0),
+            )
+        };
+        let left = stick(GamepadAxisType::LeftStickX, GamepadAxisType::LeftStickY);
+        if left.length() > settings.deadzone {
+            input_event_writer.send(InputAction::Move(left.clamp_length_max(1.0)));
+        }
+        let fire = buttons.any_pressed([
+            GamepadButton::new(gamepad, GamepadButtonType::RightTrigger2),
+            GamepadButton::new(gamepad, GamepadButtonType::South),
+        ]);
+        if fire {
+            input_event_writer.send(InputAction::Fire);
+        }
+        if buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::East)) {
+            input_event_writer.send(InputAction::Dash);
+        }
+        if buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::North)) {
+            input_event_writer.send(InputAction::Bomb);
+        }
+        //the right stick moves the aim around the ship and takes over from the mouse
+        //until the mouse moves again
+        let right = stick(GamepadAxisType::RightStickX, GamepadAxisType::RightStickY);
+        if right.length() > settings.deadzone && *scheme != AimScheme::Mouse {
+            if let Ok(ship) = ship_query.get_single() {
+                aim_target.0 = ship.translation.xy() + right.normalize() * settings.aim_distance;
+                for mut visibility in mouse_query.iter_mut() {
+                    //the stick aims even while the cursor is off in another window
+                    *visibility = Visibility::Inherited;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        ecs::event::ManualEventReader,
+        input::gamepad::{GamepadConnection, GamepadConnectionEvent, GamepadInfo},
+    };
+
+    use super::*;
+
+    /// The moves sent since the app started.
+    fn moves(app: &App) -> Vec<Vec2> {
+        let events = app.world.resource::<Events<InputAction>>();
+        ManualEventReader::<InputAction>::default()
+            .read(events)
+            .filter_map(|event| match event {
+                InputAction::Move(direction) => Some(*direction),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn the_left_stick_moves_past_the_deadzone() {
+        let mut app = App::new();
+        app.add_plugins(bevy::input::InputPlugin)
+            .init_resource::<GamepadSettings>()
+            .init_resource::<AimScheme>()
+            .init_resource::<AimTarget>()
+            .add_event::<InputAction>()
+            .add_systems(Update, gamepad_input);
+        let gamepad = Gamepad::new(0);
+        app.world.send_event(GamepadConnectionEvent::new(
+            gamepad,
+            GamepadConnection::Connected(GamepadInfo {
+                name: "test pad".into(),
+            }),
+        ));
+        app.update();
+        let tilt = |app: &mut App, x: f32, y: f32| {
+            let mut axes = app.world.resource_mut::<Axis<GamepadAxis>>();
+            axes.set(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX), x);
+            axes.set(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY), y);
+            app.update();
+        };
+
+        //drift inside the deadzone is ignored
+        tilt(&mut app, 0.05, 0.05);
+        assert!(moves(&app).is_empty());
+
+        tilt(&mut app, 0.3, -0.4);
+        let moved = moves(&app);
+        assert_eq!(moved.len(), 1);
+        assert!(moved[0].distance(Vec2::new(0.3, -0.4)) < 0.001);
+    }
+
+    #[test]
+    fn diagonal_keys_thrust_no_harder_than_one() {
+        let mut app = App::new();
+        app.init_resource::<Input<KeyCode>>()
+            .add_event::<InputAction>()
+            .add_systems(Update, proto_input);
+        let mut keys = app.world.resource_mut::<Input<KeyCode>>();
+        keys.press(KeyCode::W);
+        keys.press(KeyCode::D);
+        app.update();
+
+        let moved = moves(&app);
+        assert_eq!(moved.len(), 1);
+        assert!((moved[0].length() - 1.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn the_cursor_hides_while_playing() {
+        let mut app = App::new();
+        app.add_state::<GameState>()
+            .add_systems(OnEnter(GameState::Playing), hide_cursor)
+            .add_systems(OnExit(GameState::Playing), show_cursor);
+        let window = app.world.spawn((Window::default(), PrimaryWindow)).id();
+        let enter = |app: &mut App, state| {
+            app.world.resource_mut::<NextState<GameState>>().set(state);
+            app.update();
+            app.world.get::<Window>(window).unwrap().cursor.visible
+        };
+
+        assert!(!enter(&mut app, GameState::Playing));
+        assert!(enter(&mut app, GameState::Paused));
+    }
+
+    #[test]
+    fn the_ship_turns_to_face_the_aim_target() {
+        let mut app = App::new();
+        app.init_resource::<AimTarget>()
+            .add_systems(Update, look_at_aim_target);
+        let ship = app
+            .world
+            .spawn((LookAtMouse, Transform::from_xyz(10.0, 20.0, 0.0)))
+            .id();
+        let aim_at = |app: &mut App, target: Vec2| {
+            app.world.resource_mut::<AimTarget>().0 = target;
+            app.update();
+            let rotation = app.world.get::<Transform>(ship).unwrap().rotation;
+            rotation.to_euler(EulerRot::XYZ).2
+        };
+
+        //the sprite points up, so straight north needs no turn at all
+        assert!(aim_at(&mut app, Vec2::new(10.0, 200.0)).abs() < 0.001);
+        assert!((aim_at(&mut app, Vec2::new(200.0, 20.0)) + PI / 2.0).abs() < 0.001);
+    }
+}
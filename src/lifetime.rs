@@ -0,0 +1,113 @@
+use bevy::prelude::*;
+
+use crate::weapons::{Laser, Retired};
+
+/// Despawns entities once their [`Lifetime`] runs out and fades out the ones with a
+/// [`FadeOut`] on the way.
+pub struct LifetimePlugin;
+
+impl Plugin for LifetimePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<LifetimeExpiredEvent>()
+            .add_systems(PostUpdate, (update_lifetimes, despawn_expired).chain())
+            .add_systems(Update, fade_out);
+    }
+}
+
+#[derive(Component)]
+pub struct Lifetime(pub Timer);
+
+/// Sent when an entity's [`Lifetime`] runs out, just before it is despawned or, for lasers,
+/// pooled.
+#[derive(Event)]
+pub struct LifetimeExpiredEvent {
+    pub entity: Entity,
+}
+
+pub fn update_lifetimes(
+    mut lifetimes_query: Query<(Entity, &mut Lifetime)>,
+    time: Res<Time>,
+    mut expired_writer: EventWriter<LifetimeExpiredEvent>,
+) {
+    let delta = time.delta();
+    for (entity, mut lifetime) in lifetimes_query.iter_mut() {
+        lifetime.0.tick(delta);
+        if lifetime.0.just_finished() {
+            expired_writer.send(LifetimeExpiredEvent { entity });
+        }
+    }
+}
+
+pub fn despawn_expired(
+    mut events: EventReader<LifetimeExpiredEvent>,
+    lasers: Query<(), With<Laser>>,
+    mut commands: Commands,
+) {
+    for event in events.read() {
+        if let Some(mut entity) = commands.get_entity(event.entity) {
+            //lasers go back to the pool to be fired again
+            if lasers.contains(event.entity) {
+                entity.insert(Retired);
+            } else {
+                entity.despawn_recursive();
+            }
+        }
+    }
+}
+
+/// Fades a sprite's alpha out from the given starting alpha over its [`Lifetime`].
+#[derive(Component)]
+pub struct FadeOut(pub f32);
+
+fn fade_out(
+    mut query: Query<(
+        Option<&mut Sprite>,
+        Option<&mut TextureAtlasSprite>,
+        &Lifetime,
+        &FadeOut,
+    )>,
+) {
+    for (sprite, atlas_sprite, lifetime, fade) in query.iter_mut() {
+        let alpha = fade.0 * lifetime.0.percent_left();
+        if let Some(mut sprite) = sprite {
+            sprite.color.set_a(alpha);
+        }
+        if let Some(mut sprite) = atlas_sprite {
+            sprite.color.set_a(alpha);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn advance(app: &mut App, delta: Duration) {
+        app.world.resource_mut::<Time>().advance_by(delta);
+        app.update();
+    }
+
+    #[test]
+    fn an_expired_laser_is_announced_once() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_event::<LifetimeExpiredEvent>()
+            .add_systems(Update, (update_lifetimes, despawn_expired).chain());
+        let laser = app
+            .world
+            .spawn((Laser, Lifetime(Timer::from_seconds(1.0, TimerMode::Once))))
+            .id();
+        let mut reader = bevy::ecs::event::ManualEventReader::<LifetimeExpiredEvent>::default();
+        let mut expired = Vec::new();
+        for _ in 0..4 {
+            advance(&mut app, Duration::from_millis(600));
+            let events = app.world.resource::<Events<LifetimeExpiredEvent>>();
+            expired.extend(reader.read(events).map(|event| event.entity));
+        }
+        assert_eq!(expired, vec![laser]);
+        //lasers are pooled rather than despawned
+        assert!(app.world.get::<Retired>(laser).is_some());
+    }
+}
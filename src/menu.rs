@@ -0,0 +1,379 @@
+use bevy::prelude::*;
+
+use crate::{
+    asteroids::{AsteroidSpawnQueue, SpawnAsteroidEvent},
+    boss_rush::BossRush,
+    run::{Difficulty, GameEntity, GameState, StartGameEvent},
+    score::Score,
+    settings::SettingsState,
+    ship::Lives,
+    weapons::Bombs,
+};
+
+/// The title screen the game boots into, with buttons to start a run, change settings or
+/// quit, and the overlay shown while paused.
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Menu), spawn_menu)
+            .add_systems(OnExit(GameState::Menu), despawn_menu)
+            .add_systems(Update, menu_buttons.run_if(in_state(GameState::Menu)))
+            .add_systems(
+                Update,
+                update_difficulty_label
+                    .after(menu_buttons)
+                    .run_if(in_state(GameState::Menu)),
+            )
+            .add_systems(OnEnter(GameState::Paused), spawn_pause_menu)
+            .add_systems(OnExit(GameState::Paused), despawn_pause_menu)
+            .add_systems(Update, pause_buttons.run_if(in_state(GameState::Paused)));
+    }
+}
+
+/// The top of the menu, despawning it takes the whole screen with it.
+#[derive(Component)]
+pub struct MenuRoot;
+
+#[derive(Component, Clone, Copy)]
+enum MenuButton {
+    Play,
+    BossRush,
+    //cycles through the difficulties
+    Difficulty,
+    Settings,
+    //browsers don't let a page close itself, so there is no quit on wasm
+    #[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), allow(dead_code))]
+    Quit,
+}
+
+pub const BUTTON_COLOR: Color = Color::rgb(0.15, 0.2, 0.35);
+pub const BUTTON_HOVER_COLOR: Color = Color::rgb(0.25, 0.35, 0.6);
+
+fn spawn_menu(mut commands: Commands, difficulty: Res<Difficulty>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(20.0),
+                    ..default()
+                },
+                ..default()
+            },
+            MenuRoot,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Survive",
+                TextStyle {
+                    font_size: 80.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+            spawn_button(parent, "Play", MenuButton::Play);
+            spawn_button(parent, "Boss Rush", MenuButton::BossRush);
+            spawn_button(
+                parent,
+                &difficulty_label(*difficulty),
+                MenuButton::Difficulty,
+            );
+            spawn_button(parent, "Settings", MenuButton::Settings);
+            #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+            spawn_button(parent, "Quit", MenuButton::Quit);
+        });
+}
+
+pub fn spawn_button(parent: &mut ChildBuilder, label: &str, button: impl Component) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(260.0),
+                    height: Val::Px(60.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: BUTTON_COLOR.into(),
+                ..default()
+            },
+            button,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                label,
+                TextStyle {
+                    font_size: 32.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn difficulty_label(difficulty: Difficulty) -> String {
+    format!("Difficulty: {:?}", difficulty)
+}
+
+fn update_difficulty_label(
+    difficulty: Res<Difficulty>,
+    buttons: Query<(&MenuButton, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    if !difficulty.is_changed() {
+        return;
+    }
+    for (button, children) in buttons.iter() {
+        if !matches!(button, MenuButton::Difficulty) {
+            continue;
+        }
+        for child in children.iter() {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                text.sections[0].value = difficulty_label(*difficulty);
+            }
+        }
+    }
+}
+
+fn despawn_menu(mut commands: Commands, roots: Query<Entity, With<MenuRoot>>) {
+    for entity in roots.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn menu_buttons(
+    mut buttons: Query<(&Interaction, &MenuButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut start_event_writer: EventWriter<StartGameEvent>,
+    mut exit_writer: EventWriter<bevy::app::AppExit>,
+    mut difficulty: ResMut<Difficulty>,
+    mut settings_state: ResMut<NextState<SettingsState>>,
+    mut boss_rush: ResMut<BossRush>,
+) {
+    for (interaction, button, mut color) in buttons.iter_mut() {
+        match interaction {
+            Interaction::Pressed => match button {
+                //the start event brings the run in through the intro and on to Playing
+                MenuButton::Play => {
+                    boss_rush.active = false;
+                    start_event_writer.send(StartGameEvent);
+                }
+                MenuButton::BossRush => {
+                    boss_rush.active = true;
+                    start_event_writer.send(StartGameEvent);
+                }
+                MenuButton::Difficulty => *difficulty = difficulty.next(),
+                MenuButton::Settings => settings_state.set(SettingsState::Open),
+                MenuButton::Quit => exit_writer.send(bevy::app::AppExit),
+            },
+            Interaction::Hovered => *color = BUTTON_HOVER_COLOR.into(),
+            Interaction::None => *color = BUTTON_COLOR.into(),
+        }
+    }
+}
+
+/// The top of the pause overlay.
+#[derive(Component)]
+pub struct PauseMenuRoot;
+
+#[derive(Component, Clone, Copy)]
+enum PauseButton {
+    Resume,
+    Settings,
+    MainMenu,
+}
+
+fn spawn_pause_menu(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(20.0),
+                    ..default()
+                },
+                //dims the frozen game behind it
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+                ..default()
+            },
+            PauseMenuRoot,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Paused",
+                TextStyle {
+                    font_size: 60.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+            spawn_button(parent, "Resume", PauseButton::Resume);
+            spawn_button(parent, "Settings", PauseButton::Settings);
+            spawn_button(parent, "Main Menu", PauseButton::MainMenu);
+        });
+}
+
+fn despawn_pause_menu(mut commands: Commands, roots: Query<Entity, With<PauseMenuRoot>>) {
+    for entity in roots.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn pause_buttons(
+    mut buttons: Query<(&Interaction, &PauseButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut settings_state: ResMut<NextState<SettingsState>>,
+    mut commands: Commands,
+    entity_query: Query<Entity, With<GameEntity>>,
+    mut asteroid_event_writer: ResMut<Events<SpawnAsteroidEvent>>,
+    mut asteroid_queue: ResMut<AsteroidSpawnQueue>,
+) {
+    for (interaction, button, mut color) in buttons.iter_mut() {
+        match interaction {
+            Interaction::Pressed => match button {
+                PauseButton::Resume => next_state.set(GameState::Playing),
+                PauseButton::Settings => settings_state.set(SettingsState::Open),
+                PauseButton::MainMenu => {
+                    //abandon the run, the next one starts from scratch through Play
+                    for entity in entity_query.iter() {
+                        commands.entity(entity).despawn_recursive();
+                    }
+                    asteroid_event_writer.clear();
+                    asteroid_queue.pending.clear();
+                    commands.insert_resource(Score::default());
+                    commands.insert_resource(Lives::default());
+                    commands.insert_resource(Bombs::default());
+                    next_state.set(GameState::Menu);
+                }
+            },
+            Interaction::Hovered => *color = BUTTON_HOVER_COLOR.into(),
+            Interaction::None => *color = BUTTON_COLOR.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ghost::GhostRecorder,
+        movement::{PlayArea, ShipWrapSettings},
+        run::{handle_start_event, run_intro, GameRng, GameSeed},
+        run_log::RunLog,
+    };
+
+    #[test]
+    fn clicking_play_starts_a_run() {
+        let mut app = App::new();
+        app.add_plugins((TaskPoolPlugin::default(), AssetPlugin::default()))
+            .init_asset::<Image>()
+            .init_resource::<Time>()
+            .init_resource::<Input<KeyCode>>()
+            .add_state::<GameState>()
+            .add_state::<SettingsState>()
+            .add_event::<StartGameEvent>()
+            .add_event::<bevy::app::AppExit>()
+            .add_event::<SpawnAsteroidEvent>()
+            .init_resource::<Difficulty>()
+            .init_resource::<BossRush>()
+            .init_resource::<GameSeed>()
+            .init_resource::<GameRng>()
+            .init_resource::<PlayArea>()
+            .init_resource::<ShipWrapSettings>()
+            .init_resource::<GhostRecorder>()
+            .insert_resource(RunLog {
+                enabled: false,
+                ..default()
+            })
+            .add_systems(OnEnter(GameState::Menu), spawn_menu)
+            .add_systems(OnExit(GameState::Menu), despawn_menu)
+            .add_systems(
+                Update,
+                (
+                    menu_buttons.run_if(in_state(GameState::Menu)),
+                    handle_start_event,
+                    run_intro.run_if(in_state(GameState::Intro)),
+                )
+                    .chain(),
+            );
+        app.update();
+
+        let play = app
+            .world
+            .query::<(Entity, &MenuButton)>()
+            .iter(&app.world)
+            .find(|(_, button)| matches!(button, MenuButton::Play))
+            .map(|(entity, _)| entity)
+            .unwrap();
+        app.world.entity_mut(play).insert(Interaction::Pressed);
+        //skip the intro rather than wait out its asteroids
+        app.world
+            .resource_mut::<Input<KeyCode>>()
+            .press(KeyCode::Return);
+        for _ in 0..3 {
+            app.update();
+        }
+
+        assert_eq!(
+            *app.world.resource::<State<GameState>>().get(),
+            GameState::Playing
+        );
+        let mut roots = app.world.query_filtered::<(), With<MenuRoot>>();
+        assert_eq!(roots.iter(&app.world).count(), 0);
+    }
+
+    #[test]
+    fn main_menu_from_pause_abandons_the_run() {
+        let mut app = App::new();
+        app.add_state::<GameState>()
+            .add_state::<SettingsState>()
+            .add_event::<SpawnAsteroidEvent>()
+            .init_resource::<AsteroidSpawnQueue>()
+            .insert_resource(Score(500))
+            .insert_resource(Lives(1))
+            .add_systems(OnEnter(GameState::Paused), spawn_pause_menu)
+            .add_systems(OnExit(GameState::Paused), despawn_pause_menu)
+            .add_systems(Update, pause_buttons.run_if(in_state(GameState::Paused)));
+        let asteroid = app
+            .world
+            .spawn((crate::asteroids::AsteroidClass::Big, GameEntity))
+            .id();
+        let laser = app.world.spawn((crate::weapons::Laser, GameEntity)).id();
+        app.world
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Paused);
+        app.update();
+
+        let main_menu = app
+            .world
+            .query::<(Entity, &PauseButton)>()
+            .iter(&app.world)
+            .find(|(_, button)| matches!(button, PauseButton::MainMenu))
+            .map(|(entity, _)| entity)
+            .unwrap();
+        app.world.entity_mut(main_menu).insert(Interaction::Pressed);
+        app.update();
+        app.update();
+
+        assert_eq!(
+            *app.world.resource::<State<GameState>>().get(),
+            GameState::Menu
+        );
+        assert!(app.world.get_entity(asteroid).is_none());
+        assert!(app.world.get_entity(laser).is_none());
+        assert_eq!(app.world.resource::<Score>().0, 0);
+        assert_eq!(app.world.resource::<Lives>().0, Lives::default().0);
+        let mut roots = app.world.query_filtered::<(), With<PauseMenuRoot>>();
+        assert_eq!(roots.iter(&app.world).count(), 0);
+    }
+}
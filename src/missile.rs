@@ -0,0 +1,348 @@
+use std::{f32::consts::PI, time::Duration};
+
+use bevy::prelude::*;
+use bevy_xpbd_2d::prelude::*;
+
+use crate::{
+    asteroids::{AsteroidClass, Despawning, WeakSpot},
+    collisions::{apply_damage, DamageEvent, Layer},
+    explosion::{Explosion, SpawnExplosionEvent},
+    input::AimTarget,
+    lifetime::{FadeOut, Lifetime},
+    run::{GameEntity, GameState},
+    ufo::Ufo,
+    weapons::{update_weapons, AimMode},
+};
+
+/// Missiles fired by [`crate::weapons::WeaponKind::Missile`] weapons. They home in on the nearest
+/// asteroid and blow up on contact, hurting everything close by.
+pub struct MissilePlugin;
+
+impl Plugin for MissilePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MissileSettings>()
+            .add_event::<SpawnMissileEvent>()
+            .add_systems(Update, missile_spawner.after(update_weapons))
+            .add_systems(
+                Update,
+                (missile_guidance, emit_missile_smoke)
+                    .chain()
+                    .run_if(not(in_state(GameState::Paused))),
+            )
+            .add_systems(
+                Update,
+                missile_hits
+                    .before(apply_damage)
+                    .run_if(not(in_state(GameState::Paused))),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct MissileSettings {
+    pub speed: f32,
+    //radians per second a missile can turn towards its target
+    pub turn_rate: f32,
+    pub lifetime: Duration,
+    pub blast_radius: f32,
+    pub blast_damage: i32,
+    pub blast_impulse: f32,
+}
+
+impl Default for MissileSettings {
+    fn default() -> Self {
+        Self {
+            speed: 320.0,
+            turn_rate: 3.0,
+            lifetime: Duration::from_secs(4),
+            blast_radius: 80.0,
+            blast_damage: 3,
+            blast_impulse: 120.0,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Missile;
+
+/// Steers a projectile towards `target`, picking the nearest asteroid whenever it has none.
+#[derive(Component)]
+pub struct Homing {
+    pub turn_rate: f32,
+    pub target: Option<Entity>,
+}
+
+/// Puffs of smoke left behind a missile, one every time the timer goes off.
+#[derive(Component)]
+struct SmokeTrail(Timer);
+
+/// An event sent for firing a missile
+#[derive(Event)]
+pub struct SpawnMissileEvent {
+    pub origin: Transform,
+    pub inherited_velocity: Vec2,
+}
+
+fn missile_spawner(
+    mut reader: EventReader<SpawnMissileEvent>,
+    settings: Res<MissileSettings>,
+    aim_mode: Res<AimMode>,
+    aim_target: Res<AimTarget>,
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+) {
+    for event in reader.read() {
+        //missiles leave the same way a laser would and only start turning once out
+        let angle = aim_mode.angle(&event.origin, aim_target.0);
+        let direction = Vec2::new(angle.cos(), angle.sin());
+        commands.spawn((
+            SpriteBundle {
+                texture: asset_server.load("lasers/laserBlue01.png"),
+                sprite: Sprite {
+                    color: Color::rgb(1.0, 0.6, 0.2),
+                    ..default()
+                },
+                transform: event
+                    .origin
+                    .with_rotation(Quat::from_rotation_z(angle - PI / 2.0))
+                    .with_scale(Vec3::new(1.4, 0.6, 1.0)),
+                ..default()
+            },
+            RigidBody::Kinematic,
+            Collider::ball(8.0),
+            //it blows up instead of bouncing off
+            Sensor,
+            CollisionLayers::new([Layer::PlayerBullet], [Layer::Asteroid, Layer::Enemy]),
+            LinearVelocity(direction * settings.speed + event.inherited_velocity),
+            Lifetime(Timer::new(settings.lifetime, TimerMode::Once)),
+            Missile,
+            Homing {
+                turn_rate: settings.turn_rate,
+                target: None,
+            },
+            SmokeTrail(Timer::from_seconds(0.03, TimerMode::Repeating)),
+            GameEntity,
+        ));
+    }
+}
+
+fn missile_guidance(
+    time: Res<Time>,
+    settings: Res<MissileSettings>,
+    mut missiles: Query<(&mut Transform, &mut LinearVelocity, &mut Homing)>,
+    asteroids: Query<
+        (Entity, &Transform),
+        (With<AsteroidClass>, Without<Despawning>, Without<Homing>),
+    >,
+) {
+    for (mut transform, mut velocity, mut homing) in missiles.iter_mut() {
+        let position = transform.translation.xy();
+        //keep chasing the same asteroid until it is gone
+        let target = homing
+            .target
+            .and_then(|target| asteroids.get(target).ok())
+            .or_else(|| {
+                asteroids.iter().min_by(|a, b| {
+                    let distance = |(_, transform): &(Entity, &Transform)| {
+                        transform.translation.xy().distance_squared(position)
+                    };
+                    distance(a).total_cmp(&distance(b))
+                })
+            });
+        homing.target = target.map(|(entity, _)| entity);
+        let heading = velocity.0.y.atan2(velocity.0.x);
+        if let Some((_, target)) = target {
+            let to_target = target.translation.xy() - position;
+            let wanted = to_target.y.atan2(to_target.x);
+            //the shortest way round, in -PI..PI
+            let difference = (wanted - heading + PI).rem_euclid(2.0 * PI) - PI;
+            let max_turn = homing.turn_rate * time.delta_seconds();
+            let heading = heading + difference.clamp(-max_turn, max_turn);
+            velocity.0 = Vec2::new(heading.cos(), heading.sin()) * settings.speed;
+        }
+        let heading = velocity.0.y.atan2(velocity.0.x);
+        transform.rotation = Quat::from_rotation_z(heading - PI / 2.0);
+    }
+}
+
+fn emit_missile_smoke(
+    time: Res<Time>,
+    mut missiles: Query<(&Transform, &mut SmokeTrail)>,
+    mut commands: Commands,
+) {
+    for (transform, mut trail) in missiles.iter_mut() {
+        trail.0.tick(time.delta());
+        for _ in 0..trail.0.times_finished_this_tick() {
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::rgb(0.7, 0.7, 0.7),
+                        custom_size: Some(Vec2::splat(6.0)),
+                        ..default()
+                    },
+                    //behind the missile
+                    transform: Transform::from_translation(transform.translation.xy().extend(-1.0)),
+                    ..default()
+                },
+                Lifetime(Timer::from_seconds(0.4, TimerMode::Once)),
+                FadeOut(0.5),
+                GameEntity,
+            ));
+        }
+    }
+}
+
+fn missile_hits(
+    mut events: EventReader<Collision>,
+    settings: Res<MissileSettings>,
+    missiles: Query<&Transform, With<Missile>>,
+    weak_spots: Query<&Parent, With<WeakSpot>>,
+    ufos: Query<(), With<Ufo>>,
+    mut damage_writer: EventWriter<DamageEvent>,
+    mut explosion_writer: EventWriter<SpawnExplosionEvent>,
+    mut commands: Commands,
+) {
+    //a missile can touch a few things in the frame it hits, it only goes off once
+    let mut exploded = Vec::new();
+    for event in events.read() {
+        let pair = [
+            (event.0.entity1, event.0.entity2),
+            (event.0.entity2, event.0.entity1),
+        ];
+        for (missile, other) in pair {
+            let Ok(transform) = missiles.get(missile) else {
+                continue;
+            };
+            if exploded.contains(&missile) {
+                continue;
+            }
+            exploded.push(missile);
+            commands.entity(missile).despawn_recursive();
+            let position = transform.translation.xy();
+            explosion_writer.send(SpawnExplosionEvent {
+                position,
+                scale: 0.8,
+            });
+            //a hit on a weak spot gets through to its armored asteroid, and the blast only
+            //reaches asteroids, so a ufo takes the full damage from the hit itself
+            let direct_hit = match weak_spots.get(other) {
+                Ok(parent) => Some(parent.get()),
+                Err(_) if ufos.contains(other) => Some(other),
+                Err(_) => None,
+            };
+            if let Some(target) = direct_hit {
+                damage_writer.send(DamageEvent {
+                    target,
+                    amount: settings.blast_damage,
+                    source: missile,
+                });
+            }
+            //the blast itself is left to an explosion entity
+            commands.spawn((
+                TransformBundle::from_transform(Transform::from_translation(position.extend(0.0))),
+                Explosion {
+                    radius: settings.blast_radius,
+                    damage: settings.blast_damage,
+                    impulse: settings.blast_impulse,
+                    hurts_ship: false,
+                },
+                Lifetime(Timer::from_seconds(0.1, TimerMode::Once)),
+                GameEntity,
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        asteroids::{AsteroidHealth, AsteroidHitEvent},
+        collisions::tests::collision,
+        shield::ShieldBrokenEvent,
+        ship::PlayerDamagedEvent,
+    };
+
+    #[test]
+    fn a_missile_curves_towards_an_asteroid_off_to_the_side() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<MissileSettings>()
+            .add_systems(Update, missile_guidance);
+        let settings = MissileSettings::default();
+        let missile = app
+            .world
+            .spawn((
+                Transform::default(),
+                LinearVelocity(Vec2::Y * settings.speed),
+                Homing {
+                    turn_rate: settings.turn_rate,
+                    target: None,
+                },
+            ))
+            .id();
+        //straight ahead is up, the asteroid is off to the right
+        let asteroid = app
+            .world
+            .spawn((AsteroidClass::Big, Transform::from_xyz(300.0, 0.0, 0.0)))
+            .id();
+
+        let frame = Duration::from_millis(16);
+        let mut headings = Vec::new();
+        for _ in 0..10 {
+            app.world.resource_mut::<Time>().advance_by(frame);
+            app.update();
+            //no physics here, so move it along by hand
+            let velocity = app.world.get::<LinearVelocity>(missile).unwrap().0;
+            app.world.get_mut::<Transform>(missile).unwrap().translation +=
+                (velocity * frame.as_secs_f32()).extend(0.0);
+            headings.push(velocity.y.atan2(velocity.x));
+        }
+
+        let homing = app.world.get::<Homing>(missile).unwrap();
+        assert_eq!(homing.target, Some(asteroid));
+        //a little further right every frame, never snapping straight at it
+        let max_turn = settings.turn_rate * frame.as_secs_f32();
+        let mut previous = PI / 2.0;
+        for heading in headings {
+            assert!(heading < previous);
+            assert!(previous - heading <= max_turn + 1e-4);
+            previous = heading;
+        }
+        assert!(previous < PI / 2.0 - 0.3);
+    }
+
+    #[test]
+    fn a_missile_hurts_the_ufo_it_hits() {
+        let mut app = App::new();
+        app.init_resource::<MissileSettings>()
+            .add_event::<Collision>()
+            .add_event::<DamageEvent>()
+            .add_event::<SpawnExplosionEvent>()
+            .add_event::<AsteroidHitEvent>()
+            .add_event::<PlayerDamagedEvent>()
+            .add_event::<ShieldBrokenEvent>()
+            .add_systems(Update, (missile_hits, apply_damage).chain());
+        let missile = app.world.spawn((Missile, Transform::default())).id();
+        let ufo = app
+            .world
+            .spawn((Ufo { health: 6 }, Transform::default()))
+            .id();
+
+        app.world.send_event(collision(missile, ufo));
+        app.update();
+
+        let blast_damage = MissileSettings::default().blast_damage;
+        assert_eq!(app.world.get::<Ufo>(ufo).unwrap().health, 6 - blast_damage);
+        assert!(app.world.get_entity(missile).is_none());
+        //an asteroid caught in the same blast is left to the explosion
+        let asteroid = app
+            .world
+            .spawn((AsteroidClass::Big, AsteroidHealth(3)))
+            .id();
+        let missile = app.world.spawn((Missile, Transform::default())).id();
+        app.world.send_event(collision(missile, asteroid));
+        app.update();
+        assert_eq!(app.world.get::<AsteroidHealth>(asteroid).unwrap().0, 3);
+    }
+}
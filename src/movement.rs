@@ -0,0 +1,957 @@
+use bevy::prelude::*;
+use bevy_xpbd_2d::{math::Scalar, prelude::*};
+use rand::Rng;
+
+use crate::{
+    asteroids::AsteroidClass,
+    collisions::{apply_damage, DamageEvent, Layer},
+    cooldown::{tick_cooldowns, Cooldown},
+    input::{gamepad_input, proto_input, InputAction},
+    lifetime::{FadeOut, Lifetime},
+    run::{GameEntity, GameRng, GameRngSet, GameState, MainCamera},
+    ship::{Invulnerable, Ship, ShipHealth, SHIP_HEALTH},
+};
+
+/// Moves the ship on input, trails its thrust and wraps everything around the play area.
+pub struct MovementPlugin;
+
+impl Plugin for MovementPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            movement
+                .after(proto_input)
+                .after(gamepad_input)
+                .run_if(not(in_state(GameState::Paused))),
+        )
+        .add_systems(Update, emit_thrust.after(movement))
+        .init_resource::<DashSettings>()
+        .add_event::<DashEvent>()
+        .add_systems(Update, tick_cooldowns::<Dash>)
+        .add_systems(
+            Update,
+            dash.after(tick_cooldowns::<Dash>)
+                .after(proto_input)
+                .after(gamepad_input)
+                .run_if(not(in_state(GameState::Paused))),
+        )
+        .add_systems(Update, dash_burst.after(dash))
+        .init_resource::<HyperspaceSettings>()
+        .add_event::<HyperspaceEvent>()
+        .add_systems(Update, tick_cooldowns::<Hyperspace>)
+        .add_systems(
+            Update,
+            (
+                trigger_hyperspace,
+                hyperspace.in_set(GameRngSet::Hyperspace),
+            )
+                .chain()
+                .after(tick_cooldowns::<Hyperspace>)
+                .before(apply_damage)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(Update, wrapper)
+        .init_resource::<ShipWrapSettings>()
+        .add_systems(Update, toggle_ship_wrap.before(wrapper))
+        //constrain the play area to 16:9 so wrapping is the same on every window shape
+        .insert_resource(PlayArea {
+            aspect: Some(16.0 / 9.0),
+            ..default()
+        })
+        .add_systems(PreUpdate, update_play_area)
+        .add_systems(Update, draw_play_area_border)
+        .init_resource::<EdgeIndicatorSettings>()
+        .add_systems(
+            Update,
+            draw_edge_indicators
+                .after(wrapper)
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+#[derive(Component)]
+pub struct MovementAcceleration(Scalar);
+
+/// The damping factor used for slowing down movement.
+#[derive(Component)]
+pub struct MovementDampingFactor(Scalar);
+
+//ship controller bundle
+#[derive(Bundle)]
+pub struct ShipControllerBundle {
+    rigid_body: RigidBody,
+    collider: Collider,
+    acceleration: MovementAcceleration,
+    lineardamping: LinearDamping,
+    layer: CollisionLayers,
+    health: ShipHealth,
+}
+
+impl Default for ShipControllerBundle {
+    fn default() -> Self {
+        Self {
+            rigid_body: RigidBody::Dynamic,
+            collider: Collider::ball(40.0),
+            acceleration: MovementAcceleration(10.0 * 128.0),
+            lineardamping: LinearDamping(0.99),
+            layer: CollisionLayers::new(
+                [Layer::Player],
+                [Layer::Asteroid, Layer::Enemy, Layer::EnemyBullet],
+            ),
+            health: ShipHealth(SHIP_HEALTH),
+        }
+    }
+}
+
+/// Responds to [`InputAction`] events and moves character controllers accordingly.
+fn movement(
+    time: Res<Time>,
+    mut input_event_reader: EventReader<InputAction>,
+    mut ship_query: Query<(&mut LinearVelocity, &MovementAcceleration), With<Ship>>,
+) {
+    let delta_time = time.delta_seconds();
+
+    for event in input_event_reader.read() {
+        for mut ship in ship_query.iter_mut() {
+            match event {
+                InputAction::Move(direction) => {
+                    //testing
+                    let thrust = *direction * (ship.1 .0 * delta_time);
+                    ship.0 .0 += thrust;
+                }
+                InputAction::Fire | InputAction::Dash | InputAction::Bomb => {}
+            }
+        }
+    }
+}
+
+/// How hard a dash kicks the ship and how long it can't be hurt afterwards, in seconds.
+#[derive(Resource)]
+pub struct DashSettings {
+    pub impulse: f32,
+    pub invulnerable: f32,
+}
+
+impl Default for DashSettings {
+    fn default() -> Self {
+        Self {
+            impulse: 600.0,
+            invulnerable: 0.3,
+        }
+    }
+}
+
+/// Marks the cooldown between the ship's dashes.
+pub enum Dash {}
+
+/// Sent when the ship dashes, `direction` is the normalized way it was kicked.
+#[derive(Event)]
+pub struct DashEvent {
+    pub position: Vec2,
+    pub direction: Vec2,
+}
+
+fn dash(
+    settings: Res<DashSettings>,
+    mut input_event_reader: EventReader<InputAction>,
+    mut ships: Query<
+        (
+            Entity,
+            &Transform,
+            &mut LinearVelocity,
+            &mut Cooldown<Dash>,
+            Option<&Invulnerable>,
+        ),
+        With<Ship>,
+    >,
+    mut dash_writer: EventWriter<DashEvent>,
+    mut commands: Commands,
+) {
+    let mut steering = Vec2::ZERO;
+    let mut dashing = false;
+    for event in input_event_reader.read() {
+        match event {
+            InputAction::Move(direction) => steering += *direction,
+            InputAction::Dash => dashing = true,
+            InputAction::Fire | InputAction::Bomb => {}
+        }
+    }
+    for (entity, transform, mut velocity, mut cooldown, invulnerable) in ships.iter_mut() {
+        if !dashing || !cooldown.trigger() {
+            continue;
+        }
+        //with nothing held dash the way the ship is drifting, or else the way it faces
+        let direction = if steering != Vec2::ZERO {
+            steering.normalize()
+        } else if velocity.0 != Vec2::ZERO {
+            velocity.0.normalize()
+        } else {
+            (transform.rotation * Vec3::Y).xy()
+        };
+        velocity.0 += direction * settings.impulse;
+        //don't cut a longer respawn invulnerability short
+        let covered = invulnerable
+            .is_some_and(|invulnerable| invulnerable.0.remaining_secs() >= settings.invulnerable);
+        if !covered {
+            commands
+                .entity(entity)
+                .insert(Invulnerable(Timer::from_seconds(
+                    settings.invulnerable,
+                    TimerMode::Once,
+                )));
+        }
+        dash_writer.send(DashEvent {
+            position: transform.translation.xy(),
+            direction,
+        });
+    }
+}
+
+/// A burst of exhaust left behind where the ship dashed off.
+fn dash_burst(mut events: EventReader<DashEvent>, mut commands: Commands) {
+    let mut rng = rand::thread_rng();
+    for event in events.read() {
+        let backwards = -event.direction;
+        for _ in 0..12 {
+            let angle = backwards.y.atan2(backwards.x) + rng.gen_range(-0.6..0.6);
+            let speed = rng.gen_range(120.0..240.0);
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::rgb(0.6, 0.8, 1.0),
+                        custom_size: Some(Vec2::splat(5.0)),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(event.position.extend(-1.0)),
+                    ..default()
+                },
+                RigidBody::Kinematic,
+                LinearVelocity(Vec2::new(angle.cos(), angle.sin()) * speed),
+                Lifetime(Timer::from_seconds(0.4, TimerMode::Once)),
+                FadeOut(0.9),
+                GameEntity,
+            ));
+        }
+    }
+}
+
+/// Puffs exhaust out behind the ship while it thrusts, more of it the harder it thrusts.
+#[derive(Component)]
+pub struct ThrustEmitter {
+    //particles per second at full thrust
+    pub rate: f32,
+    //how far behind the center the exhaust comes out
+    pub offset: f32,
+    pending: f32,
+}
+
+impl Default for ThrustEmitter {
+    fn default() -> Self {
+        Self {
+            rate: 60.0,
+            offset: 25.0,
+            pending: 0.0,
+        }
+    }
+}
+
+fn emit_thrust(
+    time: Res<Time>,
+    mut input_event_reader: EventReader<InputAction>,
+    mut emitters: Query<(&Transform, &LinearVelocity, &mut ThrustEmitter), With<Ship>>,
+    mut commands: Commands,
+) {
+    //keyboard and gamepad can both push in the same frame
+    let mut thrust = Vec2::ZERO;
+    for event in input_event_reader.read() {
+        if let InputAction::Move(direction) = event {
+            thrust += *direction;
+        }
+    }
+    let thrust = thrust.clamp_length_max(1.0);
+    if thrust == Vec2::ZERO {
+        return;
+    }
+    let mut rng = rand::thread_rng();
+    let backwards = -thrust.normalize();
+    for (transform, velocity, mut emitter) in emitters.iter_mut() {
+        emitter.pending += emitter.rate * thrust.length() * time.delta_seconds();
+        let origin = transform.translation.xy() + backwards * emitter.offset;
+        while emitter.pending >= 1.0 {
+            emitter.pending -= 1.0;
+            let angle = backwards.y.atan2(backwards.x) + rng.gen_range(-0.3..0.3);
+            let speed = rng.gen_range(80.0..140.0);
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::rgb(1.0, 0.6, 0.2),
+                        custom_size: Some(Vec2::splat(4.0)),
+                        ..default()
+                    },
+                    //behind the ship
+                    transform: Transform::from_translation(origin.extend(-1.0)),
+                    ..default()
+                },
+                RigidBody::Kinematic,
+                LinearVelocity(velocity.0 + Vec2::new(angle.cos(), angle.sin()) * speed),
+                Lifetime(Timer::from_seconds(0.3, TimerMode::Once)),
+                FadeOut(0.8),
+                GameEntity,
+            ));
+        }
+    }
+}
+
+/// Marks the cooldown on the ship's hyperspace jump.
+pub enum Hyperspace {}
+
+/// Hyperspace lands somewhere random, at least `margin` in from the edges, and now and
+/// then the ship doesn't make it.
+#[derive(Resource)]
+pub struct HyperspaceSettings {
+    pub margin: f32,
+    pub self_destruct_chance: f64,
+}
+
+impl Default for HyperspaceSettings {
+    fn default() -> Self {
+        Self {
+            margin: 50.0,
+            self_destruct_chance: 0.05,
+        }
+    }
+}
+
+/// Sent when a ship jumps into hyperspace.
+#[derive(Event)]
+pub struct HyperspaceEvent {
+    pub ship: Entity,
+}
+
+fn trigger_hyperspace(
+    key: Res<Input<KeyCode>>,
+    mut ships: Query<(Entity, &mut Cooldown<Hyperspace>), With<Ship>>,
+    mut hyperspace_writer: EventWriter<HyperspaceEvent>,
+) {
+    if !key.just_pressed(KeyCode::H) {
+        return;
+    }
+    for (ship, mut cooldown) in ships.iter_mut() {
+        if cooldown.trigger() {
+            hyperspace_writer.send(HyperspaceEvent { ship });
+        }
+    }
+}
+
+fn hyperspace(
+    mut events: EventReader<HyperspaceEvent>,
+    settings: Res<HyperspaceSettings>,
+    play_area: Res<PlayArea>,
+    mut ships: Query<(&mut Transform, &mut LinearVelocity, &ShipHealth)>,
+    mut damage_writer: EventWriter<DamageEvent>,
+    mut game_rng: ResMut<GameRng>,
+) {
+    let rng = &mut game_rng.0;
+    for event in events.read() {
+        let Ok((mut transform, mut velocity, health)) = ships.get_mut(event.ship) else {
+            continue;
+        };
+        let destination = play_area.random_point(rng, settings.margin);
+        info!("hyperspace to {}", destination);
+        transform.translation = destination.extend(transform.translation.z);
+        velocity.0 = Vec2::ZERO;
+        if rng.gen_bool(settings.self_destruct_chance) {
+            info!("the ship broke up in hyperspace");
+            //the ship is its own source, which is how the run log tells this death apart
+            damage_writer.send(DamageEvent {
+                target: event.ship,
+                amount: health.0,
+                source: event.ship,
+            });
+        }
+    }
+}
+
+/// The area entities wrap around in, in world units centered on the origin.
+/// When `aspect` is set the area is letterboxed inside the window to that ratio.
+#[derive(Resource)]
+pub struct PlayArea {
+    pub aspect: Option<f32>,
+    pub half_extents: Vec2,
+}
+
+impl Default for PlayArea {
+    fn default() -> Self {
+        Self {
+            aspect: None,
+            half_extents: Vec2::new(640.0, 360.0),
+        }
+    }
+}
+
+impl PlayArea {
+    /// A random point inside the area, at least `margin` in from its edges.
+    pub fn random_point(&self, rng: &mut impl Rng, margin: f32) -> Vec2 {
+        let half = (self.half_extents - Vec2::splat(margin)).max(Vec2::ZERO);
+        Vec2::new(
+            rng.gen_range(-half.x..=half.x),
+            rng.gen_range(-half.y..=half.y),
+        )
+    }
+
+    /// A random point on the edge of the area pushed out by `margin`, spread evenly along
+    /// the perimeter. With `avoid` set it tries to stay at least that far from that point.
+    pub fn random_edge_point(
+        &self,
+        rng: &mut impl Rng,
+        margin: f32,
+        avoid: Option<(Vec2, f32)>,
+    ) -> Vec2 {
+        let half = self.half_extents + Vec2::splat(margin);
+        let corners = [
+            Vec2::new(-half.x, half.y),
+            Vec2::new(half.x, half.y),
+            Vec2::new(half.x, -half.y),
+            Vec2::new(-half.x, -half.y),
+        ];
+        let perimeter = 4.0 * (half.x + half.y);
+        let mut point = corners[0];
+        //a few tries is plenty, the ship can only be close to a small part of the edge
+        for _ in 0..10 {
+            let mut distance = rng.gen_range(0.0..perimeter);
+            for n in 0..4 {
+                let (start, end) = (corners[n], corners[(n + 1) % 4]);
+                let length = start.distance(end);
+                if distance <= length {
+                    point = start + (end - start) / length * distance;
+                    break;
+                }
+                distance -= length;
+            }
+            match avoid {
+                Some((position, min_distance)) if point.distance(position) < min_distance => {}
+                _ => break,
+            }
+        }
+        point
+    }
+
+    /// How close `position` is to each edge, in the order left, right, bottom, top. Each is 0
+    /// at `range` or further from that edge and 1 right on it.
+    pub fn edge_proximity(&self, position: Vec2, range: f32) -> [f32; 4] {
+        let proximity = |distance: f32| (1.0 - distance / range).clamp(0.0, 1.0);
+        [
+            proximity(position.x + self.half_extents.x),
+            proximity(self.half_extents.x - position.x),
+            proximity(position.y + self.half_extents.y),
+            proximity(self.half_extents.y - position.y),
+        ]
+    }
+
+    /// Computes the largest area of the configured aspect that fits in the visible world.
+    pub fn fit(&mut self, visible_size: Vec2) {
+        let size = match self.aspect {
+            Some(aspect) if visible_size.x / visible_size.y > aspect => {
+                Vec2::new(visible_size.y * aspect, visible_size.y)
+            }
+            Some(aspect) => Vec2::new(visible_size.x, visible_size.x / aspect),
+            None => visible_size,
+        };
+        self.half_extents = size / 2.0;
+    }
+}
+
+fn update_play_area(
+    camera_query: Query<&OrthographicProjection, With<MainCamera>>,
+    mut play_area: ResMut<PlayArea>,
+) {
+    //the projection knows how much of the world is visible after scaling
+    let Ok(projection) = camera_query.get_single() else {
+        return;
+    };
+    let visible_size = projection.area.size();
+    if visible_size.x <= 0.0 || visible_size.y <= 0.0 {
+        return;
+    }
+    play_area.fit(visible_size);
+}
+
+fn draw_play_area_border(mut gizmos: Gizmos, play_area: Res<PlayArea>) {
+    //only letterboxed areas get a border, otherwise it would sit on the window edge
+    if play_area.aspect.is_some() {
+        gizmos.rect_2d(
+            Vec2::ZERO,
+            0.0,
+            play_area.half_extents * 2.0,
+            Color::rgba(1.0, 1.0, 1.0, 0.25),
+        );
+    }
+}
+
+/// Glows along the edges of the play area that light up as the ship gets close to them.
+#[derive(Resource)]
+pub struct EdgeIndicatorSettings {
+    //distance from an edge at which its glow starts to show
+    pub range: f32,
+    //how far the glow reaches into the play area
+    pub depth: f32,
+    pub color: Color,
+}
+
+impl Default for EdgeIndicatorSettings {
+    fn default() -> Self {
+        Self {
+            range: 250.0,
+            depth: 12.0,
+            color: Color::rgba(0.4, 0.8, 1.0, 0.6),
+        }
+    }
+}
+
+fn draw_edge_indicators(
+    mut gizmos: Gizmos,
+    settings: Res<EdgeIndicatorSettings>,
+    play_area: Res<PlayArea>,
+    ships: Query<&Transform, With<Ship>>,
+) {
+    let Ok(ship) = ships.get_single() else {
+        return;
+    };
+    let half = play_area.half_extents;
+    let proximity = play_area.edge_proximity(ship.translation.xy(), settings.range);
+    //each edge as a corner, the direction along it and the direction into the area
+    let edges = [
+        (Vec2::new(-half.x, -half.y), Vec2::Y * half.y * 2.0, Vec2::X),
+        (
+            Vec2::new(half.x, -half.y),
+            Vec2::Y * half.y * 2.0,
+            Vec2::NEG_X,
+        ),
+        (Vec2::new(-half.x, -half.y), Vec2::X * half.x * 2.0, Vec2::Y),
+        (
+            Vec2::new(-half.x, half.y),
+            Vec2::X * half.x * 2.0,
+            Vec2::NEG_Y,
+        ),
+    ];
+    for ((start, along, inward), intensity) in edges.into_iter().zip(proximity) {
+        if intensity <= 0.0 {
+            continue;
+        }
+        //a few lines fading out away from the edge stand in for a glow
+        for n in 0..4 {
+            let fade = 1.0 - n as f32 / 4.0;
+            let offset = inward * settings.depth * n as f32 / 4.0;
+            let color = settings.color.with_a(settings.color.a() * intensity * fade);
+            gizmos.line_2d(start + offset, start + along + offset, color);
+        }
+    }
+}
+
+/// How an entity behaves at the edge of the play area. Entities without it wrap hard.
+#[derive(Component, Default, Debug, Clone, Copy)]
+pub enum WrapBehavior {
+    /// Teleport to the opposite edge.
+    #[default]
+    Hard,
+    /// Get pushed back from the edge, harder the further past it the entity is.
+    Soft { strength: f32 },
+}
+
+/// How the ship wraps, toggled between hard and soft with F4.
+#[derive(Resource)]
+pub struct ShipWrapSettings {
+    pub soft: bool,
+    //how hard the soft wall pushes back
+    pub strength: f32,
+}
+
+impl Default for ShipWrapSettings {
+    fn default() -> Self {
+        Self {
+            soft: false,
+            strength: 20.0,
+        }
+    }
+}
+
+impl ShipWrapSettings {
+    pub fn behavior(&self) -> WrapBehavior {
+        if self.soft {
+            WrapBehavior::Soft {
+                strength: self.strength,
+            }
+        } else {
+            WrapBehavior::Hard
+        }
+    }
+}
+
+fn toggle_ship_wrap(
+    key: Res<Input<KeyCode>>,
+    mut settings: ResMut<ShipWrapSettings>,
+    mut ships: Query<&mut WrapBehavior, With<Ship>>,
+) {
+    if key.just_pressed(KeyCode::F4) {
+        settings.soft = !settings.soft;
+        info!("soft wrap: {}", settings.soft);
+        for mut behavior in ships.iter_mut() {
+            *behavior = settings.behavior();
+        }
+    }
+}
+
+fn wrapper(
+    time: Res<Time>,
+    mut wrapped_entities_query: Query<
+        (
+            &mut Transform,
+            Option<&mut IgnoreWrapper>,
+            &Collider,
+            Option<&WrapBehavior>,
+            &mut LinearVelocity,
+        ),
+        Or<(&Ship, &AsteroidClass)>,
+    >,
+    play_area: Res<PlayArea>,
+) {
+    let half = play_area.half_extents;
+    let delta = time.delta_seconds();
+    for (mut transform, ignore_wrapper, collider, behavior, mut velocity) in
+        wrapped_entities_query.iter_mut()
+    {
+        match ignore_wrapper {
+            Some(mut wrapper) => match *wrapper {
+                IgnoreWrapper::False => {}
+                IgnoreWrapper::True => {
+                    if transform.translation.x.abs() < half.x
+                        && transform.translation.y.abs() < half.y
+                    {
+                        *wrapper = IgnoreWrapper::False;
+                    }
+                    match *wrapper {
+                        IgnoreWrapper::False => {}
+                        IgnoreWrapper::True => continue,
+                    }
+                }
+            },
+            None => {}
+        }
+        let huh = collider.shape_scaled();
+        let size = huh.compute_local_bounding_sphere().radius();
+        if let Some(WrapBehavior::Soft { strength }) = behavior {
+            //the wall starts where the entity would touch the edge
+            let limit = half - Vec2::splat(size);
+            let position = transform.translation.xy();
+            let over = position.abs() - limit;
+            for axis in 0..2 {
+                if over[axis] > 0.0 {
+                    let outward = position[axis].signum();
+                    velocity.0[axis] -= outward * strength * over[axis] * delta;
+                    //bleed off speed heading into the wall
+                    if velocity.0[axis] * outward > 0.0 {
+                        velocity.0[axis] *= (1.0 - 5.0 * delta).max(0.0);
+                    }
+                }
+            }
+            continue;
+        }
+        //wrap to the opposite edge once fully off screen, keeping the overshoot
+        let limit = half + Vec2::splat(size);
+        for axis in 0..2 {
+            let span = 2.0 * limit[axis];
+            if transform.translation[axis] > limit[axis] {
+                transform.translation[axis] -= span;
+            } else if transform.translation[axis] < -limit[axis] {
+                transform.translation[axis] += span;
+            }
+        }
+    }
+}
+
+#[derive(Component, Default)]
+pub enum IgnoreWrapper {
+    #[default]
+    False,
+    True,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    /// Lets `delta` of real time pass, as much of it as virtual time lets through reaches the
+    /// gameplay systems.
+    fn advance_real_time(app: &mut App, delta: Duration) {
+        let mut virtual_time = app.world.resource_mut::<Time<Virtual>>();
+        virtual_time.advance_with_raw_delta(delta);
+        let generic = virtual_time.as_generic();
+        *app.world.resource_mut::<Time>() = generic;
+    }
+
+    #[test]
+    fn a_hitch_only_applies_a_capped_amount_of_thrust() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<Time<Virtual>>()
+            .add_event::<InputAction>()
+            .add_systems(Startup, crate::run::limit_frame_delta)
+            .add_systems(Update, movement);
+        let ship = app
+            .world
+            .spawn((
+                Ship,
+                LinearVelocity::default(),
+                MovementAcceleration(1280.0),
+            ))
+            .id();
+        app.update();
+
+        advance_real_time(&mut app, Duration::from_secs(5));
+        app.world.send_event(InputAction::Move(Vec2::X));
+        app.update();
+
+        let velocity = app.world.get::<LinearVelocity>(ship).unwrap().0;
+        assert!(velocity.x > 0.0);
+        //at most 100ms worth of thrust, not 5s of it
+        assert!(velocity.x <= 1280.0 * 0.1 + 0.001);
+    }
+
+    /// How far a ship holding thrust to the right gets in one second at `fps` frames a second.
+    fn distance_in_a_second(fps: f64) -> f32 {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            TransformPlugin,
+            HierarchyPlugin,
+            PhysicsPlugins::default(),
+        ))
+        .insert_resource(bevy::time::TimeUpdateStrategy::ManualDuration(
+            Duration::from_secs_f64(1.0 / fps),
+        ))
+        .insert_resource(Time::new_with(Physics::fixed_hz(crate::PHYSICS_HZ)))
+        .insert_resource(Gravity(Vec2::ZERO))
+        .add_event::<InputAction>()
+        .add_systems(Update, movement);
+        let ship = app
+            .world
+            .spawn((
+                Ship,
+                RigidBody::Dynamic,
+                Collider::ball(20.0),
+                MovementAcceleration(1280.0),
+                TransformBundle::default(),
+            ))
+            .id();
+        while app.world.resource::<Time<Virtual>>().elapsed() < Duration::from_secs(1) {
+            app.world.send_event(InputAction::Move(Vec2::X));
+            app.update();
+        }
+        app.world.get::<Transform>(ship).unwrap().translation.x
+    }
+
+    #[test]
+    fn thrust_covers_the_same_ground_at_any_frame_rate() {
+        let slow = distance_in_a_second(30.0);
+        let fast = distance_in_a_second(120.0);
+        assert!(slow > 0.0);
+        assert!((slow - fast).abs() < slow * 0.05);
+    }
+
+    #[test]
+    fn leaving_the_right_edge_wraps_to_the_left() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<PlayArea>()
+            .add_systems(Update, wrapper);
+        let asteroid = app
+            .world
+            .spawn((
+                AsteroidClass::Medium,
+                Transform::from_xyz(700.0, 0.0, 0.0),
+                Collider::ball(30.0),
+                LinearVelocity::default(),
+            ))
+            .id();
+        app.update();
+
+        let position = app.world.get::<Transform>(asteroid).unwrap().translation;
+        assert!((position.x + 640.0).abs() < 1.0, "wrapped to {}", position);
+        assert_eq!(position.y, 0.0);
+    }
+
+    #[test]
+    fn the_play_area_follows_the_visible_world() {
+        let mut app = App::new();
+        app.init_resource::<PlayArea>()
+            .add_systems(Update, update_play_area);
+        //what a 1920x1080 window shows at scale 1
+        app.world.spawn((
+            MainCamera,
+            OrthographicProjection {
+                area: Rect::new(-960.0, -540.0, 960.0, 540.0),
+                ..default()
+            },
+        ));
+        app.update();
+
+        assert_eq!(
+            app.world.resource::<PlayArea>().half_extents,
+            Vec2::new(960.0, 540.0)
+        );
+    }
+
+    #[test]
+    fn edge_points_sit_on_the_perimeter_away_from_the_ship() {
+        let play_area = PlayArea::default();
+        let mut rng = GameRng::seeded(3).0;
+        let margin = 60.0;
+        let half = play_area.half_extents + Vec2::splat(margin);
+        //hugging the right edge, where a careless spawn would land on it
+        let ship = Vec2::new(600.0, 0.0);
+        for _ in 0..50 {
+            let point = play_area.random_edge_point(&mut rng, margin, Some((ship, 250.0)));
+            let off_x = (point.x.abs() - half.x).abs();
+            let off_y = (point.y.abs() - half.y).abs();
+            assert!(off_x.min(off_y) < 0.01, "{} is not on the edge", point);
+            assert!(point.abs().cmple(half + 0.01).all());
+            assert!(point.distance(ship) >= 250.0, "{} is too close", point);
+        }
+    }
+
+    #[test]
+    fn a_second_dash_waits_for_the_cooldown() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .init_resource::<DashSettings>()
+            .add_event::<InputAction>()
+            .add_event::<DashEvent>()
+            .add_systems(Update, (tick_cooldowns::<Dash>, dash).chain());
+        let ship = app
+            .world
+            .spawn((
+                Ship,
+                Transform::default(),
+                LinearVelocity::default(),
+                Cooldown::<Dash>::new(Duration::from_secs(2)),
+            ))
+            .id();
+        let dash_after = |app: &mut App, millis| {
+            app.world
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_millis(millis));
+            app.world.send_event(InputAction::Dash);
+            app.update();
+            app.world.get::<LinearVelocity>(ship).unwrap().0
+        };
+
+        //standing still it dashes the way the ship faces
+        let impulse = DashSettings::default().impulse;
+        assert_eq!(dash_after(&mut app, 0), Vec2::Y * impulse);
+        assert!(app.world.get::<Invulnerable>(ship).is_some());
+        assert_eq!(dash_after(&mut app, 500), Vec2::Y * impulse);
+    }
+
+    #[test]
+    fn hyperspace_lands_somewhere_else_inside_the_area() {
+        let mut app = App::new();
+        app.insert_resource(HyperspaceSettings {
+            self_destruct_chance: 0.0,
+            ..default()
+        })
+        .init_resource::<PlayArea>()
+        .insert_resource(GameRng::seeded(5))
+        .add_event::<HyperspaceEvent>()
+        .add_event::<DamageEvent>()
+        .add_systems(Update, hyperspace);
+        let start = Vec3::new(100.0, -50.0, 0.0);
+        let ship = app
+            .world
+            .spawn((
+                Ship,
+                Transform::from_translation(start),
+                LinearVelocity(Vec2::new(200.0, 0.0)),
+                ShipHealth(SHIP_HEALTH),
+            ))
+            .id();
+        app.world.send_event(HyperspaceEvent { ship });
+        app.update();
+
+        let position = app.world.get::<Transform>(ship).unwrap().translation;
+        assert_ne!(position, start);
+        let inner = PlayArea::default().half_extents - HyperspaceSettings::default().margin;
+        assert!(position.xy().abs().cmple(inner).all(), "{}", position);
+        assert_eq!(app.world.get::<LinearVelocity>(ship).unwrap().0, Vec2::ZERO);
+    }
+
+    #[test]
+    fn a_ship_breaking_up_in_hyperspace_deals_itself_its_health() {
+        let mut app = App::new();
+        app.insert_resource(HyperspaceSettings {
+            self_destruct_chance: 1.0,
+            ..default()
+        })
+        .init_resource::<PlayArea>()
+        .insert_resource(GameRng::seeded(5))
+        .add_event::<HyperspaceEvent>()
+        .add_event::<DamageEvent>()
+        .add_systems(Update, hyperspace);
+        let ship = app
+            .world
+            .spawn((
+                Ship,
+                Transform::default(),
+                LinearVelocity::default(),
+                ShipHealth(2),
+            ))
+            .id();
+        app.world.send_event(HyperspaceEvent { ship });
+        app.update();
+
+        let damage: Vec<(Entity, i32, Entity)> = app
+            .world
+            .resource_mut::<Events<DamageEvent>>()
+            .drain()
+            .map(|event| (event.target, event.amount, event.source))
+            .collect();
+        assert_eq!(damage, vec![(ship, 2, ship)]);
+        //taking it off is left to apply_damage
+        assert_eq!(app.world.get::<ShipHealth>(ship).unwrap().0, 2);
+    }
+
+    #[test]
+    fn the_right_edge_glows_brighter_with_the_ship_beside_it() {
+        let range = EdgeIndicatorSettings::default().range;
+        let [left, right, bottom, top] =
+            PlayArea::default().edge_proximity(Vec2::new(600.0, 0.0), range);
+        assert!(right > left);
+        assert!((right - (1.0 - 40.0 / range)).abs() < 0.001);
+        //too far from everything else to show at all
+        assert_eq!([left, bottom, top], [0.0; 3]);
+    }
+
+    #[test]
+    fn thrusting_puffs_exhaust() {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_event::<InputAction>()
+            .add_systems(Update, emit_thrust);
+        app.world.spawn((
+            Ship,
+            Transform::default(),
+            LinearVelocity::default(),
+            ThrustEmitter::default(),
+        ));
+        app.world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_millis(100));
+        app.world.send_event(InputAction::Move(Vec2::Y));
+        app.update();
+
+        let mut exhaust = app.world.query_filtered::<(), With<Lifetime>>();
+        assert!(exhaust.iter(&app.world).count() > 0);
+    }
+}
@@ -0,0 +1,182 @@
+use bevy::prelude::*;
+use bevy_xpbd_2d::prelude::*;
+
+use crate::{
+    asteroids::{AsteroidClass, SpawnRamp},
+    run::GameState,
+    ship::Lives,
+    weapons::LaserPiercing,
+};
+
+/// Optional rule changes picked during the intro, worth more points for each one in play.
+pub struct MutatorPlugin;
+
+impl Plugin for MutatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Mutators>()
+            .add_systems(Update, select_mutators.run_if(in_state(GameState::Intro)))
+            //pausing leaves and re-enters Playing, so mutators hook onto the run's ends
+            .add_systems(OnExit(GameState::Intro), apply_mutators)
+            .add_systems(OnEnter(GameState::GameOver), clear_mutators)
+            .add_systems(OnEnter(GameState::Menu), clear_mutators);
+    }
+}
+
+/// An optional rule change for a single run, picked during the intro.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mutator {
+    //asteroids move twice as fast
+    FastAsteroids,
+    //the run has a single life, no respawns
+    OneLife,
+    //every laser goes through one more asteroid, charged or not
+    LasersPierce,
+}
+
+/// The mutators picked for the next run and the ones in effect for the current one.
+#[derive(Resource, Default)]
+pub struct Mutators {
+    selected: Vec<Mutator>,
+    active: Vec<Mutator>,
+}
+
+impl Mutators {
+    pub fn toggle(&mut self, mutator: Mutator) {
+        match self.selected.iter().position(|m| *m == mutator) {
+            Some(index) => {
+                self.selected.remove(index);
+            }
+            None => self.selected.push(mutator),
+        }
+    }
+
+    /// Puts the selected mutators into effect for the run that is starting.
+    pub fn start_run(&mut self) {
+        self.active = self.selected.clone();
+    }
+
+    pub fn active(&self) -> &[Mutator] {
+        &self.active
+    }
+
+    pub fn is_active(&self, mutator: Mutator) -> bool {
+        self.active.contains(&mutator)
+    }
+
+    /// Every active mutator adds the base value of a kill on top again.
+    pub fn score_multiplier(&self) -> u32 {
+        1 + self.active.len() as u32
+    }
+}
+
+fn select_mutators(key: Res<Input<KeyCode>>, mut mutators: ResMut<Mutators>) {
+    let mutator = if key.just_pressed(KeyCode::F9) {
+        Mutator::FastAsteroids
+    } else if key.just_pressed(KeyCode::F10) {
+        Mutator::OneLife
+    } else if key.just_pressed(KeyCode::F12) {
+        Mutator::LasersPierce
+    } else {
+        return;
+    };
+    mutators.toggle(mutator);
+    info!("selected mutators: {:?}", mutators.selected);
+}
+
+fn apply_mutators(
+    mut mutators: ResMut<Mutators>,
+    mut asteroids: Query<(&mut LinearVelocity, Option<&mut SpawnRamp>), With<AsteroidClass>>,
+    mut lives: ResMut<Lives>,
+    mut laser_piercing: ResMut<LaserPiercing>,
+) {
+    mutators.start_run();
+    if mutators.is_active(Mutator::FastAsteroids) {
+        //the intro asteroids are already out there, speed them up too
+        for (mut velocity, ramp) in asteroids.iter_mut() {
+            velocity.0 *= 2.0;
+            if let Some(mut ramp) = ramp {
+                ramp.velocity *= 2.0;
+            }
+        }
+    }
+    if mutators.is_active(Mutator::OneLife) {
+        lives.0 = 1;
+    }
+    laser_piercing.0 = if mutators.is_active(Mutator::LasersPierce) {
+        1
+    } else {
+        0
+    };
+    info!(
+        "active mutators: {:?}, score x{}",
+        mutators.active,
+        mutators.score_multiplier()
+    );
+}
+
+fn clear_mutators(mut mutators: ResMut<Mutators>, mut laser_piercing: ResMut<LaserPiercing>) {
+    mutators.active.clear();
+    //otherwise lasers fired during the next intro would still pierce
+    laser_piercing.0 = 0;
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::ship::{ShipHealth, SHIP_HEALTH};
+
+    #[test]
+    fn one_life_leaves_a_single_life_and_full_health() {
+        let mut app = App::new();
+        app.init_resource::<Lives>()
+            .init_resource::<Mutators>()
+            .init_resource::<LaserPiercing>()
+            .add_systems(Update, apply_mutators);
+        app.world
+            .resource_mut::<Mutators>()
+            .toggle(Mutator::OneLife);
+        let ship = app.world.spawn(ShipHealth(SHIP_HEALTH)).id();
+        app.update();
+
+        assert_eq!(app.world.resource::<Lives>().0, 1);
+        assert_eq!(app.world.get::<ShipHealth>(ship).unwrap().0, SHIP_HEALTH);
+    }
+
+    #[test]
+    fn lasers_pierce_for_the_run_it_was_picked_for() {
+        let mut app = App::new();
+        app.init_resource::<Lives>()
+            .init_resource::<Mutators>()
+            .init_resource::<LaserPiercing>()
+            .add_systems(Update, apply_mutators);
+        app.world
+            .resource_mut::<Mutators>()
+            .toggle(Mutator::LasersPierce);
+        app.update();
+
+        assert_eq!(app.world.resource::<LaserPiercing>().0, 1);
+        let mutators = app.world.resource::<Mutators>();
+        assert!(mutators.is_active(Mutator::LasersPierce));
+        assert_eq!(mutators.score_multiplier(), 2);
+
+        app.world.run_system_once(clear_mutators);
+        assert_eq!(app.world.resource::<LaserPiercing>().0, 0);
+        assert_eq!(app.world.resource::<Mutators>().score_multiplier(), 1);
+    }
+
+    #[test]
+    fn every_mutator_raises_the_score_multiplier() {
+        let mut mutators = Mutators::default();
+        for mutator in [
+            Mutator::FastAsteroids,
+            Mutator::OneLife,
+            Mutator::LasersPierce,
+        ] {
+            mutators.toggle(mutator);
+        }
+        mutators.start_run();
+        assert_eq!(mutators.score_multiplier(), 4);
+    }
+}
@@ -0,0 +1,375 @@
+use std::{f32::consts::PI, time::Duration};
+
+use bevy::prelude::*;
+use bevy_xpbd_2d::prelude::*;
+use rand::Rng;
+
+use crate::{
+    lifetime::Lifetime,
+    movement::PlayArea,
+    run::{GameEntity, GameState},
+    shield::Shield,
+    ship::{Lives, Ship},
+    weapons::{Bombs, RateOfFire, Weapon, WeaponKind},
+};
+
+/// Pickups that destroyed asteroids sometimes leave behind, collected by flying close to them.
+pub struct PowerUpPlugin;
+
+impl Plugin for PowerUpPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PowerUpSettings>()
+            .add_event::<PowerUpCollectedEvent>()
+            .add_systems(
+                Update,
+                collect_power_ups
+                    .before(apply_power_ups)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(Update, apply_power_ups)
+            .add_systems(Update, (expire_rapid_fire, expire_magnet));
+    }
+}
+
+#[derive(Resource)]
+pub struct PowerUpSettings {
+    //chance for each destroyed asteroid to drop something
+    pub drop_chance: f64,
+    pub drift_speed: f32,
+    //how long a power-up floats around before it is gone
+    pub lifetime: Duration,
+    pub rapid_fire_duration: Duration,
+    pub max_lives: u8,
+    pub max_bombs: u8,
+    //how close to the ship's center a power-up gets picked up
+    pub collect_radius: f32,
+    //the collect radius while a magnet is active
+    pub magnet_radius: f32,
+    pub magnet_duration: Duration,
+}
+
+impl Default for PowerUpSettings {
+    fn default() -> Self {
+        Self {
+            drop_chance: 0.1,
+            drift_speed: 20.0,
+            lifetime: Duration::from_secs(10),
+            rapid_fire_duration: Duration::from_secs(10),
+            max_lives: 9,
+            max_bombs: 5,
+            collect_radius: 60.0,
+            magnet_radius: 180.0,
+            magnet_duration: Duration::from_secs(15),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum PowerUpKind {
+    RapidFire,
+    Shield,
+    ExtraLife,
+    SpreadShot,
+    Bomb,
+    //widens the collect radius for a while
+    Magnet,
+}
+
+impl PowerUpKind {
+    const ALL: [PowerUpKind; 6] = [
+        PowerUpKind::RapidFire,
+        PowerUpKind::Shield,
+        PowerUpKind::ExtraLife,
+        PowerUpKind::SpreadShot,
+        PowerUpKind::Bomb,
+        PowerUpKind::Magnet,
+    ];
+
+    fn color(&self) -> Color {
+        match self {
+            PowerUpKind::RapidFire => Color::rgb(1.0, 0.8, 0.2),
+            PowerUpKind::Shield => Color::rgb(0.3, 0.7, 1.0),
+            PowerUpKind::ExtraLife => Color::rgb(0.3, 1.0, 0.4),
+            PowerUpKind::SpreadShot => Color::rgb(1.0, 0.4, 0.9),
+            PowerUpKind::Bomb => Color::rgb(1.0, 0.3, 0.2),
+            PowerUpKind::Magnet => Color::rgb(0.8, 0.8, 0.85),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct PowerUp {
+    pub kind: PowerUpKind,
+}
+
+/// Sent when the ship picks up a power-up.
+#[derive(Event)]
+pub struct PowerUpCollectedEvent {
+    pub kind: PowerUpKind,
+    pub ship: Entity,
+}
+
+/// Doubled rate of fire on the primary weapon from a [`PowerUpKind::RapidFire`], undone when
+/// the timer runs out.
+#[derive(Component)]
+pub struct RapidFireBoost(Timer);
+
+/// The ship collects power-ups from `magnet_radius` away while the timer runs.
+#[derive(Component)]
+pub struct MagnetBoost(Timer);
+
+/// Rolls for a drop where an asteroid was destroyed and spawns a random power-up on success.
+pub fn maybe_drop_power_up(
+    commands: &mut Commands,
+    rng: &mut impl Rng,
+    settings: &PowerUpSettings,
+    position: Vec2,
+) {
+    if !rng.gen_bool(settings.drop_chance) {
+        return;
+    }
+    let kind = PowerUpKind::ALL[rng.gen_range(0..PowerUpKind::ALL.len())];
+    let angle = rng.gen_range(0.0..(2.0 * PI));
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: kind.color(),
+                custom_size: Some(Vec2::splat(20.0)),
+                ..default()
+            },
+            transform: Transform::from_translation(position.extend(0.5)),
+            ..default()
+        },
+        //picked up by distance in collect_power_ups, nothing collides with it
+        RigidBody::Kinematic,
+        LinearVelocity(Vec2::new(angle.cos(), angle.sin()) * settings.drift_speed),
+        AngularVelocity(1.0),
+        Lifetime(Timer::new(settings.lifetime, TimerMode::Once)),
+        PowerUp { kind },
+        GameEntity,
+    ));
+}
+
+fn collect_power_ups(
+    settings: Res<PowerUpSettings>,
+    play_area: Res<PlayArea>,
+    ships: Query<(Entity, &Transform, Option<&MagnetBoost>), With<Ship>>,
+    power_ups: Query<(Entity, &Transform, &PowerUp)>,
+    mut collected_writer: EventWriter<PowerUpCollectedEvent>,
+    mut commands: Commands,
+) {
+    let Ok((ship, ship_transform, magnet)) = ships.get_single() else {
+        return;
+    };
+    let radius = if magnet.is_some() {
+        settings.magnet_radius
+    } else {
+        settings.collect_radius
+    };
+    let center = ship_transform.translation.xy();
+    for (entity, transform, power_up) in power_ups.iter() {
+        let position = transform.translation.xy();
+        //straight line distance only, never across the edge: one drifting out past it is
+        //behind the wall of a bounded area, not next to the ship on the other side
+        if position.abs().cmpgt(play_area.half_extents).any() || position.distance(center) > radius
+        {
+            continue;
+        }
+        commands.entity(entity).despawn_recursive();
+        collected_writer.send(PowerUpCollectedEvent {
+            kind: power_up.kind,
+            ship,
+        });
+    }
+}
+
+fn apply_power_ups(
+    mut events: EventReader<PowerUpCollectedEvent>,
+    settings: Res<PowerUpSettings>,
+    mut ships: Query<&mut Shield>,
+    mut weapons: Query<(
+        Entity,
+        &Parent,
+        &Weapon,
+        &mut RateOfFire,
+        &mut WeaponKind,
+        Option<&mut RapidFireBoost>,
+    )>,
+    mut lives: ResMut<Lives>,
+    mut bombs: ResMut<Bombs>,
+    mut commands: Commands,
+) {
+    for event in events.read() {
+        info!("picked up {:?}", event.kind);
+        let Ok(mut shield) = ships.get_mut(event.ship) else {
+            continue;
+        };
+        //weapon pickups go to the ship's primary weapon
+        let Some((weapon, _, _, mut rate_of_fire, mut weapon_kind, boost)) = weapons
+            .iter_mut()
+            .find(|(_, parent, slot, ..)| parent.get() == event.ship && **slot == Weapon::Primary)
+        else {
+            continue;
+        };
+        match event.kind {
+            PowerUpKind::RapidFire => match boost {
+                //picking up another one only extends the boost
+                Some(mut boost) => boost.0.reset(),
+                None => {
+                    rate_of_fire.0 *= 2.0;
+                    commands.entity(weapon).insert(RapidFireBoost(Timer::new(
+                        settings.rapid_fire_duration,
+                        TimerMode::Once,
+                    )));
+                }
+            },
+            PowerUpKind::Shield => {
+                shield.active = true;
+            }
+            PowerUpKind::ExtraLife => {
+                lives.0 = (lives.0 + 1).min(settings.max_lives);
+            }
+            PowerUpKind::SpreadShot => {
+                *weapon_kind = WeaponKind::Spread;
+            }
+            PowerUpKind::Bomb => {
+                bombs.0 = (bombs.0 + 1).min(settings.max_bombs);
+            }
+            PowerUpKind::Magnet => {
+                //another one restarts the timer
+                commands.entity(event.ship).insert(MagnetBoost(Timer::new(
+                    settings.magnet_duration,
+                    TimerMode::Once,
+                )));
+            }
+        }
+    }
+}
+
+fn expire_rapid_fire(
+    time: Res<Time>,
+    mut ships: Query<(Entity, &mut RateOfFire, &mut RapidFireBoost)>,
+    mut commands: Commands,
+) {
+    for (entity, mut rate_of_fire, mut boost) in ships.iter_mut() {
+        if boost.0.tick(time.delta()).just_finished() {
+            rate_of_fire.0 /= 2.0;
+            commands.entity(entity).remove::<RapidFireBoost>();
+        }
+    }
+}
+
+fn expire_magnet(
+    time: Res<Time>,
+    mut ships: Query<(Entity, &mut MagnetBoost)>,
+    mut commands: Commands,
+) {
+    for (entity, mut boost) in ships.iter_mut() {
+        if boost.0.tick(time.delta()).just_finished() {
+            commands.entity(entity).remove::<MagnetBoost>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::event::ManualEventReader;
+
+    use super::*;
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.init_resource::<PowerUpSettings>()
+            .init_resource::<PlayArea>()
+            .add_event::<PowerUpCollectedEvent>()
+            .add_systems(Update, collect_power_ups);
+        app
+    }
+
+    fn power_up_at(app: &mut App, position: Vec2) -> Entity {
+        app.world
+            .spawn((
+                Transform::from_translation(position.extend(0.0)),
+                PowerUp {
+                    kind: PowerUpKind::Bomb,
+                },
+            ))
+            .id()
+    }
+
+    fn collected(app: &App) -> usize {
+        let events = app.world.resource::<Events<PowerUpCollectedEvent>>();
+        ManualEventReader::<PowerUpCollectedEvent>::default()
+            .read(events)
+            .count()
+    }
+
+    #[test]
+    fn power_ups_inside_the_radius_are_collected() {
+        let mut app = app();
+        app.world.spawn((Ship, Transform::default()));
+        let near = power_up_at(&mut app, Vec2::new(50.0, 0.0));
+        let far = power_up_at(&mut app, Vec2::new(120.0, 0.0));
+        app.update();
+
+        assert_eq!(collected(&app), 1);
+        assert!(app.world.get_entity(near).is_none());
+        assert!(app.world.get_entity(far).is_some());
+    }
+
+    #[test]
+    fn rapid_fire_shortens_the_time_between_shots() {
+        let mut app = app();
+        app.init_resource::<Lives>()
+            .init_resource::<Bombs>()
+            .add_systems(Update, apply_power_ups.after(collect_power_ups));
+        let ship = app
+            .world
+            .spawn((Ship, Transform::default(), Shield::default()))
+            .id();
+        let weapon = app
+            .world
+            .spawn((Weapon::Primary, RateOfFire(120.0), WeaponKind::Single))
+            .id();
+        app.world.entity_mut(ship).add_child(weapon);
+        app.world.spawn((
+            Transform::from_xyz(20.0, 0.0, 0.0),
+            PowerUp {
+                kind: PowerUpKind::RapidFire,
+            },
+        ));
+        let before = RateOfFire(120.0).interval();
+        app.update();
+
+        let rate_of_fire = app.world.get::<RateOfFire>(weapon).unwrap();
+        assert!(rate_of_fire.interval() < before);
+        assert!(app.world.get::<RapidFireBoost>(weapon).is_some());
+    }
+
+    #[test]
+    fn a_magnet_widens_the_radius() {
+        let mut app = app();
+        app.world.spawn((
+            Ship,
+            Transform::default(),
+            MagnetBoost(Timer::from_seconds(10.0, TimerMode::Once)),
+        ));
+        power_up_at(&mut app, Vec2::new(120.0, 0.0));
+        app.update();
+        assert_eq!(collected(&app), 1);
+    }
+
+    #[test]
+    fn nothing_is_collected_from_beyond_the_edge() {
+        let mut app = app();
+        let edge = PlayArea::default().half_extents.x;
+        app.world.spawn((
+            Ship,
+            Transform::from_xyz(edge - 10.0, 0.0, 0.0),
+            MagnetBoost(Timer::from_seconds(10.0, TimerMode::Once)),
+        ));
+        power_up_at(&mut app, Vec2::new(edge + 20.0, 0.0));
+        app.update();
+        assert_eq!(collected(&app), 0);
+    }
+}
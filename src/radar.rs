@@ -0,0 +1,178 @@
+use bevy::prelude::*;
+
+use crate::{asteroids::AsteroidClass, run::GameState, ship::Ship};
+
+/// A small radar in the corner showing where the asteroids are around the ship, including the
+/// ones just off screen.
+pub struct RadarPlugin;
+
+impl Plugin for RadarPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RadarSettings>()
+            .add_systems(OnEnter(GameState::Playing), spawn_radar)
+            .add_systems(OnExit(GameState::Playing), despawn_radar)
+            .add_systems(Update, update_radar.run_if(in_state(GameState::Playing)));
+    }
+}
+
+#[derive(Resource)]
+pub struct RadarSettings {
+    //world distance from the ship to the edge of the radar
+    pub range: f32,
+    //on screen size of the radar, in pixels
+    pub size: f32,
+    pub blip_size: f32,
+}
+
+impl Default for RadarSettings {
+    fn default() -> Self {
+        Self {
+            range: 1200.0,
+            size: 140.0,
+            blip_size: 4.0,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct RadarRoot;
+
+/// One dot on the radar. They are reused from frame to frame and hidden when not needed.
+#[derive(Component)]
+struct RadarBlip;
+
+const BLIP_COLOR: Color = Color::rgb(0.6, 1.0, 0.6);
+//asteroids out of range sit on the rim in a different color, pointing the way they are
+const EDGE_BLIP_COLOR: Color = Color::rgb(1.0, 0.6, 0.2);
+
+fn spawn_radar(settings: Res<RadarSettings>, mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(10.0),
+                    right: Val::Px(10.0),
+                    width: Val::Px(settings.size),
+                    height: Val::Px(settings.size),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.4).into(),
+                ..default()
+            },
+            RadarRoot,
+        ))
+        .with_children(|parent| {
+            //the ship sits in the middle
+            parent.spawn(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px((settings.size - settings.blip_size) / 2.0),
+                    top: Val::Px((settings.size - settings.blip_size) / 2.0),
+                    width: Val::Px(settings.blip_size),
+                    height: Val::Px(settings.blip_size),
+                    ..default()
+                },
+                background_color: Color::WHITE.into(),
+                ..default()
+            });
+        });
+}
+
+fn despawn_radar(mut commands: Commands, roots: Query<Entity, With<RadarRoot>>) {
+    for entity in roots.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Where a blip for something `offset` away from the ship goes, relative to the radar's center
+/// with up being positive y. Returns whether it had to be clamped to the rim.
+pub fn radar_position(offset: Vec2, settings: &RadarSettings) -> (Vec2, bool) {
+    let radius = settings.size / 2.0;
+    if offset.length() > settings.range {
+        (offset.normalize_or_zero() * radius, true)
+    } else {
+        (offset / settings.range * radius, false)
+    }
+}
+
+fn update_radar(
+    settings: Res<RadarSettings>,
+    ships: Query<&Transform, With<Ship>>,
+    asteroids: Query<&Transform, With<AsteroidClass>>,
+    roots: Query<Entity, With<RadarRoot>>,
+    mut blips: Query<(&mut Style, &mut BackgroundColor, &mut Visibility), With<RadarBlip>>,
+    mut commands: Commands,
+) {
+    let (Ok(ship), Ok(root)) = (ships.get_single(), roots.get_single()) else {
+        return;
+    };
+    let radius = settings.size / 2.0;
+    let mut positions = asteroids.iter().map(|asteroid| {
+        radar_position(asteroid.translation.xy() - ship.translation.xy(), &settings)
+    });
+    for (mut style, mut color, mut visibility) in blips.iter_mut() {
+        match positions.next() {
+            Some((position, clamped)) => {
+                //ui positions grow downwards
+                style.left = Val::Px(radius + position.x - settings.blip_size / 2.0);
+                style.top = Val::Px(radius - position.y - settings.blip_size / 2.0);
+                *color = if clamped { EDGE_BLIP_COLOR } else { BLIP_COLOR }.into();
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+    //more asteroids than blips, the new ones show up from the next frame on
+    for _ in positions {
+        let blip = commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        width: Val::Px(settings.blip_size),
+                        height: Val::Px(settings.blip_size),
+                        ..default()
+                    },
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                RadarBlip,
+            ))
+            .id();
+        commands.entity(root).add_child(blip);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn an_asteroid_to_the_right_shows_on_the_right() {
+        let mut app = App::new();
+        app.init_resource::<RadarSettings>()
+            .add_systems(Startup, spawn_radar)
+            .add_systems(Update, update_radar);
+        app.world
+            .spawn((Ship, Transform::from_xyz(100.0, 50.0, 0.0)));
+        app.world
+            .spawn((AsteroidClass::Big, Transform::from_xyz(400.0, 50.0, 0.0)));
+        //the blip is made on the first frame and placed on the second
+        app.update();
+        app.update();
+
+        let settings = RadarSettings::default();
+        let center = (settings.size - settings.blip_size) / 2.0;
+        let mut blips = app
+            .world
+            .query_filtered::<(&Style, &Visibility), With<RadarBlip>>();
+        let (style, visibility) = blips.single(&app.world);
+        assert_eq!(*visibility, Visibility::Inherited);
+        let (Val::Px(left), Val::Px(top)) = (style.left, style.top) else {
+            panic!("blips are placed in pixels");
+        };
+        assert!(left > center);
+        assert!((top - center).abs() < 0.001);
+    }
+}
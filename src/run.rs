@@ -0,0 +1,466 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, render::camera::ScalingMode};
+use bevy_xpbd_2d::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+use serde::Serialize;
+
+use crate::{
+    asteroids::{AsteroidSpawnQueue, SpawnAsteroidEvent, WaveManager},
+    camera_shake::CameraShake,
+    cooldown::Cooldown,
+    display::VIEW_HEIGHT,
+    ghost::{GhostRecorder, GhostShip},
+    input::{update_mouse_position_system, LookAtMouse, Mouse},
+    movement::{Dash, Hyperspace, PlayArea, ShipControllerBundle, ShipWrapSettings, ThrustEmitter},
+    run_log::RunLog,
+    score::{Combo, KillCount, Score},
+    settings::SettingsState,
+    shield::Shield,
+    ship::{CollisionDamage, EmergencyWarp, Lives, Ship, DAMAGE_COOLDOWN},
+    weapons::{Bomb, Bombs, LaserWeaponBundle, RateOfFire, Weapon, WeaponKind},
+};
+
+/// The run itself: the game states, starting and restarting a run, the intro, pausing and the
+/// seeded randomness every run draws from.
+pub struct GamePlugin;
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, (setup, limit_frame_delta))
+            .add_state::<GameState>()
+            .configure_sets(
+                Update,
+                (
+                    GameRngSet::Intro,
+                    GameRngSet::Ufos,
+                    GameRngSet::Hyperspace,
+                    GameRngSet::Director,
+                    GameRngSet::Breakup,
+                    GameRngSet::Threat,
+                    GameRngSet::Upgrades,
+                    GameRngSet::Waves,
+                    GameRngSet::Spawn,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                Update,
+                run_intro
+                    .in_set(GameRngSet::Intro)
+                    .run_if(in_state(GameState::Intro)),
+            )
+            .init_resource::<Difficulty>()
+            //escape closes the settings screen before it gets to unpause
+            .add_systems(Update, toggle_pause.run_if(in_state(SettingsState::Closed)))
+            .add_systems(OnEnter(GameState::Paused), pause_time)
+            .add_systems(OnExit(GameState::Paused), resume_time)
+            .add_systems(Update, restart_game.run_if(in_state(GameState::GameOver)))
+            .add_systems(
+                PreUpdate,
+                handle_start_event.before(update_mouse_position_system),
+            )
+            .add_event::<StartGameEvent>()
+            .init_resource::<GameSeed>()
+            .init_resource::<GameRng>();
+    }
+}
+
+/// Used to help identify our main camera
+#[derive(Component)]
+pub struct MainCamera;
+
+fn setup(mut commands: Commands) {
+    //the camera outlives each run, the menu needs it before any run has started
+    //always show the same world height so sprites never stretch with the canvas
+    let mut camera = Camera2dBundle::default();
+    camera.projection.scaling_mode = ScalingMode::FixedVertical(VIEW_HEIGHT);
+    commands.spawn((camera, MainCamera, CameraShake::default()));
+}
+
+/// Caps how much time a single frame can advance, so a hitch (alt-tab, asset loads)
+/// can't apply seconds of thrust at once or launch things through colliders.
+pub fn limit_frame_delta(mut time: ResMut<Time<Virtual>>) {
+    time.set_max_delta(Duration::from_millis(100));
+}
+
+pub fn handle_start_event(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut event_reader: EventReader<StartGameEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+    ship_wrap: Res<ShipWrapSettings>,
+    mut ghost: ResMut<GhostRecorder>,
+    difficulty: Res<Difficulty>,
+    seed: Res<GameSeed>,
+    mut run_log: ResMut<RunLog>,
+) {
+    for _event in event_reader.read() {
+        //a fresh seed each run unless one was picked, logged so the run can be played again
+        let seed = seed.0.unwrap_or_else(rand::random);
+        info!("rng seed: {}", seed);
+        commands.insert_resource(GameRng::seeded(seed));
+        run_log.start(seed, *difficulty);
+        //spawn mouse sprite
+        commands.spawn((
+            SpriteBundle {
+                texture: asset_server.load("crosshair/crossair_white.png"),
+                ..Default::default()
+            },
+            Mouse,
+            GameEntity,
+        ));
+        //spawn ship entity
+        commands
+            .spawn((
+                SpriteBundle {
+                    texture: asset_server.load("playerShip1_orange.png"),
+                    transform: Transform {
+                        scale: Vec3::splat(0.5),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                Ship,
+                LookAtMouse,
+                ShipControllerBundle::default(),
+                ThrustEmitter::default(),
+                Cooldown::<Dash>::new(Duration::from_secs(2)),
+                Cooldown::<Bomb>::new(Duration::from_secs(1)),
+                Shield::default(),
+                Cooldown::<CollisionDamage>::new(DAMAGE_COOLDOWN),
+                Cooldown::<EmergencyWarp>::new(Duration::from_secs(30)),
+                Cooldown::<Hyperspace>::new(Duration::from_secs(5)),
+                ship_wrap.behavior(),
+                GameEntity,
+            ))
+            .with_children(|ship| {
+                ship.spawn(LaserWeaponBundle::default());
+                //a missile launcher firing alongside the main gun on its own slow schedule
+                ship.spawn(LaserWeaponBundle {
+                    weapon: Weapon::Secondary,
+                    kind: WeaponKind::Missile,
+                    rate_of_fire: RateOfFire(60.0),
+                    ..default()
+                });
+            });
+        //a faint copy of the ship that replays the best run so far
+        commands.spawn((
+            SpriteBundle {
+                texture: asset_server.load("playerShip1_orange.png"),
+                sprite: Sprite {
+                    color: Color::rgba(1.0, 1.0, 1.0, 0.3),
+                    ..default()
+                },
+                transform: Transform::from_scale(Vec3::splat(0.5)),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            GhostShip,
+            GameEntity,
+        ));
+        ghost.start_run();
+        commands.insert_resource(Score::default());
+        commands.insert_resource(KillCount::default());
+        commands.insert_resource(Combo::default());
+        commands.insert_resource(Lives(Lives::default().0 + difficulty.extra_lives()));
+        commands.insert_resource(Bombs::default());
+        commands.insert_resource(WaveManager::default());
+        //the opening asteroids are brought in by the intro
+        commands.insert_resource(Intro::default());
+        next_state.set(GameState::Intro);
+    }
+}
+
+#[derive(States, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum GameState {
+    //where the app boots, pressing Play sends the start event that moves on to the intro
+    #[default]
+    Menu,
+    Intro,
+    Playing,
+    Paused,
+    GameOver,
+}
+
+fn toggle_pause(
+    key: Res<Input<KeyCode>>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if key.just_pressed(KeyCode::Escape) {
+        match state.get() {
+            GameState::Playing => next_state.set(GameState::Paused),
+            GameState::Paused => next_state.set(GameState::Playing),
+            _ => {}
+        }
+    }
+}
+
+fn pause_time(mut virtual_time: ResMut<Time<Virtual>>, mut physics_time: ResMut<Time<Physics>>) {
+    //physics keeps its own clock so it has to be paused separately
+    virtual_time.pause();
+    physics_time.pause();
+}
+
+fn resume_time(mut virtual_time: ResMut<Time<Virtual>>, mut physics_time: ResMut<Time<Physics>>) {
+    virtual_time.unpause();
+    physics_time.unpause();
+}
+
+/// Staggers the opening asteroids in one by one before handing control to the player.
+#[derive(Resource)]
+pub struct Intro {
+    timer: Timer,
+    remaining: u32,
+    speed: f32,
+}
+
+impl Default for Intro {
+    fn default() -> Self {
+        Self {
+            timer: Timer::new(Duration::from_secs_f32(0.75), TimerMode::Repeating),
+            remaining: 2,
+            speed: 30.0,
+        }
+    }
+}
+
+pub fn run_intro(
+    time: Res<Time>,
+    key: Res<Input<KeyCode>>,
+    mut intro: ResMut<Intro>,
+    mut asteroid_event_writer: EventWriter<SpawnAsteroidEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+    play_area: Res<PlayArea>,
+    difficulty: Res<Difficulty>,
+    mut rng: ResMut<GameRng>,
+) {
+    //skipping brings in everything that is left right away
+    let skip = key.just_pressed(KeyCode::Return);
+    intro.timer.tick(time.delta());
+    if skip || intro.timer.just_finished() {
+        let count = if skip {
+            intro.remaining
+        } else {
+            intro.remaining.min(1)
+        };
+        for _ in 0..count {
+            asteroid_event_writer.send(SpawnAsteroidEvent::from_edge(
+                &play_area,
+                intro.speed * difficulty.asteroid_speed(),
+                &mut rng.0,
+            ));
+        }
+        intro.remaining -= count;
+    }
+    if intro.remaining == 0 {
+        next_state.set(GameState::Playing);
+    }
+}
+
+/// How hard runs are, picked from the menu.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Difficulty {
+    //slower asteroids and an extra life
+    Easy,
+    #[default]
+    Normal,
+    //bigger, faster waves and more UFOs
+    Hard,
+}
+
+impl Difficulty {
+    pub fn next(self) -> Self {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy,
+        }
+    }
+
+    /// Multiplier on the speed of asteroids sent in from the edges.
+    pub fn asteroid_speed(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.7,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.3,
+        }
+    }
+
+    /// Multiplier on how many asteroids a wave brings.
+    pub fn wave_size(&self) -> f32 {
+        match self {
+            Difficulty::Easy | Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.5,
+        }
+    }
+
+    /// Multiplier on how often UFOs show up.
+    pub fn ufo_frequency(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.5,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.5,
+        }
+    }
+
+    /// Lives a run starts with on top of the usual ones.
+    pub fn extra_lives(&self) -> u8 {
+        match self {
+            Difficulty::Easy => 1,
+            Difficulty::Normal | Difficulty::Hard => 0,
+        }
+    }
+}
+
+/// The seed every run's [`GameRng`] starts from, a random one per run when `None`.
+#[derive(Resource, Default)]
+pub struct GameSeed(pub Option<u64>);
+
+impl GameSeed {
+    /// The seed picked at launch with `--seed <n>`, or else the `SURVIVE_SEED` environment
+    /// variable, so a logged run can be played again.
+    pub fn from_launch() -> Self {
+        Self::parse(std::env::args().skip(1), std::env::var("SURVIVE_SEED").ok())
+    }
+
+    fn parse(mut args: impl Iterator<Item = String>, env: Option<String>) -> Self {
+        let arg = args.find(|arg| arg == "--seed").map(|_| args.next());
+        let Some(seed) = arg.flatten().or(env) else {
+            return Self(None);
+        };
+        match seed.trim().parse() {
+            Ok(seed) => Self(Some(seed)),
+            Err(error) => {
+                warn!("ignoring unreadable seed {:?}: {}", seed, error);
+                Self(None)
+            }
+        }
+    }
+}
+
+/// The randomness that decides how a run plays out: spawns, splits, drops and the like, so the
+/// same seed plays out the same way. Purely visual effects keep using `thread_rng` so how many
+/// of them a frame happens to spawn can't shift the run.
+#[derive(Resource)]
+pub struct GameRng(pub StdRng);
+
+impl GameRng {
+    pub fn seeded(seed: u64) -> Self {
+        Self(StdRng::seed_from_u64(seed))
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self(StdRng::from_entropy())
+    }
+}
+
+/// Every system that draws from [`GameRng`], one slot each. The slots run chained in this
+/// order, otherwise two of them could swap places from one frame to the next and hand out
+/// the seed's numbers in a different order.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GameRngSet {
+    Intro,
+    Ufos,
+    Hyperspace,
+    //everything from here on runs after damage has been applied
+    Director,
+    Breakup,
+    Threat,
+    Upgrades,
+    Waves,
+    Spawn,
+}
+
+#[derive(Event)]
+pub struct StartGameEvent;
+#[derive(Component)]
+pub struct GameEntity;
+
+fn restart_game(
+    key: Res<Input<KeyCode>>,
+    mut commands: Commands,
+    entity_query: Query<Entity, With<GameEntity>>,
+    mut event_writer: EventWriter<StartGameEvent>,
+    mut asteroid_event_writer: ResMut<Events<SpawnAsteroidEvent>>,
+    mut asteroid_queue: ResMut<AsteroidSpawnQueue>,
+) {
+    if !key.just_pressed(KeyCode::Return) {
+        return;
+    }
+    for entity in entity_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    event_writer.send(StartGameEvent);
+    asteroid_event_writer.clear();
+    asteroid_queue.pending.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asteroids::AsteroidClass;
+
+    #[test]
+    fn a_seed_can_be_picked_at_launch() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        let seed = |list: &[&str], env: Option<&str>| {
+            GameSeed::parse(args(list).into_iter(), env.map(String::from)).0
+        };
+        assert_eq!(seed(&[], None), None);
+        assert_eq!(seed(&["--seed", "42"], None), Some(42));
+        assert_eq!(seed(&[], Some("7")), Some(7));
+        //the command line wins over the environment
+        assert_eq!(seed(&["--seed", "42"], Some("7")), Some(42));
+        assert_eq!(seed(&["--seed", "lots"], None), None);
+        assert_eq!(seed(&["--seed"], Some("7")), Some(7));
+    }
+
+    #[test]
+    fn asteroids_hold_still_while_paused() {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            TransformPlugin,
+            HierarchyPlugin,
+            PhysicsPlugins::default(),
+        ))
+        .insert_resource(bevy::time::TimeUpdateStrategy::ManualDuration(
+            Duration::from_millis(16),
+        ))
+        .add_state::<GameState>()
+        .add_systems(OnEnter(GameState::Paused), pause_time)
+        .add_systems(OnExit(GameState::Paused), resume_time);
+        app.world
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Playing);
+        let asteroid = app
+            .world
+            .spawn((
+                AsteroidClass::Big,
+                RigidBody::Kinematic,
+                LinearVelocity(Vec2::new(100.0, 0.0)),
+                TransformBundle::default(),
+            ))
+            .id();
+        let position = |app: &App| app.world.get::<Transform>(asteroid).unwrap().translation;
+        for _ in 0..10 {
+            app.update();
+        }
+        //moving along while playing shows the physics is really running
+        assert!(position(&app).x > 0.0);
+
+        app.world
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Paused);
+        app.update();
+        let paused_at = position(&app);
+        for _ in 0..10 {
+            app.update();
+        }
+        assert_eq!(position(&app), paused_at);
+    }
+}
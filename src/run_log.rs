@@ -0,0 +1,221 @@
+use bevy::prelude::*;
+use bevy_xpbd_2d::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    asteroids::{AsteroidClass, WaveStartedEvent},
+    collisions::apply_damage,
+    explosion::Explosion,
+    run::Difficulty,
+    score::Score,
+    ship::{handle_ship_damage, PlayerDamagedEvent, Ship},
+    ufo::{EnemyLaser, Ufo},
+    weapons::{update_weapons, Laser, SpawnLaserEvent},
+};
+
+/// Keeps stats on each run and writes them out as a summary when it ends.
+pub struct RunLogPlugin;
+
+impl Plugin for RunLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunLog>()
+            .add_systems(Update, track_run_stats.after(update_weapons))
+            .add_systems(
+                Update,
+                track_death_cause
+                    .after(apply_damage)
+                    .before(handle_ship_damage),
+            );
+    }
+}
+
+/// Tracks stats for the current run and writes a summary to `runs/` when it ends.
+#[derive(Resource)]
+pub struct RunLog {
+    pub enabled: bool,
+    seed: u64,
+    difficulty: Difficulty,
+    elapsed: f32,
+    shots_fired: u32,
+    hits: u32,
+    waves_cleared: u32,
+    death_cause: DeathCause,
+}
+
+impl Default for RunLog {
+    fn default() -> Self {
+        Self {
+            //there is no file system to write to on the web
+            enabled: cfg!(not(target_arch = "wasm32")),
+            seed: 0,
+            difficulty: Difficulty::default(),
+            elapsed: 0.0,
+            shots_fired: 0,
+            hits: 0,
+            waves_cleared: 0,
+            death_cause: DeathCause::Unknown,
+        }
+    }
+}
+
+/// What dealt the ship the last hit it took.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeathCause {
+    Unknown,
+    Asteroid,
+    Ufo,
+    UfoShot,
+    Explosion,
+    //a hyperspace jump gone wrong
+    Hyperspace,
+}
+
+#[derive(Serialize)]
+struct RunSummary {
+    seed: u64,
+    difficulty: Difficulty,
+    score: u32,
+    survival_seconds: f32,
+    shots_fired: u32,
+    hits: u32,
+    accuracy: f32,
+    waves_cleared: u32,
+    death_cause: DeathCause,
+}
+
+impl RunLog {
+    pub fn start(&mut self, seed: u64, difficulty: Difficulty) {
+        *self = Self {
+            enabled: self.enabled,
+            seed,
+            difficulty,
+            ..default()
+        };
+    }
+
+    pub fn finish(&mut self, score: &Score) {
+        if !self.enabled {
+            return;
+        }
+        let summary = RunSummary {
+            seed: self.seed,
+            difficulty: self.difficulty,
+            score: score.0,
+            survival_seconds: self.elapsed,
+            shots_fired: self.shots_fired,
+            hits: self.hits,
+            accuracy: self.hits as f32 / self.shots_fired.max(1) as f32,
+            waves_cleared: self.waves_cleared,
+            death_cause: self.death_cause,
+        };
+        if let Err(error) = write_run_summary(&summary) {
+            warn!("could not write run summary: {}", error);
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write_run_summary(summary: &RunSummary) -> std::io::Result<()> {
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    std::fs::create_dir_all("runs")?;
+    let json = serde_json::to_string_pretty(summary)?;
+    std::fs::write(format!("runs/run-{}.json", timestamp), json)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write_run_summary(_summary: &RunSummary) -> std::io::Result<()> {
+    Ok(())
+}
+
+fn track_run_stats(
+    time: Res<Time>,
+    mut run_log: ResMut<RunLog>,
+    mut lasers_fired: EventReader<SpawnLaserEvent>,
+    mut waves: EventReader<WaveStartedEvent>,
+    mut collisions: EventReader<Collision>,
+    lasers: Query<(), With<Laser>>,
+    asteroids: Query<(), With<AsteroidClass>>,
+) {
+    run_log.elapsed += time.delta_seconds();
+    run_log.shots_fired += lasers_fired.read().count() as u32;
+    //a wave only comes in once the field is clear, the opening one counts as the first
+    if let Some(wave) = waves.read().last() {
+        run_log.waves_cleared = wave.wave;
+    }
+    for event in collisions.read() {
+        let (a, b) = (event.0.entity1, event.0.entity2);
+        if (lasers.contains(a) && asteroids.contains(b))
+            || (lasers.contains(b) && asteroids.contains(a))
+        {
+            run_log.hits += 1;
+        }
+    }
+}
+
+fn track_death_cause(
+    mut damage_events: EventReader<PlayerDamagedEvent>,
+    asteroids: Query<(), With<AsteroidClass>>,
+    ufos: Query<(), With<Ufo>>,
+    ufo_shots: Query<(), With<EnemyLaser>>,
+    explosions: Query<(), With<Explosion>>,
+    ships: Query<(), With<Ship>>,
+    mut run_log: ResMut<RunLog>,
+) {
+    //whatever hit the ship is only cleaned up at the end of the frame, so it can still be told
+    for event in damage_events.read() {
+        run_log.death_cause = if asteroids.contains(event.source) {
+            DeathCause::Asteroid
+        } else if ufos.contains(event.source) {
+            DeathCause::Ufo
+        } else if ufo_shots.contains(event.source) {
+            DeathCause::UfoShot
+        } else if explosions.contains(event.source) {
+            DeathCause::Explosion
+        } else if ships.contains(event.source) {
+            DeathCause::Hyperspace
+        } else {
+            DeathCause::Unknown
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_last_hit_decides_the_death_cause() {
+        let mut app = App::new();
+        app.init_resource::<RunLog>()
+            .add_event::<PlayerDamagedEvent>()
+            .add_systems(Update, track_death_cause);
+        let asteroid = app.world.spawn(AsteroidClass::Small).id();
+        let ship = app.world.spawn(Ship).id();
+
+        app.world.send_event(PlayerDamagedEvent {
+            amount: 1,
+            remaining: 1,
+            source: asteroid,
+        });
+        app.update();
+        assert_eq!(
+            app.world.resource::<RunLog>().death_cause,
+            DeathCause::Asteroid
+        );
+
+        app.world.send_event(PlayerDamagedEvent {
+            amount: 1,
+            remaining: 0,
+            source: ship,
+        });
+        app.update();
+        assert_eq!(
+            app.world.resource::<RunLog>().death_cause,
+            DeathCause::Hyperspace
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use bevy::prelude::*;
+
+use crate::{
+    asteroids::{handle_destroyed_asteroids, AsteroidDestroyedEvent},
+    run::GameState,
+    ufo::UfoDestroyedEvent,
+    weapons::WeaponUpgrade,
+};
+
+/// The run's score, kill count and combo.
+pub struct ScorePlugin;
+
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Score>()
+            .init_resource::<KillCount>()
+            .add_event::<ScoreEvent>()
+            .add_systems(Update, award_points.after(handle_destroyed_asteroids))
+            .init_resource::<Combo>()
+            .init_resource::<ComboTimer>()
+            .add_systems(
+                Update,
+                tick_combo
+                    .before(handle_destroyed_asteroids)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Score(pub u32);
+
+/// Points scored for something other than a kill, like grazing a UFO bolt.
+#[derive(Event)]
+pub struct ScoreEvent {
+    pub points: u32,
+    pub position: Vec2,
+}
+
+/// Asteroids destroyed this run, every few of them earns a weapon upgrade.
+#[derive(Resource, Default)]
+pub struct KillCount(pub u32);
+
+/// Asteroids destroyed in quick succession, each one within the [`ComboTimer`] of the last
+/// scores one more times its points, up to `max_multiplier`.
+#[derive(Resource)]
+pub struct Combo {
+    //0 while no combo is running
+    pub multiplier: u32,
+    pub max_multiplier: u32,
+}
+
+impl Default for Combo {
+    fn default() -> Self {
+        Self {
+            multiplier: 0,
+            max_multiplier: 8,
+        }
+    }
+}
+
+impl Combo {
+    /// Counts a kill towards the combo and returns the multiplier it scores with.
+    pub fn register_kill(&mut self, timer: &mut ComboTimer) -> u32 {
+        self.multiplier = (self.multiplier + 1).min(self.max_multiplier);
+        timer.0.reset();
+        self.multiplier
+    }
+}
+
+/// Time left to keep the [`Combo`] going, restarted by every kill.
+#[derive(Resource)]
+pub struct ComboTimer(pub Timer);
+
+impl Default for ComboTimer {
+    fn default() -> Self {
+        Self(Timer::from_seconds(1.5, TimerMode::Once))
+    }
+}
+
+pub fn tick_combo(time: Res<Time>, mut timer: ResMut<ComboTimer>, mut combo: ResMut<Combo>) {
+    if timer.0.tick(time.delta()).just_finished() && combo.multiplier > 0 {
+        combo.multiplier = 0;
+    }
+}
+
+pub fn award_points(
+    mut events: EventReader<AsteroidDestroyedEvent>,
+    mut ufo_events: EventReader<UfoDestroyedEvent>,
+    mut score_events: EventReader<ScoreEvent>,
+    mut score: ResMut<Score>,
+    mut kills: ResMut<KillCount>,
+    mut upgrade_writer: EventWriter<WeaponUpgrade>,
+) {
+    for event in events.read() {
+        score.0 += event.points;
+        kills.0 += 1;
+        info!(
+            "{:?} asteroid destroyed at {} for {}, score: {}",
+            event.class, event.position, event.points, score.0
+        );
+        if kills.0 % 5 == 0 {
+            upgrade_writer.send(WeaponUpgrade);
+        }
+    }
+    for event in ufo_events.read() {
+        score.0 += event.points;
+        info!(
+            "ufo destroyed at {} for {}, score: {}",
+            event.position, event.points, score.0
+        );
+    }
+    for event in score_events.read() {
+        score.0 += event.points;
+    }
+}
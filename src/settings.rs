@@ -0,0 +1,405 @@
+use bevy::{prelude::*, ui::FocusPolicy};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audio::SfxVolume,
+    display::{msaa_from_samples, next_msaa, FrameRateCap},
+    highscore::{read_save, write_save},
+    input::AimScheme,
+    menu::{spawn_button, BUTTON_COLOR, BUTTON_HOVER_COLOR},
+    run_log::RunLog,
+};
+
+/// The settings screen, opened over the main menu or the pause menu. Settings apply as soon as
+/// they are changed and are saved next to the high score when the screen closes.
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<SettingsState>()
+            .add_systems(Startup, load_settings)
+            .add_systems(OnEnter(SettingsState::Open), spawn_settings)
+            .add_systems(
+                OnExit(SettingsState::Open),
+                (despawn_settings, save_settings),
+            )
+            .add_systems(
+                Update,
+                settings_buttons.run_if(in_state(SettingsState::Open)),
+            )
+            .add_systems(
+                Update,
+                update_settings_labels
+                    .after(settings_buttons)
+                    .run_if(in_state(SettingsState::Open)),
+            );
+    }
+}
+
+/// Whether the settings screen is up. It sits next to [`crate::run::GameState`] instead of in
+/// it so opening it from the pause menu keeps the game paused underneath.
+#[derive(States, Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SettingsState {
+    #[default]
+    Closed,
+    Open,
+}
+
+const SETTINGS_SAVE: &str = "settings";
+
+/// What gets written to disk, one field per setting on the screen.
+#[derive(Serialize, Deserialize)]
+struct SavedSettings {
+    sfx_volume: f32,
+    frame_rate_cap: FrameRateCap,
+    aim_scheme: AimScheme,
+    //missing from saves made before it could be turned off, which keep the default
+    #[serde(default)]
+    run_log: Option<bool>,
+    //samples per pixel, missing from saves made before it was a setting
+    #[serde(default)]
+    msaa: Option<u32>,
+}
+
+fn load_settings(
+    mut sfx_volume: ResMut<SfxVolume>,
+    mut frame_rate_cap: ResMut<FrameRateCap>,
+    mut aim_scheme: ResMut<AimScheme>,
+    mut run_log: ResMut<RunLog>,
+    mut msaa: ResMut<Msaa>,
+) {
+    //nothing saved yet keeps the defaults
+    let Some(saved) = read_save(SETTINGS_SAVE) else {
+        return;
+    };
+    match serde_json::from_str::<SavedSettings>(&saved) {
+        Ok(saved) => {
+            sfx_volume.0 = saved.sfx_volume.clamp(0.0, 1.0);
+            *frame_rate_cap = saved.frame_rate_cap;
+            *aim_scheme = saved.aim_scheme;
+            if let Some(enabled) = saved.run_log {
+                run_log.enabled = enabled;
+            }
+            if let Some(level) = saved.msaa.and_then(msaa_from_samples) {
+                *msaa = level;
+            }
+        }
+        Err(error) => warn!("ignoring unreadable settings: {}", error),
+    }
+}
+
+fn save_settings(
+    sfx_volume: Res<SfxVolume>,
+    frame_rate_cap: Res<FrameRateCap>,
+    aim_scheme: Res<AimScheme>,
+    run_log: Res<RunLog>,
+    msaa: Res<Msaa>,
+) {
+    let saved = SavedSettings {
+        sfx_volume: sfx_volume.0,
+        frame_rate_cap: *frame_rate_cap,
+        aim_scheme: *aim_scheme,
+        run_log: Some(run_log.enabled),
+        msaa: Some(msaa.samples()),
+    };
+    let result = serde_json::to_string(&saved)
+        .map_err(std::io::Error::from)
+        .and_then(|json| write_save(SETTINGS_SAVE, &json));
+    if let Err(error) = result {
+        warn!("could not save settings: {}", error);
+    }
+}
+
+/// The top of the settings screen.
+#[derive(Component)]
+pub struct SettingsRoot;
+
+#[derive(Component, Clone, Copy)]
+enum SettingsButton {
+    VolumeDown,
+    VolumeUp,
+    //cycles through the caps
+    FrameRateCap,
+    //cycles through the aim schemes
+    Aim,
+    //turns writing run summaries on and off
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    RunLog,
+    //cycles through the anti-aliasing levels
+    Msaa,
+    Back,
+}
+
+/// Marks the text showing the current sound effect volume.
+#[derive(Component)]
+struct VolumeLabel;
+
+const VOLUME_STEP: f32 = 0.1;
+
+fn spawn_settings(
+    mut commands: Commands,
+    sfx_volume: Res<SfxVolume>,
+    frame_rate_cap: Res<FrameRateCap>,
+    aim_scheme: Res<AimScheme>,
+    run_log: Res<RunLog>,
+    msaa: Res<Msaa>,
+) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(20.0),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.9).into(),
+                //drawn over the menu it was opened from, which must not take the clicks
+                z_index: ZIndex::Global(1),
+                focus_policy: FocusPolicy::Block,
+                ..default()
+            },
+            SettingsRoot,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Settings",
+                TextStyle {
+                    font_size: 60.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        align_items: AlignItems::Center,
+                        column_gap: Val::Px(20.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    spawn_step_button(parent, "-", SettingsButton::VolumeDown);
+                    parent.spawn((
+                        TextBundle::from_section(
+                            volume_label(&sfx_volume),
+                            TextStyle {
+                                font_size: 32.0,
+                                color: Color::WHITE,
+                                ..default()
+                            },
+                        ),
+                        VolumeLabel,
+                    ));
+                    spawn_step_button(parent, "+", SettingsButton::VolumeUp);
+                });
+            spawn_button(
+                parent,
+                &frame_rate_cap_label(*frame_rate_cap),
+                SettingsButton::FrameRateCap,
+            );
+            spawn_button(parent, &aim_label(*aim_scheme), SettingsButton::Aim);
+            //run summaries are written to files, which the web doesn't have
+            #[cfg(not(target_arch = "wasm32"))]
+            spawn_button(parent, &run_log_label(&run_log), SettingsButton::RunLog);
+            spawn_button(parent, &msaa_label(*msaa), SettingsButton::Msaa);
+            spawn_button(parent, "Back", SettingsButton::Back);
+        });
+}
+
+fn spawn_step_button(parent: &mut ChildBuilder, label: &str, button: SettingsButton) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(60.0),
+                    height: Val::Px(60.0),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: BUTTON_COLOR.into(),
+                ..default()
+            },
+            button,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                label,
+                TextStyle {
+                    font_size: 32.0,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn volume_label(volume: &SfxVolume) -> String {
+    format!("Sound: {:.0}%", volume.0 * 100.0)
+}
+
+fn frame_rate_cap_label(cap: FrameRateCap) -> String {
+    format!("FPS cap: {:?}", cap)
+}
+
+fn aim_label(scheme: AimScheme) -> String {
+    format!("Aim: {:?}", scheme)
+}
+
+fn run_log_label(run_log: &RunLog) -> String {
+    format!("Run log: {}", if run_log.enabled { "On" } else { "Off" })
+}
+
+fn msaa_label(msaa: Msaa) -> String {
+    match msaa {
+        Msaa::Off => "Anti-aliasing: Off".to_string(),
+        _ => format!("Anti-aliasing: {}x", msaa.samples()),
+    }
+}
+
+fn despawn_settings(mut commands: Commands, roots: Query<Entity, With<SettingsRoot>>) {
+    for entity in roots.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn settings_buttons(
+    mut buttons: Query<(&Interaction, &SettingsButton, &mut BackgroundColor), Changed<Interaction>>,
+    key: Res<Input<KeyCode>>,
+    mut next_state: ResMut<NextState<SettingsState>>,
+    mut sfx_volume: ResMut<SfxVolume>,
+    mut frame_rate_cap: ResMut<FrameRateCap>,
+    mut aim_scheme: ResMut<AimScheme>,
+    mut run_log: ResMut<RunLog>,
+    mut msaa: ResMut<Msaa>,
+) {
+    if key.just_pressed(KeyCode::Escape) {
+        next_state.set(SettingsState::Closed);
+    }
+    for (interaction, button, mut color) in buttons.iter_mut() {
+        match interaction {
+            Interaction::Pressed => match button {
+                SettingsButton::VolumeDown => {
+                    sfx_volume.0 = (sfx_volume.0 - VOLUME_STEP).max(0.0);
+                }
+                SettingsButton::VolumeUp => {
+                    sfx_volume.0 = (sfx_volume.0 + VOLUME_STEP).min(1.0);
+                }
+                SettingsButton::FrameRateCap => *frame_rate_cap = frame_rate_cap.next(),
+                SettingsButton::Aim => *aim_scheme = aim_scheme.next(),
+                SettingsButton::RunLog => run_log.enabled = !run_log.enabled,
+                SettingsButton::Msaa => *msaa = next_msaa(*msaa),
+                SettingsButton::Back => next_state.set(SettingsState::Closed),
+            },
+            Interaction::Hovered => *color = BUTTON_HOVER_COLOR.into(),
+            Interaction::None => *color = BUTTON_COLOR.into(),
+        }
+    }
+}
+
+fn update_settings_labels(
+    sfx_volume: Res<SfxVolume>,
+    frame_rate_cap: Res<FrameRateCap>,
+    aim_scheme: Res<AimScheme>,
+    run_log: Res<RunLog>,
+    msaa: Res<Msaa>,
+    buttons: Query<(&SettingsButton, &Children)>,
+    mut volume_labels: Query<&mut Text, With<VolumeLabel>>,
+    mut texts: Query<&mut Text, Without<VolumeLabel>>,
+) {
+    if sfx_volume.is_changed() {
+        for mut text in volume_labels.iter_mut() {
+            text.sections[0].value = volume_label(&sfx_volume);
+        }
+    }
+    if !frame_rate_cap.is_changed()
+        && !aim_scheme.is_changed()
+        && !run_log.is_changed()
+        && !msaa.is_changed()
+    {
+        return;
+    }
+    for (button, children) in buttons.iter() {
+        let label = match button {
+            SettingsButton::FrameRateCap => frame_rate_cap_label(*frame_rate_cap),
+            SettingsButton::Aim => aim_label(*aim_scheme),
+            SettingsButton::RunLog => run_log_label(&run_log),
+            SettingsButton::Msaa => msaa_label(*msaa),
+            _ => continue,
+        };
+        for child in children.iter() {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                text.sections[0].value = label.clone();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_volume_buttons_change_the_sfx_volume_right_away() {
+        let mut app = App::new();
+        app.init_resource::<Input<KeyCode>>()
+            .add_state::<SettingsState>()
+            .init_resource::<SfxVolume>()
+            .init_resource::<FrameRateCap>()
+            .init_resource::<AimScheme>()
+            .init_resource::<Msaa>()
+            .insert_resource(RunLog {
+                enabled: false,
+                ..default()
+            })
+            .add_systems(OnEnter(SettingsState::Open), spawn_settings)
+            .add_systems(
+                Update,
+                (settings_buttons, update_settings_labels)
+                    .chain()
+                    .run_if(in_state(SettingsState::Open)),
+            );
+        app.world
+            .resource_mut::<NextState<SettingsState>>()
+            .set(SettingsState::Open);
+        app.update();
+
+        let up = app
+            .world
+            .query::<(Entity, &SettingsButton)>()
+            .iter(&app.world)
+            .find(|(_, button)| matches!(button, SettingsButton::VolumeUp))
+            .map(|(entity, _)| entity)
+            .unwrap();
+        app.world.entity_mut(up).insert(Interaction::Pressed);
+        app.update();
+
+        let volume = app.world.resource::<SfxVolume>().0;
+        assert!((volume - 0.6).abs() < 0.001);
+        let mut labels = app.world.query_filtered::<&Text, With<VolumeLabel>>();
+        assert_eq!(labels.single(&app.world).sections[0].value, "Sound: 60%");
+    }
+
+    #[test]
+    fn the_msaa_level_survives_a_save_and_load() {
+        use bevy::ecs::system::RunSystemOnce;
+
+        let mut app = App::new();
+        app.init_resource::<SfxVolume>()
+            .init_resource::<FrameRateCap>()
+            .init_resource::<AimScheme>()
+            .init_resource::<RunLog>()
+            .insert_resource(Msaa::Sample2);
+        app.world.run_system_once(save_settings);
+
+        *app.world.resource_mut::<Msaa>() = Msaa::Off;
+        app.world.run_system_once(load_settings);
+        assert_eq!(*app.world.resource::<Msaa>(), Msaa::Sample2);
+    }
+}
@@ -0,0 +1,102 @@
+use std::f32::consts::PI;
+
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+
+use crate::collisions::SpawnSparksEvent;
+
+/// A one-hit shield on the ship, drawn as a ring around it while it is up.
+pub struct ShieldPlugin;
+
+impl Plugin for ShieldPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ShieldBrokenEvent>()
+            .add_systems(Update, attach_shield_ring)
+            .add_systems(Update, show_shield_ring.after(attach_shield_ring))
+            .add_systems(Update, shield_broken_effect);
+    }
+}
+
+/// Absorbs the next hit the ship takes while `active`.
+#[derive(Component, Default)]
+pub struct Shield {
+    pub active: bool,
+}
+
+impl Shield {
+    /// Takes a hit on the shield if it is up, dropping it. Returns whether the hit was absorbed.
+    pub fn absorb(&mut self) -> bool {
+        let absorbed = self.active;
+        self.active = false;
+        absorbed
+    }
+}
+
+/// Sent when a shield absorbs a hit and goes down.
+#[derive(Event)]
+pub struct ShieldBrokenEvent {
+    pub ship: Entity,
+}
+
+#[derive(Component)]
+struct ShieldRing;
+
+fn attach_shield_ring(
+    shields: Query<Entity, Added<Shield>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut commands: Commands,
+) {
+    for entity in shields.iter() {
+        let ring = commands
+            .spawn((
+                MaterialMesh2dBundle {
+                    //the ship is drawn at half scale, so this is a 55 unit radius in the world
+                    mesh: meshes.add(shape::Circle::new(110.0).into()).into(),
+                    material: materials.add(ColorMaterial::from(Color::rgba(0.3, 0.7, 1.0, 0.25))),
+                    transform: Transform::from_xyz(0.0, 0.0, 0.5),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                ShieldRing,
+            ))
+            .id();
+        commands.entity(entity).add_child(ring);
+    }
+}
+
+fn show_shield_ring(
+    shields: Query<(&Shield, &Children), Changed<Shield>>,
+    mut rings: Query<&mut Visibility, With<ShieldRing>>,
+) {
+    for (shield, children) in shields.iter() {
+        for child in children.iter() {
+            if let Ok(mut visibility) = rings.get_mut(*child) {
+                *visibility = if shield.active {
+                    Visibility::Inherited
+                } else {
+                    Visibility::Hidden
+                };
+            }
+        }
+    }
+}
+
+fn shield_broken_effect(
+    mut events: EventReader<ShieldBrokenEvent>,
+    ships: Query<&Transform>,
+    mut spark_writer: EventWriter<SpawnSparksEvent>,
+) {
+    for event in events.read() {
+        info!("shield down");
+        let Ok(transform) = ships.get(event.ship) else {
+            continue;
+        };
+        for n in 0..12 {
+            let angle = n as f32 * PI / 6.0;
+            spark_writer.send(SpawnSparksEvent {
+                position: transform.translation.xy(),
+                normal: Vec2::new(angle.cos(), angle.sin()),
+            });
+        }
+    }
+}
@@ -0,0 +1,333 @@
+use std::{f32::consts::PI, time::Duration};
+
+use bevy::prelude::*;
+use bevy_xpbd_2d::prelude::*;
+
+use crate::{
+    asteroids::AsteroidClass,
+    collisions::{apply_damage, SpawnSparksEvent},
+    cooldown::{tick_cooldowns, Cooldown},
+    explosion::SpawnExplosionEvent,
+    ghost::GhostRecorder,
+    run::GameState,
+    run_log::RunLog,
+    score::Score,
+};
+
+/// The player's ship taking damage, its lives and coming back after losing one.
+pub struct ShipPlugin;
+
+impl Plugin for ShipPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, tick_cooldowns::<EmergencyWarp>)
+            .add_systems(
+                Update,
+                emergency_warp
+                    .after(tick_cooldowns::<EmergencyWarp>)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_event::<PlayerDamagedEvent>()
+            .init_resource::<Lives>()
+            .add_event::<PlayerRespawnEvent>()
+            .add_systems(Update, tick_invulnerability)
+            .add_systems(Update, respawn_effect.after(handle_ship_damage))
+            .add_systems(Update, handle_ship_damage.after(apply_damage));
+    }
+}
+
+#[derive(Component)]
+pub struct Ship;
+
+#[derive(Component)]
+pub struct ShipHealth(pub i32);
+
+/// The health a fresh ship starts with.
+pub const SHIP_HEALTH: i32 = 5;
+
+/// Ships left before the game is over, counting the one in play.
+#[derive(Resource)]
+pub struct Lives(pub u8);
+
+impl Default for Lives {
+    fn default() -> Self {
+        Self(3)
+    }
+}
+
+/// A ship that can't be hurt by collisions until the timer runs out.
+#[derive(Component)]
+pub struct Invulnerable(pub Timer);
+
+fn tick_invulnerability(
+    time: Res<Time>,
+    mut ships: Query<(Entity, &mut Invulnerable)>,
+    mut commands: Commands,
+) {
+    for (entity, mut invulnerable) in ships.iter_mut() {
+        if invulnerable.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Invulnerable>();
+        }
+    }
+}
+
+/// Sent when a destroyed ship comes back with one of its remaining lives.
+#[derive(Event)]
+pub struct PlayerRespawnEvent {
+    pub position: Vec2,
+}
+
+fn respawn_effect(
+    mut events: EventReader<PlayerRespawnEvent>,
+    mut spark_writer: EventWriter<SpawnSparksEvent>,
+) {
+    //a ring of sparks where the ship reappears
+    for event in events.read() {
+        for n in 0..8 {
+            let angle = n as f32 * PI / 4.0;
+            spark_writer.send(SpawnSparksEvent {
+                position: event.position,
+                normal: Vec2::new(angle.cos(), angle.sin()),
+            });
+        }
+    }
+}
+
+/// Sent whenever the ship loses health.
+#[derive(Event)]
+pub struct PlayerDamagedEvent {
+    pub amount: i32,
+    pub remaining: i32,
+    //whatever dealt the damage
+    pub source: Entity,
+}
+
+/// Marks the cooldown between taking collision damage, so an overlap that lasts
+/// several frames only hurts once.
+pub enum CollisionDamage {}
+
+/// How long after a collision hit the ship can be hurt by collisions again.
+pub const DAMAGE_COOLDOWN: Duration = Duration::from_millis(500);
+
+/// Marks the long cooldown on the player's emergency warp back to the center.
+pub enum EmergencyWarp {}
+
+/// Puts the ship back in the middle of the arena at rest, briefly safe from collisions.
+pub fn recenter_ship(
+    transform: &mut Transform,
+    velocity: &mut LinearVelocity,
+    damage_cooldown: &mut Cooldown<CollisionDamage>,
+) {
+    transform.translation = Vec3::new(0.0, 0.0, transform.translation.z);
+    velocity.0 = Vec2::ZERO;
+    damage_cooldown.restart();
+}
+
+/// Lets a player who got wedged in warp back to the center with R, on a long cooldown.
+fn emergency_warp(
+    key: Res<Input<KeyCode>>,
+    mut ships: Query<
+        (
+            &mut Transform,
+            &mut LinearVelocity,
+            &mut Cooldown<EmergencyWarp>,
+            &mut Cooldown<CollisionDamage>,
+        ),
+        With<Ship>,
+    >,
+) {
+    if !key.just_pressed(KeyCode::R) {
+        return;
+    }
+    for (mut transform, mut velocity, mut warp, mut damage_cooldown) in ships.iter_mut() {
+        if warp.trigger() {
+            recenter_ship(&mut transform, &mut velocity, &mut damage_cooldown);
+        }
+    }
+}
+
+pub fn handle_ship_damage(
+    mut damage_events: EventReader<PlayerDamagedEvent>,
+    mut ship_query: Query<(
+        Entity,
+        &mut ShipHealth,
+        &mut Transform,
+        &mut LinearVelocity,
+        &mut Cooldown<CollisionDamage>,
+    )>,
+    asteroids: Query<(Entity, &Transform), (With<AsteroidClass>, Without<ShipHealth>)>,
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut lives: ResMut<Lives>,
+    mut respawn_writer: EventWriter<PlayerRespawnEvent>,
+    mut explosion_writer: EventWriter<SpawnExplosionEvent>,
+    mut ghost: ResMut<GhostRecorder>,
+    mut run_log: ResMut<RunLog>,
+    score: Res<Score>,
+) {
+    for event in damage_events.read() {
+        info!(
+            "ship hit for {}, {} health left",
+            event.amount, event.remaining
+        );
+    }
+    //nothing may be sitting on the spot a ship respawns on
+    let safe_radius = 200.0;
+    for (entity, mut health, mut transform, mut velocity, mut damage_cooldown) in
+        ship_query.iter_mut()
+    {
+        if health.0 > 0 {
+            continue;
+        }
+        explosion_writer.send(SpawnExplosionEvent {
+            position: transform.translation.xy(),
+            scale: 1.5,
+        });
+        lives.0 = lives.0.saturating_sub(1);
+        if lives.0 > 0 {
+            info!("ship destroyed, {} lives left", lives.0);
+            recenter_ship(&mut transform, &mut velocity, &mut damage_cooldown);
+            health.0 = SHIP_HEALTH;
+            commands
+                .entity(entity)
+                .insert(Invulnerable(Timer::from_seconds(3.0, TimerMode::Once)));
+            for (asteroid, asteroid_transform) in asteroids.iter() {
+                if asteroid_transform.translation.xy().length() < safe_radius {
+                    commands.entity(asteroid).despawn_recursive();
+                }
+            }
+            respawn_writer.send(PlayerRespawnEvent {
+                position: transform.translation.xy(),
+            });
+        } else {
+            info!("Game Over, press enter to play again");
+            commands.entity(entity).despawn_recursive();
+            next_state.set(GameState::GameOver);
+            ghost.finish_run();
+            run_log.finish(&score);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        asteroids::{AsteroidHealth, AsteroidHitEvent},
+        collisions::{handle_collisions, tests::collision, DamageEvent},
+        shield::ShieldBrokenEvent,
+    };
+
+    fn advance(app: &mut App, delta: Duration) {
+        app.world.resource_mut::<Time>().advance_by(delta);
+        app.update();
+    }
+
+    /// Ships taking collision damage, down to losing lives and the game.
+    fn ship_damage_app(lives: u8) -> App {
+        let mut app = App::new();
+        app.init_resource::<Time>()
+            .add_state::<GameState>()
+            .add_event::<Collision>()
+            .add_event::<DamageEvent>()
+            .add_event::<SpawnSparksEvent>()
+            .add_event::<AsteroidHitEvent>()
+            .add_event::<PlayerDamagedEvent>()
+            .add_event::<ShieldBrokenEvent>()
+            .add_event::<PlayerRespawnEvent>()
+            .add_event::<SpawnExplosionEvent>()
+            .insert_resource(Lives(lives))
+            .init_resource::<GhostRecorder>()
+            .init_resource::<Score>()
+            //keep test runs out of the run log
+            .insert_resource(RunLog {
+                enabled: false,
+                ..default()
+            })
+            .add_systems(
+                Update,
+                (
+                    tick_cooldowns::<CollisionDamage>,
+                    handle_collisions,
+                    apply_damage,
+                    handle_ship_damage,
+                )
+                    .chain(),
+            );
+        app
+    }
+
+    fn spawn_ship(app: &mut App, health: i32, position: Vec2) -> Entity {
+        app.world
+            .spawn((
+                Ship,
+                ShipHealth(health),
+                Cooldown::<CollisionDamage>::new(DAMAGE_COOLDOWN),
+                Transform::from_translation(position.extend(0.0)),
+                LinearVelocity::default(),
+            ))
+            .id()
+    }
+
+    /// Rams the ship with a fresh asteroid once its damage window has passed.
+    fn ram(app: &mut App, ship: Entity) {
+        let asteroid = app
+            .world
+            .spawn((
+                AsteroidClass::Big,
+                AsteroidHealth(5),
+                Transform::from_xyz(500.0, 0.0, 0.0),
+            ))
+            .id();
+        app.world.send_event(collision(ship, asteroid));
+        advance(app, DAMAGE_COOLDOWN);
+    }
+
+    #[test]
+    fn three_hits_take_the_last_ship_and_end_the_game() {
+        let mut app = ship_damage_app(1);
+        let ship = spawn_ship(&mut app, 3, Vec2::ZERO);
+
+        ram(&mut app, ship);
+        assert_eq!(app.world.get::<ShipHealth>(ship).unwrap().0, 2);
+        ram(&mut app, ship);
+        assert_eq!(app.world.get::<ShipHealth>(ship).unwrap().0, 1);
+        ram(&mut app, ship);
+        assert!(app.world.get_entity(ship).is_none());
+
+        //the state change lands on the next frame
+        app.update();
+        assert_eq!(
+            *app.world.resource::<State<GameState>>().get(),
+            GameState::GameOver
+        );
+    }
+
+    /// Finishes off the ship wherever it is, once any respawn invulnerability is over.
+    fn kill(app: &mut App, ship: Entity, position: Vec2) {
+        let mut entity = app.world.entity_mut(ship);
+        entity.remove::<Invulnerable>();
+        entity.get_mut::<Transform>().unwrap().translation = position.extend(0.0);
+        app.world.send_event(DamageEvent {
+            target: ship,
+            amount: SHIP_HEALTH,
+            source: ship,
+        });
+        advance(app, DAMAGE_COOLDOWN);
+    }
+
+    #[test]
+    fn a_lost_ship_comes_back_in_the_center() {
+        let mut app = ship_damage_app(3);
+        let ship = spawn_ship(&mut app, SHIP_HEALTH, Vec2::ZERO);
+
+        kill(&mut app, ship, Vec2::new(300.0, 100.0));
+        assert_eq!(app.world.resource::<Lives>().0, 2);
+        kill(&mut app, ship, Vec2::new(-200.0, 50.0));
+        assert_eq!(app.world.resource::<Lives>().0, 1);
+
+        let position = app.world.get::<Transform>(ship).unwrap().translation;
+        assert_eq!(position.xy(), Vec2::ZERO);
+        assert_eq!(app.world.get::<ShipHealth>(ship).unwrap().0, SHIP_HEALTH);
+        assert!(app.world.get::<Invulnerable>(ship).is_some());
+    }
+}
@@ -0,0 +1,555 @@
+use std::{f32::consts::PI, time::Duration};
+
+use bevy::{ecs::system::EntityCommands, prelude::*};
+use bevy_xpbd_2d::prelude::*;
+
+use crate::{
+    asteroids::{Armored, AsteroidClass, WeakSpot, EDGE_SPAWN_MARGIN},
+    boss_rush::in_boss_rush,
+    collisions::{apply_damage, spend_laser, DamageEvent, Layer, SpawnSparksEvent},
+    cooldown::{tick_cooldowns, Cooldown},
+    explosion::SpawnExplosionEvent,
+    lifetime::{FadeOut, Lifetime},
+    movement::PlayArea,
+    run::{Difficulty, GameEntity, GameRng, GameRngSet, GameState},
+    score::{Combo, ComboTimer, ScoreEvent},
+    ship::Ship,
+    weapons::{Damage, Laser, PiercedTargets, Piercing},
+};
+
+/// Flying saucers that every so often come in from the edge, chase the ship and shoot at it.
+pub struct UfoPlugin;
+
+impl Plugin for UfoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UfoSettings>()
+            .add_event::<UfoDestroyedEvent>()
+            .add_systems(
+                Update,
+                (
+                    //the boss rush sends in its own
+                    spawn_ufos
+                        .in_set(GameRngSet::Ufos)
+                        .run_if(not(in_boss_rush)),
+                    chase_ship,
+                    ufo_fire,
+                    handle_ufo_collisions,
+                )
+                    .chain()
+                    .before(apply_damage)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            //lasers, missiles and rams all take their health off through apply_damage
+            .add_systems(
+                Update,
+                destroy_ufos
+                    .after(apply_damage)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(Update, tick_cooldowns::<UfoGun>)
+            .init_resource::<GrazeSettings>()
+            .add_event::<GrazeEvent>()
+            .add_systems(
+                Update,
+                graze_bolts
+                    .after(handle_ufo_collisions)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct UfoSettings {
+    pub enabled: bool,
+    pub speed: f32,
+    pub health: i32,
+    pub points: u32,
+    pub shot_speed: f32,
+    spawn_timer: Timer,
+}
+
+impl Default for UfoSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            speed: 90.0,
+            health: 6,
+            points: 500,
+            shot_speed: 300.0,
+            spawn_timer: Timer::from_seconds(40.0, TimerMode::Repeating),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Ufo {
+    pub health: i32,
+}
+
+/// Marks the cooldown between a UFO's shots.
+pub enum UfoGun {}
+
+/// A shot fired by a UFO, it only hurts the ship.
+#[derive(Component)]
+pub struct EnemyLaser;
+
+/// How close a UFO bolt has to pass the ship's center to count as a graze, and what it scores
+/// before the combo multiplier.
+#[derive(Resource)]
+pub struct GrazeSettings {
+    pub radius: f32,
+    pub points: u32,
+}
+
+impl Default for GrazeSettings {
+    fn default() -> Self {
+        Self {
+            radius: 60.0,
+            points: 10,
+        }
+    }
+}
+
+/// How far a bolt has got with grazing the ship. It only scores once it has come close and
+/// then flown off again, a bolt that hits is gone before that.
+#[derive(Component, PartialEq, Eq)]
+enum Graze {
+    Close,
+    Scored,
+}
+
+/// Sent when a UFO bolt narrowly misses the ship.
+#[derive(Event)]
+pub struct GrazeEvent {
+    pub position: Vec2,
+}
+
+/// Sent when the player shoots down a UFO, alongside [`crate::AsteroidDestroyedEvent`].
+#[derive(Event)]
+pub struct UfoDestroyedEvent {
+    pub position: Vec2,
+    pub points: u32,
+}
+
+fn spawn_ufos(
+    time: Res<Time>,
+    mut settings: ResMut<UfoSettings>,
+    play_area: Res<PlayArea>,
+    asset_server: Res<AssetServer>,
+    difficulty: Res<Difficulty>,
+    mut commands: Commands,
+    mut game_rng: ResMut<GameRng>,
+) {
+    if !settings.enabled {
+        return;
+    }
+    //a faster clock brings them in more often
+    let delta = time.delta().mul_f32(difficulty.ufo_frequency());
+    if !settings.spawn_timer.tick(delta).just_finished() {
+        return;
+    }
+    let rng = &mut game_rng.0;
+    let origin = play_area.random_edge_point(rng, EDGE_SPAWN_MARGIN, None);
+    info!("ufo incoming");
+    spawn_ufo(&mut commands, &asset_server, origin, settings.health, 0.4);
+}
+
+/// Spawns a UFO at `origin` with `health`, drawn `scale` times the size of the ship sprite.
+pub fn spawn_ufo<'w, 's, 'a>(
+    commands: &'a mut Commands<'w, 's>,
+    asset_server: &AssetServer,
+    origin: Vec2,
+    health: i32,
+    scale: f32,
+) -> EntityCommands<'w, 's, 'a> {
+    commands.spawn((
+        SpriteBundle {
+            texture: asset_server.load("playerShip1_orange.png"),
+            sprite: Sprite {
+                color: Color::rgb(1.0, 0.3, 0.3),
+                ..default()
+            },
+            transform: Transform::from_translation(origin.extend(0.0))
+                .with_scale(Vec3::splat(scale)),
+            ..default()
+        },
+        RigidBody::Kinematic,
+        Collider::ball(40.0),
+        //hit by the ship and its lasers, but flies over asteroids
+        CollisionLayers::new([Layer::Enemy], [Layer::Player, Layer::PlayerBullet]),
+        Ufo { health },
+        Cooldown::<UfoGun>::new(Duration::from_secs(2)),
+        GameEntity,
+    ))
+}
+
+fn chase_ship(
+    settings: Res<UfoSettings>,
+    ships: Query<&Transform, With<Ship>>,
+    mut ufos: Query<(&mut Transform, &mut LinearVelocity), (With<Ufo>, Without<Ship>)>,
+) {
+    let Ok(ship) = ships.get_single() else {
+        return;
+    };
+    for (mut transform, mut velocity) in ufos.iter_mut() {
+        let to_ship = ship.translation.xy() - transform.translation.xy();
+        velocity.0 = to_ship.normalize_or_zero() * settings.speed;
+        //point the nose at the ship
+        transform.rotation = Quat::from_rotation_z(to_ship.y.atan2(to_ship.x) - PI / 2.0);
+    }
+}
+
+fn ufo_fire(
+    settings: Res<UfoSettings>,
+    ships: Query<&Transform, With<Ship>>,
+    mut ufos: Query<(&Transform, &mut Cooldown<UfoGun>), Without<Ship>>,
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+) {
+    let Ok(ship) = ships.get_single() else {
+        return;
+    };
+    for (transform, mut gun) in ufos.iter_mut() {
+        if !gun.trigger() {
+            continue;
+        }
+        let direction = (ship.translation.xy() - transform.translation.xy()).normalize_or_zero();
+        let angle = direction.y.atan2(direction.x);
+        commands.spawn((
+            SpriteBundle {
+                texture: asset_server.load("lasers/laserBlue01.png"),
+                sprite: Sprite {
+                    color: Color::rgb(1.0, 0.2, 0.2),
+                    ..default()
+                },
+                transform: transform
+                    .with_rotation(Quat::from_rotation_z(angle - PI / 2.0))
+                    .with_scale(Vec3::ONE),
+                ..default()
+            },
+            RigidBody::Kinematic,
+            Collider::capsule(40.0, 6.0),
+            LinearVelocity(direction * settings.shot_speed),
+            CollisionLayers::new([Layer::EnemyBullet], [Layer::Player, Layer::Asteroid]),
+            Lifetime(Timer::from_seconds(4.0, TimerMode::Once)),
+            EnemyLaser,
+            GameEntity,
+        ));
+    }
+}
+
+fn handle_ufo_collisions(
+    mut events: EventReader<Collision>,
+    ufos: Query<&Ufo>,
+    lasers: Query<&Damage, With<Laser>>,
+    mut piercing: Query<(&mut Piercing, &mut PiercedTargets)>,
+    enemy_lasers: Query<(), With<EnemyLaser>>,
+    ships: Query<(), With<Ship>>,
+    asteroids: Query<Option<&Armored>, Or<(With<AsteroidClass>, With<WeakSpot>)>>,
+    mut damage_writer: EventWriter<DamageEvent>,
+    mut commands: Commands,
+) {
+    for event in events.read() {
+        let pair = [
+            (event.0.entity1, event.0.entity2),
+            (event.0.entity2, event.0.entity1),
+        ];
+        for (a, b) in pair {
+            //player lasers chip away at the ufo
+            if let (true, Ok(damage)) = (ufos.contains(a), lasers.get(b)) {
+                if spend_laser(b, a, &mut piercing, &mut commands) {
+                    damage_writer.send(DamageEvent {
+                        target: a,
+                        amount: damage.0,
+                        source: b,
+                    });
+                }
+            }
+            //ufo shots and rams hurt the ship like an asteroid would
+            let enemy_shot = enemy_lasers.contains(b);
+            if ships.contains(a) && (enemy_shot || ufos.contains(b)) {
+                if enemy_shot {
+                    commands.entity(b).despawn_recursive();
+                } else if let Ok(ufo) = ufos.get(b) {
+                    //the ufo doesn't survive ramming the ship either
+                    damage_writer.send(DamageEvent {
+                        target: b,
+                        amount: ufo.health,
+                        source: a,
+                    });
+                }
+                damage_writer.send(DamageEvent {
+                    target: a,
+                    amount: 1,
+                    source: b,
+                });
+            }
+            //stray ufo shots break up on asteroids, armor and weak spots just stop them
+            if let (Ok(armored), true) = (asteroids.get(a), enemy_shot) {
+                commands.entity(b).despawn_recursive();
+                if armored.is_none() {
+                    damage_writer.send(DamageEvent {
+                        target: a,
+                        amount: 1,
+                        source: b,
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn graze_bolts(
+    settings: Res<GrazeSettings>,
+    ships: Query<&Transform, With<Ship>>,
+    bolts: Query<(Entity, &Transform, Option<&Graze>), With<EnemyLaser>>,
+    combo: Res<Combo>,
+    mut combo_timer: ResMut<ComboTimer>,
+    mut score_writer: EventWriter<ScoreEvent>,
+    mut graze_writer: EventWriter<GrazeEvent>,
+    mut commands: Commands,
+) {
+    let Ok(ship) = ships.get_single() else {
+        return;
+    };
+    for (entity, transform, graze) in bolts.iter() {
+        let position = transform.translation.xy();
+        let close = position.distance(ship.translation.xy()) < settings.radius;
+        match (graze, close) {
+            (None, true) => {
+                commands.entity(entity).insert(Graze::Close);
+            }
+            (Some(Graze::Close), false) => {
+                commands.entity(entity).insert(Graze::Scored);
+                //grazes score with a running combo and keep it going, but don't build it up
+                if combo.multiplier > 0 {
+                    combo_timer.0.reset();
+                }
+                score_writer.send(ScoreEvent {
+                    points: settings.points * combo.multiplier.max(1),
+                    position,
+                });
+                graze_writer.send(GrazeEvent { position });
+                commands.spawn((
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: Color::rgb(0.8, 0.9, 1.0),
+                            custom_size: Some(Vec2::splat(6.0)),
+                            ..default()
+                        },
+                        transform: Transform::from_translation(position.extend(1.0)),
+                        ..default()
+                    },
+                    Lifetime(Timer::from_seconds(0.2, TimerMode::Once)),
+                    FadeOut(0.8),
+                    GameEntity,
+                ));
+            }
+            _ => {}
+        }
+    }
+}
+
+fn destroy_ufos(
+    ufos: Query<(Entity, &Ufo, &Transform)>,
+    settings: Res<UfoSettings>,
+    mut destroyed_writer: EventWriter<UfoDestroyedEvent>,
+    mut spark_writer: EventWriter<SpawnSparksEvent>,
+    mut explosion_writer: EventWriter<SpawnExplosionEvent>,
+    mut commands: Commands,
+) {
+    for (entity, ufo, transform) in ufos.iter() {
+        if ufo.health > 0 {
+            continue;
+        }
+        commands.entity(entity).despawn_recursive();
+        let position = transform.translation.xy();
+        for n in 0..8 {
+            let angle = n as f32 * PI / 4.0;
+            spark_writer.send(SpawnSparksEvent {
+                position,
+                normal: Vec2::new(angle.cos(), angle.sin()),
+            });
+        }
+        explosion_writer.send(SpawnExplosionEvent {
+            position,
+            scale: 1.5,
+        });
+        destroyed_writer.send(UfoDestroyedEvent {
+            position,
+            points: settings.points,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::event::ManualEventReader;
+
+    use super::*;
+
+    fn graze_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<GrazeSettings>()
+            .init_resource::<Combo>()
+            .init_resource::<ComboTimer>()
+            .add_event::<ScoreEvent>()
+            .add_event::<GrazeEvent>()
+            .add_systems(Update, graze_bolts);
+        app.world.spawn((Ship, Transform::default()));
+        app
+    }
+
+    fn bolt_at(app: &mut App, position: Vec2) -> Entity {
+        app.world
+            .spawn((
+                EnemyLaser,
+                Transform::from_translation(position.extend(0.0)),
+            ))
+            .id()
+    }
+
+    fn move_to(app: &mut App, bolt: Entity, position: Vec2) {
+        app.world.get_mut::<Transform>(bolt).unwrap().translation = position.extend(0.0);
+    }
+
+    #[test]
+    fn a_ufo_closes_in_on_the_ship() {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            TransformPlugin,
+            HierarchyPlugin,
+            PhysicsPlugins::default(),
+        ))
+        .insert_resource(bevy::time::TimeUpdateStrategy::ManualDuration(
+            Duration::from_millis(16),
+        ))
+        .init_resource::<UfoSettings>()
+        .add_systems(Update, chase_ship);
+        app.world.spawn((Ship, TransformBundle::default()));
+        let ufo = app
+            .world
+            .spawn((
+                Ufo { health: 6 },
+                RigidBody::Kinematic,
+                TransformBundle::from_transform(Transform::from_xyz(300.0, 200.0, 0.0)),
+            ))
+            .id();
+        let distance = |app: &App| {
+            let position = app.world.get::<Transform>(ufo).unwrap().translation;
+            position.xy().length()
+        };
+        let start = distance(&app);
+        let mut last = start;
+        for _ in 0..30 {
+            app.update();
+            let now = distance(&app);
+            assert!(now <= last);
+            last = now;
+        }
+        assert!(last < start - 10.0);
+    }
+
+    #[test]
+    fn only_enemy_bolts_hurt_the_player() {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            TransformPlugin,
+            HierarchyPlugin,
+            PhysicsPlugins::default(),
+        ))
+        .init_asset::<Image>()
+        .insert_resource(bevy::time::TimeUpdateStrategy::ManualDuration(
+            Duration::from_millis(16),
+        ))
+        .insert_resource(Gravity(Vec2::ZERO))
+        .init_resource::<UfoSettings>()
+        .add_event::<DamageEvent>()
+        .add_event::<SpawnSparksEvent>()
+        .add_systems(
+            Update,
+            (
+                ufo_fire,
+                crate::collisions::handle_collisions,
+                handle_ufo_collisions,
+            )
+                .chain(),
+        );
+        //the real bundles, so the test goes through the layers the game uses
+        let ship = app
+            .world
+            .spawn((
+                Ship,
+                crate::movement::ShipControllerBundle::default(),
+                TransformBundle::default(),
+            ))
+            .id();
+        let own_laser = app
+            .world
+            .spawn(crate::weapons::LaserBoltBundle::default())
+            .id();
+        let mut collisions = ManualEventReader::<Collision>::default();
+        let mut damage = ManualEventReader::<DamageEvent>::default();
+        let mut touched_own_laser = false;
+        let mut hits = Vec::new();
+        let mut run = |app: &mut App, frames: usize| {
+            for _ in 0..frames {
+                app.update();
+                let events = app.world.resource::<Events<Collision>>();
+                touched_own_laser |= collisions
+                    .read(events)
+                    .any(|event| [event.0.entity1, event.0.entity2].contains(&own_laser));
+                let events = app.world.resource::<Events<DamageEvent>>();
+                hits.extend(
+                    damage
+                        .read(events)
+                        .map(|event| (event.target, event.source)),
+                );
+            }
+        };
+
+        //sitting right on top of its own laser
+        run(&mut app, 5);
+
+        //a ufo just above the ship fires straight down at it
+        app.world.spawn((
+            Ufo { health: 6 },
+            Cooldown::<UfoGun>::new(Duration::from_secs(2)),
+            TransformBundle::from_transform(Transform::from_xyz(0.0, 60.0, 0.0)),
+        ));
+        run(&mut app, 10);
+
+        assert!(!touched_own_laser);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].0, ship);
+        assert_ne!(hits[0].1, own_laser);
+    }
+
+    #[test]
+    fn a_near_miss_scores_once() {
+        let mut app = graze_app();
+        let mut reader = ManualEventReader::<ScoreEvent>::default();
+        let bolt = bolt_at(&mut app, Vec2::new(-200.0, 40.0));
+        for x in [-200.0, 0.0, 10.0, 200.0, 400.0] {
+            move_to(&mut app, bolt, Vec2::new(x, 40.0));
+            app.update();
+        }
+        let events = app.world.resource::<Events<ScoreEvent>>();
+        let points: Vec<u32> = reader.read(events).map(|event| event.points).collect();
+        assert_eq!(points, vec![GrazeSettings::default().points]);
+    }
+
+    #[test]
+    fn a_bolt_that_never_leaves_does_not_score() {
+        let mut app = graze_app();
+        let mut reader = ManualEventReader::<ScoreEvent>::default();
+        bolt_at(&mut app, Vec2::new(0.0, 10.0));
+        app.update();
+        app.update();
+        let events = app.world.resource::<Events<ScoreEvent>>();
+        assert_eq!(reader.read(events).count(), 0);
+    }
+}
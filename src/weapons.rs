@@ -0,0 +1,1506 @@
+use std::{f32::consts::PI, time::Duration};
+
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::MeshVertexBufferLayout,
+        render_resource::{
+            AsBindGroup, BlendComponent, BlendFactor, BlendOperation, BlendState,
+            RenderPipelineDescriptor, ShaderRef, SpecializedMeshPipelineError,
+        },
+    },
+    sprite::{Material2d, Material2dKey, Material2dPlugin, MaterialMesh2dBundle, Mesh2dHandle},
+};
+use bevy_xpbd_2d::prelude::*;
+
+use crate::{
+    asteroids::{AsteroidHealth, SpawnAsteroidEvent},
+    atlas::{SpriteAtlas, LASER_SPRITE},
+    collisions::{apply_damage, DamageEvent, Layer},
+    cooldown::{tick_cooldowns, Cooldown},
+    explosion::{Explosion, ExplosionSettings, SpawnExplosionEvent},
+    input::{gamepad_input, proto_input, AimTarget, InputAction},
+    lifetime::{despawn_expired, FadeOut, Lifetime},
+    missile::SpawnMissileEvent,
+    movement::PlayArea,
+    run::{Difficulty, GameEntity, GameRng, GameRngSet, GameState},
+    score::award_points,
+    ship::{Ship, ShipHealth},
+};
+
+/// The ship's guns: firing modes, rate of fire, upgrades and the lasers they spawn. Each gun is a
+/// [`Weapon`] child of the ship with its own kind, rate of fire and cooldown.
+pub struct WeaponsPlugin;
+
+impl Plugin for WeaponsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            update_weapons
+                .after(proto_input)
+                .after(gamepad_input)
+                .after(tick_cooldowns::<Laser>)
+                .run_if(not(in_state(GameState::Paused))),
+        )
+        .add_systems(Update, tick_cooldowns::<Laser>)
+        .add_systems(Update, apply_rate_of_fire.before(update_weapons))
+        .init_resource::<MuzzleSettings>()
+        .add_event::<SpawnLaserEvent>()
+        .add_systems(Update, laser_spawner.after(update_weapons))
+        .init_resource::<LaserPool>()
+        .init_resource::<LaserPiercing>()
+        .add_systems(
+            PostUpdate,
+            recycle_lasers
+                .after(despawn_expired)
+                .before(PhysicsSet::Prepare),
+        )
+        .add_systems(Update, spawn_muzzle_flashes.after(update_weapons))
+        .add_systems(Update, shrink_muzzle_flashes)
+        .init_resource::<TrailSettings>()
+        .add_systems(
+            Update,
+            spawn_laser_trails.run_if(not(in_state(GameState::Paused))),
+        )
+        .init_resource::<AimMode>()
+        .init_resource::<AimLine>()
+        .add_systems(Update, toggle_aim_line)
+        .add_systems(
+            Update,
+            draw_aim_line
+                .after(toggle_aim_line)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_plugins(Material2dPlugin::<AdditiveMaterial>::default())
+        .init_resource::<VisualStyle>()
+        .add_systems(Startup, setup_additive_assets)
+        .add_systems(Update, toggle_visual_style.before(laser_spawner))
+        .add_systems(Update, toggle_fire_pattern.before(update_weapons))
+        .add_systems(
+            Update,
+            select_weapon_kind
+                .before(apply_rate_of_fire)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(Update, toggle_aim_mode.before(laser_spawner))
+        .add_event::<WeaponUpgrade>()
+        .add_systems(
+            Update,
+            handle_upgrades
+                .in_set(GameRngSet::Upgrades)
+                .after(award_points),
+        )
+        .init_resource::<Bombs>()
+        .add_event::<ScreenClearEvent>()
+        .add_systems(Update, tick_cooldowns::<Bomb>)
+        .add_systems(
+            Update,
+            (use_bomb, clear_screen)
+                .chain()
+                .after(tick_cooldowns::<Bomb>)
+                .after(proto_input)
+                .after(gamepad_input)
+                .before(apply_damage)
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// Bombs the ship has left, each one wipes out every asteroid in the play area.
+#[derive(Resource)]
+pub struct Bombs(pub u8);
+
+impl Default for Bombs {
+    fn default() -> Self {
+        Self(2)
+    }
+}
+
+/// Marks the short cooldown between bombs, so one long press can't spend them all.
+pub enum Bomb {}
+
+/// Sent when a bomb goes off, `source` is the ship that set it off.
+#[derive(Event)]
+pub struct ScreenClearEvent {
+    pub source: Entity,
+}
+
+fn use_bomb(
+    mut input_event_reader: EventReader<InputAction>,
+    mut bombs: ResMut<Bombs>,
+    mut ships: Query<(Entity, &mut Cooldown<Bomb>), With<Ship>>,
+    mut clear_writer: EventWriter<ScreenClearEvent>,
+) {
+    //holding a few inputs in one frame still only sets off one bomb
+    let pressed = input_event_reader
+        .read()
+        .any(|event| matches!(event, InputAction::Bomb));
+    let Ok((ship, mut cooldown)) = ships.get_single_mut() else {
+        return;
+    };
+    if !pressed || bombs.0 == 0 || !cooldown.trigger() {
+        return;
+    }
+    bombs.0 -= 1;
+    info!("bomb, {} left", bombs.0);
+    clear_writer.send(ScreenClearEvent { source: ship });
+}
+
+fn clear_screen(
+    mut events: EventReader<ScreenClearEvent>,
+    play_area: Res<PlayArea>,
+    explosion_settings: Res<ExplosionSettings>,
+    asteroids: Query<(Entity, &AsteroidHealth, &Transform)>,
+    mut damage_writer: EventWriter<DamageEvent>,
+    mut explosion_writer: EventWriter<SpawnExplosionEvent>,
+    mut commands: Commands,
+) {
+    for event in events.read() {
+        for (entity, health, transform) in asteroids.iter() {
+            let position = transform.translation.xy();
+            //the ones still coming in from beyond the edge are spared
+            if position.abs().cmpgt(play_area.half_extents).any() {
+                continue;
+            }
+            //armor doesn't help against a bomb
+            damage_writer.send(DamageEvent {
+                target: entity,
+                amount: health.0.max(1),
+                source: event.source,
+            });
+            explosion_writer.send(SpawnExplosionEvent {
+                position,
+                scale: 1.0,
+            });
+            //every one of them blows up for real, setting one off too close to the ship hurts
+            commands.spawn((
+                TransformBundle::from_transform(Transform::from_translation(position.extend(0.0))),
+                Explosion {
+                    radius: explosion_settings.bomb_radius,
+                    damage: explosion_settings.bomb_damage,
+                    impulse: explosion_settings.bomb_impulse,
+                    hurts_ship: true,
+                },
+                Lifetime(Timer::from_seconds(0.1, TimerMode::Once)),
+                GameEntity,
+            ));
+        }
+    }
+}
+
+//this is in rounds per minute
+#[derive(Component)]
+pub struct RateOfFire(pub f32);
+
+impl RateOfFire {
+    /// The time between two shots.
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs_f32(60.0 / self.0.max(1.0))
+    }
+}
+
+fn apply_rate_of_fire(
+    mut weapons: Query<
+        (&RateOfFire, &WeaponKind, &mut Cooldown<Laser>),
+        Or<(Changed<RateOfFire>, Changed<WeaponKind>)>,
+    >,
+) {
+    for (rate, kind, mut cooldown) in weapons.iter_mut() {
+        cooldown.set_duration(rate.interval().div_f32(kind.rate_multiplier()));
+    }
+}
+
+//this is in rounds per minute
+#[derive(Component, Default)]
+pub struct ProjectileCount(pub u128);
+
+/// Which slot of the ship a weapon child sits in. Number keys and pickups only change the
+/// primary one, all of them fire together.
+#[derive(Component, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Weapon {
+    #[default]
+    Primary,
+    Secondary,
+}
+
+//bundle for the laser weapon, spawned as a child of the ship
+#[derive(Bundle)]
+pub struct LaserWeaponBundle {
+    pub weapon: Weapon,
+    pub rate_of_fire: RateOfFire,
+    pub fire_cooldown: Cooldown<Laser>,
+    pub projectile_count: ProjectileCount,
+    pub muzzles: MuzzlePoints,
+    pub pattern: FirePattern,
+    pub kind: WeaponKind,
+    pub charge: ChargeLevel,
+}
+
+impl Default for LaserWeaponBundle {
+    fn default() -> Self {
+        Self {
+            weapon: Weapon::Primary,
+            rate_of_fire: RateOfFire(120.0),
+            fire_cooldown: Cooldown::new(RateOfFire(120.0).interval()),
+            projectile_count: ProjectileCount(1),
+            muzzles: MuzzlePoints::default(),
+            pattern: FirePattern::default(),
+            kind: WeaponKind::default(),
+            charge: ChargeLevel::default(),
+        }
+    }
+}
+
+/// The ship's selectable weapon modes, switched with the number keys.
+#[derive(Component, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeaponKind {
+    #[default]
+    Single,
+    /// Fans every volley out into three directions.
+    Spread,
+    /// Fires much faster.
+    Rapid,
+    /// Holding fire charges up one big shot, fired on release.
+    Charge,
+    /// Slow firing homing missiles that blow up on contact.
+    Missile,
+}
+
+impl WeaponKind {
+    /// The angles each volley is repeated at.
+    pub fn fan_angles(&self) -> &'static [f32] {
+        const SPREAD: f32 = 15.0 * PI / 180.0;
+        match self {
+            WeaponKind::Spread => &[-SPREAD, 0.0, SPREAD],
+            WeaponKind::Single | WeaponKind::Rapid | WeaponKind::Charge | WeaponKind::Missile => {
+                &[0.0]
+            }
+        }
+    }
+
+    /// Scales the weapon's [`RateOfFire`].
+    pub fn rate_multiplier(&self) -> f32 {
+        match self {
+            WeaponKind::Single | WeaponKind::Charge => 1.0,
+            //three bolts at once, a little slower to make up for it
+            WeaponKind::Spread => 0.75,
+            WeaponKind::Rapid => 3.0,
+            WeaponKind::Missile => 0.25,
+        }
+    }
+}
+
+fn select_weapon_kind(key: Res<Input<KeyCode>>, mut kinds: Query<(&Weapon, &mut WeaponKind)>) {
+    let kind = if key.just_pressed(KeyCode::Key1) {
+        WeaponKind::Single
+    } else if key.just_pressed(KeyCode::Key2) {
+        WeaponKind::Spread
+    } else if key.just_pressed(KeyCode::Key3) {
+        WeaponKind::Rapid
+    } else if key.just_pressed(KeyCode::Key4) {
+        WeaponKind::Charge
+    } else if key.just_pressed(KeyCode::Key5) {
+        WeaponKind::Missile
+    } else {
+        return;
+    };
+    for (weapon, mut current) in kinds.iter_mut() {
+        if *weapon == Weapon::Primary && *current != kind {
+            *current = kind;
+            info!("weapon: {:?}", kind);
+        }
+    }
+}
+
+/// How a weapon with several muzzles uses them.
+#[derive(Component, Default, Debug, Clone, Copy)]
+pub enum FirePattern {
+    /// Every muzzle fires each shot.
+    #[default]
+    Salvo,
+    /// One muzzle per shot, cycling through them in order.
+    Alternate { next: usize },
+}
+
+fn toggle_fire_pattern(
+    key: Res<Input<KeyCode>>,
+    mut patterns: Query<&mut FirePattern, With<Weapon>>,
+) {
+    if key.just_pressed(KeyCode::X) {
+        for mut pattern in patterns.iter_mut() {
+            *pattern = match *pattern {
+                FirePattern::Salvo => FirePattern::Alternate { next: 0 },
+                FirePattern::Alternate { .. } => FirePattern::Salvo,
+            };
+            info!("fire pattern: {:?}", *pattern);
+        }
+    }
+}
+
+/// How far ahead of the ship's center its nose is, where the guns sit.
+#[derive(Resource)]
+pub struct MuzzleSettings {
+    pub distance: f32,
+}
+
+impl Default for MuzzleSettings {
+    fn default() -> Self {
+        //the tip of the half scale ship sprite
+        Self { distance: 38.0 }
+    }
+}
+
+/// Where bolts leave the ship, as offsets from its nose in the ship's local space (up is forward).
+#[derive(Component)]
+pub struct MuzzlePoints(pub Vec<Vec2>);
+
+impl Default for MuzzlePoints {
+    fn default() -> Self {
+        //a single gun right on the nose
+        Self(vec![Vec2::ZERO])
+    }
+}
+
+impl MuzzlePoints {
+    /// The world position of each muzzle, rotated by the ship's facing.
+    pub fn world_positions<'a>(
+        &'a self,
+        transform: &'a Transform,
+        nose_distance: f32,
+    ) -> impl Iterator<Item = Vec2> + 'a {
+        self.0.iter().map(move |offset| {
+            let local = *offset + Vec2::Y * nose_distance;
+            transform.translation.xy() + transform.rotation.mul_vec3(local.extend(0.0)).xy()
+        })
+    }
+}
+
+#[derive(Component)]
+pub struct Spread(f32);
+
+/// An event sent for a firing a laser
+#[derive(Event)]
+pub struct SpawnLaserEvent {
+    origin: Transform,
+    spread: Spread,
+    //how charged the shot is, 0 for a normal bolt
+    charge: f32,
+    //the ship's velocity when it fired, carried along by the bolt
+    inherited_velocity: Vec2,
+}
+
+#[cfg(test)]
+impl SpawnLaserEvent {
+    /// A plain bolt fired from `origin`, for tests that only need one to be fired.
+    pub(crate) fn plain(origin: Transform) -> Self {
+        Self {
+            origin,
+            spread: Spread(0.0),
+            charge: 0.0,
+            inherited_velocity: Vec2::ZERO,
+        }
+    }
+}
+
+/// The angle offsets of each bolt in a volley of `count`, fanned out evenly around zero.
+pub fn spread_angles(count: u128) -> impl Iterator<Item = f32> {
+    let step = 2.5 * PI / 180.0;
+    let spread = step * count.saturating_sub(1) as f32;
+    let left = -spread / 2.0;
+    (0..count).map(move |x| left + (step * x as f32))
+}
+
+pub fn update_weapons(
+    time: Res<Time>,
+    mut input_event_reader: EventReader<InputAction>,
+    mut weapon_query: Query<
+        (
+            &Parent,
+            &mut Cooldown<Laser>,
+            &ProjectileCount,
+            &MuzzlePoints,
+            &mut FirePattern,
+            &WeaponKind,
+            &mut ChargeLevel,
+        ),
+        With<Weapon>,
+    >,
+    ship_query: Query<(&Transform, &LinearVelocity), With<Ship>>,
+    mut fire_laser_event_writer: EventWriter<SpawnLaserEvent>,
+    mut missile_writer: EventWriter<SpawnMissileEvent>,
+    muzzle_settings: Res<MuzzleSettings>,
+) {
+    //several input sources can ask to fire in one frame, it still only counts once
+    let fire = input_event_reader
+        .read()
+        .any(|event| matches!(event, InputAction::Fire));
+    //every weapon keeps its own cooldown, so each fires on its own schedule
+    for mut weapon in weapon_query.iter_mut() {
+        let Ok(ship) = ship_query.get(weapon.0.get()) else {
+            continue;
+        };
+        //charge weapons wind up while fire is held and shoot once it is let go
+        let charge = if *weapon.5 == WeaponKind::Charge {
+            if fire {
+                weapon.6 .0 = (weapon.6 .0 + time.delta_seconds() / FULL_CHARGE_TIME).min(1.0);
+                continue;
+            }
+            let charge = std::mem::take(&mut weapon.6 .0);
+            //a tap is not enough to count as a shot
+            if charge < 0.1 {
+                continue;
+            }
+            charge
+        } else if fire {
+            0.0
+        } else {
+            continue;
+        };
+        //if the cooldown is over we can pew
+        if weapon.1.trigger() {
+            let muzzles: Vec<Vec2> = weapon
+                .3
+                .world_positions(ship.0, muzzle_settings.distance)
+                .collect();
+            let firing = match &mut *weapon.4 {
+                FirePattern::Salvo => &muzzles[..],
+                FirePattern::Alternate { next } => {
+                    let index = *next % muzzles.len().max(1);
+                    *next = index + 1;
+                    &muzzles[index..(index + 1).min(muzzles.len())]
+                }
+            };
+            for &muzzle in firing {
+                let origin = ship.0.with_translation(muzzle.extend(0.0));
+                if *weapon.5 == WeaponKind::Missile {
+                    missile_writer.send(SpawnMissileEvent {
+                        origin,
+                        inherited_velocity: ship.1 .0,
+                    });
+                    continue;
+                }
+                if charge > 0.0 {
+                    //the charge goes into one big bolt
+                    fire_laser_event_writer.send(SpawnLaserEvent {
+                        origin,
+                        spread: Spread(0.0),
+                        charge,
+                        inherited_velocity: ship.1 .0,
+                    });
+                    continue;
+                }
+                //each firing muzzle fires the full volley in every direction of the fan
+                for fan in weapon.5.fan_angles() {
+                    for angle in spread_angles(weapon.2 .0) {
+                        fire_laser_event_writer.send(SpawnLaserEvent {
+                            origin,
+                            spread: Spread(fan + angle),
+                            charge: 0.0,
+                            inherited_velocity: ship.1 .0,
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Laser;
+
+/// How much health a laser takes off whatever it hits.
+#[derive(Component)]
+pub struct Damage(pub i32);
+
+/// Lets a laser pass through this many more asteroids before it is used up.
+#[derive(Component)]
+pub struct Piercing(pub u8);
+
+/// Everything a piercing laser already went through.
+#[derive(Component, Default)]
+pub struct PiercedTargets(pub Vec<Entity>);
+
+/// Asteroids every laser fired passes through on top of what its charge gives it, set by the
+/// lasers pierce mutator.
+#[derive(Resource, Default)]
+pub struct LaserPiercing(pub u8);
+
+/// How far a charge weapon has been wound up, from 0 to 1.
+#[derive(Component, Default)]
+pub struct ChargeLevel(pub f32);
+
+/// Seconds of holding fire it takes to fully charge a shot.
+pub const FULL_CHARGE_TIME: f32 = 1.0;
+
+#[derive(Bundle)]
+pub struct LaserBoltBundle {
+    sprite_bundle: SpriteBundle,
+    rigid_body: RigidBody,
+    collider: Collider,
+    linear_velocity: LinearVelocity,
+    lifetime: Lifetime,
+    layer: CollisionLayers,
+    laser: Laser,
+    damage: Damage,
+    tag: GameEntity,
+}
+
+impl Default for LaserBoltBundle {
+    fn default() -> Self {
+        Self {
+            sprite_bundle: Default::default(),
+            rigid_body: RigidBody::Dynamic,
+            collider: Collider::capsule(40.0, 6.0),
+            linear_velocity: Default::default(),
+            lifetime: Lifetime(Timer::new(Duration::from_secs(5), TimerMode::Once)),
+            layer: CollisionLayers::new([Layer::PlayerBullet], [Layer::Asteroid, Layer::Enemy]),
+            laser: Laser,
+            damage: Damage(1),
+            tag: GameEntity,
+        }
+    }
+}
+
+/// Whether lasers fire along the ship's facing or straight at the cursor.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AimMode {
+    #[default]
+    ShipFacing,
+    Cursor,
+}
+
+impl AimMode {
+    /// The angle bolts fired from `origin` travel at before any spread is added.
+    pub fn angle(&self, origin: &Transform, target: Vec2) -> f32 {
+        match self {
+            AimMode::ShipFacing => {
+                //the sprites point up, like in look_at_aim_target
+                let forward = (origin.rotation * Vec3::Y).truncate();
+                forward.y.atan2(forward.x)
+            }
+            AimMode::Cursor => {
+                let diff = target - origin.translation.xy();
+                diff.y.atan2(diff.x)
+            }
+        }
+    }
+}
+
+fn toggle_aim_mode(key: Res<Input<KeyCode>>, mut aim_mode: ResMut<AimMode>) {
+    if key.just_pressed(KeyCode::C) {
+        *aim_mode = match *aim_mode {
+            AimMode::ShipFacing => AimMode::Cursor,
+            AimMode::Cursor => AimMode::ShipFacing,
+        };
+        info!("aim mode: {:?}", *aim_mode);
+    }
+}
+
+/// What the aim line in front of the ship shows, switched with V: the straight ray bolts
+/// leave along, or the path a bolt would really take with the ship's velocity added in.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AimLine {
+    #[default]
+    Ray,
+    Projectile,
+}
+
+const AIM_LINE_LENGTH: f32 = 300.0;
+//how far ahead the projectile preview looks, and in how many steps
+const AIM_PREVIEW_TIME: f32 = 0.6;
+const AIM_PREVIEW_STEPS: usize = 12;
+const AIM_LINE_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.25);
+
+fn toggle_aim_line(key: Res<Input<KeyCode>>, mut aim_line: ResMut<AimLine>) {
+    if key.just_pressed(KeyCode::V) {
+        *aim_line = match *aim_line {
+            AimLine::Ray => AimLine::Projectile,
+            AimLine::Projectile => AimLine::Ray,
+        };
+        info!("aim line: {:?}", *aim_line);
+    }
+}
+
+/// Where a bolt leaving `origin` at `velocity` is at each of `steps` even steps over `duration`
+/// seconds, starting with `origin` itself.
+pub fn projectile_path(origin: Vec2, velocity: Vec2, duration: f32, steps: usize) -> Vec<Vec2> {
+    (0..=steps)
+        .map(|step| origin + velocity * duration * step as f32 / steps as f32)
+        .collect()
+}
+
+fn draw_aim_line(
+    mut gizmos: Gizmos,
+    aim_line: Res<AimLine>,
+    aim_mode: Res<AimMode>,
+    aim_target: Res<AimTarget>,
+    ships: Query<(&Transform, &LinearVelocity), With<Ship>>,
+) {
+    let Ok((transform, ship_velocity)) = ships.get_single() else {
+        return;
+    };
+    let origin = transform.translation.xy();
+    let angle = aim_mode.angle(transform, aim_target.0);
+    let direction = Vec2::new(angle.cos(), angle.sin());
+    match *aim_line {
+        AimLine::Ray => {
+            gizmos.line_2d(origin, origin + direction * AIM_LINE_LENGTH, AIM_LINE_COLOR);
+        }
+        AimLine::Projectile => {
+            let velocity = bolt_velocity(direction, LASER_SPEED, ship_velocity.0);
+            let path = projectile_path(origin, velocity, AIM_PREVIEW_TIME, AIM_PREVIEW_STEPS);
+            gizmos.linestrip_2d(path, AIM_LINE_COLOR);
+        }
+    }
+}
+
+/// Draws a texture with additive blending so overlapping effects glow brighter. `uv_rect` picks
+/// the part of the texture to draw, as offset in `xy` and size in `zw`.
+#[derive(Asset, TypePath, AsBindGroup, Clone)]
+pub struct AdditiveMaterial {
+    #[uniform(0)]
+    pub color: Color,
+    #[uniform(0)]
+    pub uv_rect: Vec4,
+    #[texture(1)]
+    #[sampler(2)]
+    pub texture: Handle<Image>,
+}
+
+impl AdditiveMaterial {
+    /// Draws all of `texture`.
+    pub fn new(color: Color, texture: Handle<Image>) -> Self {
+        Self {
+            color,
+            uv_rect: Vec4::new(0.0, 0.0, 1.0, 1.0),
+            texture,
+        }
+    }
+}
+
+impl Material2d for AdditiveMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/additive.wgsl".into()
+    }
+
+    fn specialize(
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayout,
+        _key: Material2dKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        if let Some(fragment) = descriptor.fragment.as_mut() {
+            for target in fragment.targets.iter_mut().flatten() {
+                target.blend = Some(BlendState {
+                    color: BlendComponent {
+                        src_factor: BlendFactor::SrcAlpha,
+                        dst_factor: BlendFactor::One,
+                        operation: BlendOperation::Add,
+                    },
+                    alpha: BlendComponent::OVER,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Visual options for how effects are drawn. F8 toggles additive blending for lasers, muzzle
+/// flashes and explosions.
+#[derive(Resource, Default)]
+pub struct VisualStyle {
+    pub additive: bool,
+}
+
+/// The meshes and materials every additive laser and muzzle flash shares, so they can be
+/// batched.
+#[derive(Resource)]
+pub struct AdditiveAssets {
+    laser_mesh: Mesh2dHandle,
+    laser_material: Handle<AdditiveMaterial>,
+    flash_mesh: Mesh2dHandle,
+    flash_material: Handle<AdditiveMaterial>,
+}
+
+fn setup_additive_assets(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<AdditiveMaterial>>,
+) {
+    commands.insert_resource(AdditiveAssets {
+        //the size of laserBlue01.png
+        laser_mesh: meshes
+            .add(shape::Quad::new(Vec2::new(9.0, 54.0)).into())
+            .into(),
+        laser_material: materials.add(AdditiveMaterial::new(
+            Color::WHITE,
+            asset_server.load("lasers/laserBlue01.png"),
+        )),
+        flash_mesh: meshes
+            .add(shape::Quad::new(MUZZLE_FLASH_SIZE).into())
+            .into(),
+        //the default image is plain white, so this is just the color
+        flash_material: materials.add(AdditiveMaterial::new(MUZZLE_FLASH_COLOR, Handle::default())),
+    });
+}
+
+fn toggle_visual_style(key: Res<Input<KeyCode>>, mut style: ResMut<VisualStyle>) {
+    if key.just_pressed(KeyCode::F8) {
+        style.additive = !style.additive;
+        info!("additive blending: {}", style.additive);
+    }
+}
+
+//the least of a bolt's own speed it keeps along its direction, whatever the ship was doing
+const MIN_FORWARD_FRACTION: f32 = 0.5;
+//how fast an uncharged bolt leaves the muzzle
+const LASER_SPEED: f32 = 500.0;
+
+/// How fast a bolt fired along `direction` at `speed` travels once the ship's velocity is
+/// added in.
+pub fn bolt_velocity(direction: Vec2, speed: f32, inherited_velocity: Vec2) -> Vec2 {
+    let mut velocity = direction * speed + inherited_velocity;
+    //firing while flying backwards fast must still send the bolt forward
+    let forward = velocity.dot(direction);
+    let min_forward = speed * MIN_FORWARD_FRACTION;
+    if forward < min_forward {
+        velocity += direction * (min_forward - forward);
+    }
+    velocity
+}
+
+fn laser_spawner(
+    mut reader: EventReader<SpawnLaserEvent>,
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+    mut pool: ResMut<LaserPool>,
+    pooled: Query<(), With<Pooled>>,
+    aim_mode: Res<AimMode>,
+    aim_target: Res<AimTarget>,
+    style: Res<VisualStyle>,
+    additive: Res<AdditiveAssets>,
+    atlas: Option<Res<SpriteAtlas>>,
+    laser_piercing: Res<LaserPiercing>,
+) {
+    for event in reader.read() {
+        // info!("pew");
+        //spawn laser bolt
+        //speed
+        //charged bolts are bigger and faster
+        let speed = LASER_SPEED * (1.0 + event.charge);
+        let z_rot = event.spread.0 + aim_mode.angle(&event.origin, aim_target.0);
+        info!("spread:{}", event.spread.0);
+        let direction = Vec2::new(z_rot.cos(), z_rot.sin());
+        let velocity = bolt_velocity(direction, speed, event.inherited_velocity);
+        let bolt = LaserBoltBundle {
+            sprite_bundle: SpriteBundle {
+                texture: asset_server.load(LASER_SPRITE),
+                //point the bolt the way it travels
+                transform: event
+                    .origin
+                    .with_rotation(Quat::from_rotation_z(z_rot - PI / 2.0))
+                    .with_scale(Vec3::splat(1.0 + event.charge)),
+                ..Default::default()
+            },
+            linear_velocity: LinearVelocity(velocity),
+            //up to five times the damage at full charge
+            damage: Damage(1 + (event.charge * 4.0).round() as i32),
+            ..default()
+        };
+        let mut laser = match pool.take(&pooled) {
+            //the bundle overwrites everything a parked laser had changed, the rest is cleared
+            Some(entity) => {
+                let mut laser = commands.entity(entity);
+                laser
+                    .remove::<(
+                        Pooled,
+                        Piercing,
+                        PiercedTargets,
+                        Mesh2dHandle,
+                        Handle<AdditiveMaterial>,
+                        TextureAtlasSprite,
+                        Handle<TextureAtlas>,
+                    )>()
+                    .insert((bolt, AngularVelocity::ZERO));
+                laser
+            }
+            None => commands.spawn(bolt),
+        };
+        //only a full charge punches through on its own
+        let charge_piercing = if event.charge >= 1.0 { 2 } else { 0 };
+        let piercing = charge_piercing + laser_piercing.0;
+        if piercing > 0 {
+            laser.insert((Piercing(piercing), PiercedTargets::default()));
+        }
+        if style.additive {
+            //swap the sprite for the glowing mesh, everything else stays the same
+            laser
+                .remove::<(Sprite, Handle<Image>)>()
+                .insert((additive.laser_mesh.clone(), additive.laser_material.clone()));
+        } else if let Some(atlas) = &atlas {
+            atlas.swap_in(&mut laser, LASER_SPRITE);
+        }
+    }
+}
+
+/// Spent lasers kept around to be fired again, so holding the trigger doesn't spawn and
+/// despawn an entity for every bolt.
+#[derive(Resource)]
+pub struct LaserPool {
+    free: Vec<Entity>,
+    //lasers spent while the pool is full are despawned as before
+    pub max: usize,
+}
+
+impl Default for LaserPool {
+    fn default() -> Self {
+        Self {
+            free: Vec::new(),
+            max: 64,
+        }
+    }
+}
+
+impl LaserPool {
+    /// A parked laser to fire again. Parked lasers are despawned with the rest of a run, so
+    /// the ones that are gone are dropped on the way.
+    fn take(&mut self, pooled: &Query<(), With<Pooled>>) -> Option<Entity> {
+        while let Some(entity) = self.free.pop() {
+            if pooled.contains(entity) {
+                return Some(entity);
+            }
+        }
+        None
+    }
+}
+
+/// Marks a laser that hit something or ran out of time, to be parked in the [`LaserPool`]
+/// instead of despawned.
+#[derive(Component)]
+pub struct Retired;
+
+/// A laser parked in the [`LaserPool`]: hidden, out of the way and touching nothing.
+#[derive(Component)]
+pub struct Pooled;
+
+//far outside any play area
+const POOL_PARKING: Vec3 = Vec3::new(0.0, -100_000.0, 0.0);
+
+fn recycle_lasers(
+    mut pool: ResMut<LaserPool>,
+    mut lasers: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Visibility,
+            &mut LinearVelocity,
+            &mut CollisionLayers,
+        ),
+        With<Retired>,
+    >,
+    mut commands: Commands,
+) {
+    for (entity, mut transform, mut visibility, mut velocity, mut layers) in lasers.iter_mut() {
+        if pool.free.len() >= pool.max {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        transform.translation = POOL_PARKING;
+        *visibility = Visibility::Hidden;
+        velocity.0 = Vec2::ZERO;
+        *layers = CollisionLayers::none();
+        commands.entity(entity).remove::<Retired>().insert(Pooled);
+        pool.free.push(entity);
+    }
+}
+
+/// A short flash at the muzzle every time a bolt is fired, shrinking away from the given scale.
+#[derive(Component)]
+pub struct MuzzleFlash(f32);
+
+const MUZZLE_FLASH_TIME: Duration = Duration::from_millis(60);
+const MUZZLE_FLASH_SIZE: Vec2 = Vec2::new(16.0, 10.0);
+const MUZZLE_FLASH_COLOR: Color = Color::rgb(0.7, 0.9, 1.0);
+
+fn spawn_muzzle_flashes(
+    mut reader: EventReader<SpawnLaserEvent>,
+    style: Res<VisualStyle>,
+    additive: Res<AdditiveAssets>,
+    mut commands: Commands,
+) {
+    for event in reader.read() {
+        //charged shots get a bigger flash
+        let scale = 1.0 + event.charge;
+        //the origin carries the ship's rotation, so the flash turns with it
+        let transform = event.origin.with_scale(Vec3::splat(scale));
+        let mut flash = commands.spawn((
+            Lifetime(Timer::new(MUZZLE_FLASH_TIME, TimerMode::Once)),
+            MuzzleFlash(scale),
+            GameEntity,
+        ));
+        //an additive flash is a mesh and only shrinks, the sprite fades out as well
+        if style.additive {
+            flash.insert(MaterialMesh2dBundle {
+                mesh: additive.flash_mesh.clone(),
+                material: additive.flash_material.clone(),
+                transform,
+                ..default()
+            });
+        } else {
+            flash.insert((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: MUZZLE_FLASH_COLOR,
+                        custom_size: Some(MUZZLE_FLASH_SIZE),
+                        ..default()
+                    },
+                    transform,
+                    ..default()
+                },
+                FadeOut(1.0),
+            ));
+        }
+    }
+}
+
+fn shrink_muzzle_flashes(mut flashes: Query<(&mut Transform, &Lifetime, &MuzzleFlash)>) {
+    for (mut transform, lifetime, flash) in flashes.iter_mut() {
+        transform.scale = Vec3::splat(flash.0 * lifetime.0.percent_left());
+    }
+}
+
+/// Faint copies of fast lasers left behind every frame, so they don't seem to jump from one
+/// spot to the next.
+#[derive(Resource)]
+pub struct TrailSettings {
+    pub enabled: bool,
+    //slower lasers move little enough between frames to go without
+    pub min_speed: f32,
+    pub lifetime: Duration,
+    //how see-through a fresh copy is, fading out from there
+    pub alpha: f32,
+}
+
+impl Default for TrailSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_speed: 400.0,
+            lifetime: Duration::from_millis(80),
+            alpha: 0.35,
+        }
+    }
+}
+
+/// One of the copies a laser leaves behind.
+#[derive(Component)]
+pub struct LaserTrail;
+
+fn spawn_laser_trails(
+    settings: Res<TrailSettings>,
+    //additive lasers are meshes and glow enough on their own
+    lasers: Query<
+        (
+            &Transform,
+            &LinearVelocity,
+            AnyOf<(
+                (&Sprite, &Handle<Image>),
+                (&TextureAtlasSprite, &Handle<TextureAtlas>),
+            )>,
+        ),
+        With<Laser>,
+    >,
+    mut commands: Commands,
+) {
+    if !settings.enabled {
+        return;
+    }
+    for (transform, velocity, (image, packed)) in lasers.iter() {
+        if velocity.0.length() < settings.min_speed {
+            continue;
+        }
+        //just under the laser itself
+        let transform = transform.with_translation(transform.translation - Vec3::Z * 0.1);
+        let mut trail = match (image, packed) {
+            (Some((sprite, texture)), _) => commands.spawn(SpriteBundle {
+                texture: texture.clone(),
+                sprite: Sprite {
+                    color: sprite.color.with_a(settings.alpha),
+                    ..default()
+                },
+                transform,
+                ..default()
+            }),
+            (None, Some((sprite, atlas))) => commands.spawn(SpriteSheetBundle {
+                texture_atlas: atlas.clone(),
+                sprite: TextureAtlasSprite {
+                    color: sprite.color.with_a(settings.alpha),
+                    ..TextureAtlasSprite::new(sprite.index)
+                },
+                transform,
+                ..default()
+            }),
+            (None, None) => continue,
+        };
+        trail.insert((
+            Lifetime(Timer::new(settings.lifetime, TimerMode::Once)),
+            FadeOut(settings.alpha),
+            LaserTrail,
+            GameEntity,
+        ));
+    }
+}
+
+#[derive(Event)]
+pub struct WeaponUpgrade;
+
+fn handle_upgrades(
+    mut events: EventReader<WeaponUpgrade>,
+    mut laser_query: Query<&mut ProjectileCount>,
+    mut asteroid_events: EventWriter<SpawnAsteroidEvent>,
+    mut ship_query: Query<&mut ShipHealth>,
+    play_area: Res<PlayArea>,
+    difficulty: Res<Difficulty>,
+    mut rng: ResMut<GameRng>,
+) {
+    for _event in events.read() {
+        info!("upgrade");
+        for mut laser in laser_query.iter_mut() {
+            if laser.0 < u128::MAX {
+                laser.0 += 1;
+            }
+        }
+        //add health
+        for mut ship in ship_query.iter_mut() {
+            ship.0 += 1;
+        }
+        asteroid_events.send(SpawnAsteroidEvent::from_edge(
+            &play_area,
+            15.0 * difficulty.asteroid_speed(),
+            &mut rng.0,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Ships firing their weapons into lasers, one frame per update.
+    fn weapon_app() -> App {
+        let mut app = App::new();
+        //the laser spawner loads its sprite
+        app.add_plugins((TaskPoolPlugin::default(), AssetPlugin::default()))
+            .init_asset::<Image>()
+            .init_resource::<Time>()
+            .init_resource::<MuzzleSettings>()
+            .init_resource::<LaserPool>()
+            .init_resource::<LaserPiercing>()
+            .init_resource::<AimMode>()
+            .init_resource::<AimTarget>()
+            .init_resource::<VisualStyle>()
+            .insert_resource(AdditiveAssets {
+                laser_mesh: Mesh2dHandle(Handle::default()),
+                laser_material: Handle::default(),
+                flash_mesh: Mesh2dHandle(Handle::default()),
+                flash_material: Handle::default(),
+            })
+            .add_event::<InputAction>()
+            .add_event::<SpawnLaserEvent>()
+            .add_event::<SpawnMissileEvent>()
+            .add_systems(
+                Update,
+                (
+                    apply_rate_of_fire,
+                    tick_cooldowns::<Laser>,
+                    update_weapons,
+                    laser_spawner,
+                )
+                    .chain(),
+            );
+        app
+    }
+
+    /// A ship at the origin carrying `weapon`, returns the weapon.
+    fn arm(app: &mut App, weapon: LaserWeaponBundle) -> Entity {
+        let ship = app
+            .world
+            .spawn((Ship, Transform::default(), LinearVelocity::default()))
+            .id();
+        let weapon = app.world.spawn(weapon).id();
+        app.world.entity_mut(ship).add_child(weapon);
+        weapon
+    }
+
+    /// Runs a frame `delta` long with fire held or not, returns the bolts it fired.
+    fn frame(app: &mut App, delta: Duration, fire: bool) -> Vec<SpawnLaserEvent> {
+        app.world.resource_mut::<Time>().advance_by(delta);
+        if fire {
+            app.world.send_event(InputAction::Fire);
+        }
+        app.update();
+        app.world
+            .resource_mut::<Events<SpawnLaserEvent>>()
+            .drain()
+            .collect()
+    }
+
+    fn shots_in_a_second(rate: f32) -> usize {
+        let mut app = weapon_app();
+        arm(
+            &mut app,
+            LaserWeaponBundle {
+                rate_of_fire: RateOfFire(rate),
+                ..default()
+            },
+        );
+        //the first frame takes no time, so the second is up at 990ms
+        let mut shots = frame(&mut app, Duration::ZERO, true).len();
+        for _ in 1..100 {
+            shots += frame(&mut app, Duration::from_millis(10), true).len();
+        }
+        shots
+    }
+
+    #[test]
+    fn rate_of_fire_sets_how_often_the_gun_fires() {
+        assert_eq!(shots_in_a_second(60.0), 1);
+        assert_eq!(shots_in_a_second(600.0), 10);
+    }
+
+    #[test]
+    fn two_weapons_on_one_ship_fire_at_their_own_rates() {
+        let mut app = weapon_app();
+        let gun = arm(
+            &mut app,
+            LaserWeaponBundle {
+                rate_of_fire: RateOfFire(600.0),
+                ..default()
+            },
+        );
+        //a quarter of 240 rounds a minute, once a second
+        let launcher = app
+            .world
+            .spawn(LaserWeaponBundle {
+                weapon: Weapon::Secondary,
+                kind: WeaponKind::Missile,
+                rate_of_fire: RateOfFire(240.0),
+                ..default()
+            })
+            .id();
+        let ship = ship_of(&app, gun);
+        app.world.entity_mut(ship).add_child(launcher);
+
+        let mut bolts = 0;
+        let mut missiles = 0;
+        for n in 0..100 {
+            let delta = if n == 0 { 0 } else { 10 };
+            bolts += frame(&mut app, Duration::from_millis(delta), true).len();
+            missiles += app
+                .world
+                .resource_mut::<Events<SpawnMissileEvent>>()
+                .drain()
+                .count();
+        }
+        assert_eq!(bolts, 10);
+        assert_eq!(missiles, 1);
+    }
+
+    #[test]
+    fn spread_fans_one_volley_three_ways() {
+        let mut app = weapon_app();
+        arm(
+            &mut app,
+            LaserWeaponBundle {
+                kind: WeaponKind::Spread,
+                ..default()
+            },
+        );
+        let fired = frame(&mut app, Duration::ZERO, true);
+
+        let angles: Vec<f32> = fired
+            .iter()
+            .map(|event| event.spread.0.to_degrees())
+            .collect();
+        assert_eq!(angles.len(), 3);
+        for (angle, expected) in angles.iter().zip([-15.0, 0.0, 15.0]) {
+            assert!((angle - expected).abs() < 0.001, "{:?}", angles);
+        }
+    }
+
+    #[test]
+    fn a_full_charge_fires_a_piercing_heavy_bolt() {
+        let mut app = weapon_app();
+        arm(
+            &mut app,
+            LaserWeaponBundle {
+                kind: WeaponKind::Charge,
+                ..default()
+            },
+        );
+        //holding fire for a second only winds it up
+        for _ in 0..4 {
+            let fired = frame(&mut app, Duration::from_millis(250), true);
+            assert!(fired.is_empty());
+        }
+        let fired = frame(&mut app, Duration::from_millis(10), false);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].charge, 1.0);
+
+        let mut lasers = app.world.query::<(&Damage, Option<&Piercing>)>();
+        let (damage, piercing) = lasers.single(&app.world);
+        assert_eq!(damage.0, 5);
+        assert!(piercing.is_some_and(|piercing| piercing.0 > 0));
+    }
+
+    #[test]
+    fn with_laser_piercing_even_a_plain_bolt_pierces() {
+        let mut app = weapon_app();
+        app.insert_resource(LaserPiercing(1));
+        arm(&mut app, LaserWeaponBundle::default());
+        let fired = frame(&mut app, Duration::from_millis(10), true);
+        assert_eq!(fired.len(), 1);
+
+        let mut lasers = app.world.query_filtered::<Option<&Piercing>, With<Laser>>();
+        let piercing = lasers.single(&app.world);
+        assert_eq!(piercing.map(|piercing| piercing.0), Some(1));
+    }
+
+    #[test]
+    fn moving_and_firing_only_ticks_the_cooldown_once() {
+        let mut app = weapon_app();
+        let weapon = arm(&mut app, LaserWeaponBundle::default());
+        app.update();
+        //still cooling down, so this frame only ticks it
+        assert!(app
+            .world
+            .get_mut::<Cooldown<Laser>>(weapon)
+            .unwrap()
+            .trigger());
+
+        app.world.send_event(InputAction::Move(Vec2::X));
+        let fired = frame(&mut app, Duration::from_millis(10), true);
+        assert!(fired.is_empty());
+        let cooldown = app.world.get::<Cooldown<Laser>>(weapon).unwrap();
+        assert_eq!(cooldown.elapsed(), Duration::from_millis(10));
+    }
+
+    /// The ship `weapon` is mounted on.
+    fn ship_of(app: &App, weapon: Entity) -> Entity {
+        app.world.get::<Parent>(weapon).unwrap().get()
+    }
+
+    #[test]
+    fn a_ship_turned_a_quarter_left_fires_left() {
+        let mut app = weapon_app();
+        let weapon = arm(&mut app, LaserWeaponBundle::default());
+        let ship = ship_of(&app, weapon);
+        app.world.get_mut::<Transform>(ship).unwrap().rotation = Quat::from_rotation_z(PI / 2.0);
+        frame(&mut app, Duration::ZERO, true);
+
+        let mut lasers = app.world.query_filtered::<&LinearVelocity, With<Laser>>();
+        let direction = lasers.single(&app.world).0.normalize();
+        assert!(direction.distance(Vec2::NEG_X) < 0.001, "{:?}", direction);
+    }
+
+    #[test]
+    fn a_moving_ship_adds_its_velocity_to_the_bolt() {
+        let mut app = weapon_app();
+        let weapon = arm(&mut app, LaserWeaponBundle::default());
+        let ship = ship_of(&app, weapon);
+        app.world.get_mut::<LinearVelocity>(ship).unwrap().0 = Vec2::new(100.0, 0.0);
+        frame(&mut app, Duration::ZERO, true);
+
+        //facing up at the plain bolt speed, plus the ship's drift to the right
+        let mut lasers = app.world.query_filtered::<&LinearVelocity, With<Laser>>();
+        let velocity = lasers.single(&app.world).0;
+        assert!(
+            velocity.distance(Vec2::new(100.0, 500.0)) < 0.001,
+            "{:?}",
+            velocity
+        );
+    }
+
+    #[test]
+    fn the_projectile_preview_follows_the_bolt_a_moving_ship_fires() {
+        let mut app = weapon_app();
+        let weapon = arm(&mut app, LaserWeaponBundle::default());
+        let ship = ship_of(&app, weapon);
+        let drift = Vec2::new(100.0, -50.0);
+        app.world.get_mut::<LinearVelocity>(ship).unwrap().0 = drift;
+        frame(&mut app, Duration::ZERO, true);
+        let mut lasers = app.world.query_filtered::<&LinearVelocity, With<Laser>>();
+        let fired = lasers.single(&app.world).0;
+
+        //the ship faces up, so the preview leaves along +y
+        let velocity = bolt_velocity(Vec2::Y, LASER_SPEED, drift);
+        assert!(velocity.distance(fired) < 0.001, "{:?}", velocity);
+        let path = projectile_path(Vec2::ZERO, velocity, 0.6, 12);
+        assert_eq!(path.len(), 13);
+        assert_eq!(path[0], Vec2::ZERO);
+        for step in path.windows(2) {
+            assert!((step[1] - step[0]).distance(fired * 0.05) < 0.001);
+        }
+        //bent off the straight ray by the ship's drift
+        assert!(
+            path[12].distance(Vec2::new(60.0, 270.0)) < 0.001,
+            "{:?}",
+            path[12]
+        );
+    }
+
+    #[test]
+    fn bolts_leave_from_the_nose() {
+        let mut app = weapon_app();
+        arm(&mut app, LaserWeaponBundle::default());
+        frame(&mut app, Duration::ZERO, true);
+
+        let distance = MuzzleSettings::default().distance;
+        let mut lasers = app.world.query_filtered::<&Transform, With<Laser>>();
+        let position = lasers.single(&app.world).translation.xy();
+        assert!(position.y > 0.0);
+        assert!(position.distance(Vec2::new(0.0, distance)) < 0.001);
+    }
+
+    #[test]
+    fn every_bolt_gets_a_short_muzzle_flash() {
+        let mut app = weapon_app();
+        app.add_systems(Update, spawn_muzzle_flashes);
+        let origin = Transform::from_xyz(0.0, 38.0, 0.0);
+        app.world.send_event(SpawnLaserEvent::plain(origin));
+        app.update();
+
+        let mut flashes = app.world.query::<(&Lifetime, &Transform, &MuzzleFlash)>();
+        let (lifetime, transform, _) = flashes.single(&app.world);
+        assert!(lifetime.0.duration() < Duration::from_millis(100));
+        assert_eq!(transform.translation, origin.translation);
+    }
+
+    #[test]
+    fn a_bomb_finishes_every_asteroid_on_screen_and_is_used_up() {
+        let mut app = App::new();
+        app.init_resource::<Bombs>()
+            .init_resource::<PlayArea>()
+            .init_resource::<ExplosionSettings>()
+            .add_event::<InputAction>()
+            .add_event::<ScreenClearEvent>()
+            .add_event::<DamageEvent>()
+            .add_event::<SpawnExplosionEvent>()
+            .add_event::<crate::asteroids::AsteroidHitEvent>()
+            .add_event::<crate::ship::PlayerDamagedEvent>()
+            .add_event::<crate::shield::ShieldBrokenEvent>()
+            .add_systems(Update, (use_bomb, clear_screen, apply_damage).chain());
+        app.world
+            .spawn((Ship, Cooldown::<Bomb>::new(Duration::from_secs(1))));
+        let asteroids = [
+            (Vec3::new(-200.0, 100.0, 0.0), 5),
+            (Vec3::new(300.0, -50.0, 0.0), 2),
+        ]
+        .map(|(position, health)| {
+            app.world
+                .spawn((
+                    AsteroidHealth(health),
+                    Transform::from_translation(position),
+                ))
+                .id()
+        });
+        app.world.send_event(InputAction::Bomb);
+        app.update();
+
+        for asteroid in asteroids {
+            assert!(app.world.get::<AsteroidHealth>(asteroid).unwrap().0 <= 0);
+        }
+        assert_eq!(app.world.resource::<Bombs>().0, Bombs::default().0 - 1);
+    }
+
+    #[test]
+    fn the_plugin_registers_its_own_events() {
+        let mut app = App::new();
+        //the additive material is an asset
+        app.add_plugins((
+            TaskPoolPlugin::default(),
+            AssetPlugin::default(),
+            WeaponsPlugin,
+        ));
+        assert!(app.world.contains_resource::<Events<SpawnLaserEvent>>());
+    }
+
+    #[test]
+    fn muzzles_turn_with_the_ship() {
+        let muzzles = MuzzlePoints(vec![Vec2::new(-10.0, 0.0), Vec2::new(10.0, 0.0)]);
+        //facing left, so the ship's up is the world's -x and its right is the world's +y
+        let transform =
+            Transform::from_xyz(100.0, 50.0, 0.0).with_rotation(Quat::from_rotation_z(PI / 2.0));
+        let positions: Vec<Vec2> = muzzles.world_positions(&transform, 38.0).collect();
+        assert_eq!(positions.len(), 2);
+        assert!(positions[0].distance(Vec2::new(62.0, 40.0)) < 0.001);
+        assert!(positions[1].distance(Vec2::new(62.0, 60.0)) < 0.001);
+    }
+
+    #[test]
+    fn fast_lasers_leave_a_trail_and_slow_ones_do_not() {
+        let mut app = App::new();
+        app.init_resource::<TrailSettings>()
+            .add_systems(Update, spawn_laser_trails);
+        let laser = |speed: f32| {
+            (
+                Laser,
+                Transform::default(),
+                LinearVelocity(Vec2::Y * speed),
+                Sprite::default(),
+                Handle::<Image>::default(),
+            )
+        };
+        let min_speed = TrailSettings::default().min_speed;
+        app.world.spawn(laser(min_speed * 2.0));
+        app.world.spawn(laser(min_speed / 2.0));
+
+        //one copy a frame for the fast one only
+        for _ in 0..3 {
+            app.update();
+        }
+        let mut trails = app.world.query_filtered::<&Sprite, With<LaserTrail>>();
+        let alphas: Vec<f32> = trails.iter(&app.world).map(|s| s.color.a()).collect();
+        assert_eq!(alphas.len(), 3);
+        assert!(alphas.iter().all(|&alpha| alpha < 1.0));
+
+        app.world.resource_mut::<TrailSettings>().enabled = false;
+        app.update();
+        assert_eq!(trails.iter(&app.world).count(), 3);
+    }
+
+    #[test]
+    fn a_spent_laser_is_fired_again_instead_of_a_new_one() {
+        let mut app = weapon_app();
+        app.add_event::<crate::lifetime::LifetimeExpiredEvent>()
+            .add_systems(
+                Update,
+                (crate::lifetime::update_lifetimes, despawn_expired).chain(),
+            )
+            .add_systems(PostUpdate, recycle_lasers);
+        let mut lasers = app.world.query_filtered::<Entity, With<Laser>>();
+
+        app.world
+            .send_event(SpawnLaserEvent::plain(Transform::default()));
+        app.update();
+        let first = lasers.single(&app.world);
+
+        //long past its lifetime, so it is parked
+        app.world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs(6));
+        app.update();
+        assert!(app.world.get::<Pooled>(first).is_some());
+
+        app.world.resource_mut::<Time>().advance_by(Duration::ZERO);
+        app.world
+            .send_event(SpawnLaserEvent::plain(Transform::default()));
+        app.update();
+        assert_eq!(lasers.iter(&app.world).collect::<Vec<_>>(), vec![first]);
+        assert!(app.world.get::<Pooled>(first).is_none());
+        assert_ne!(
+            app.world.get::<Visibility>(first),
+            Some(&Visibility::Hidden)
+        );
+    }
+}